@@ -16,26 +16,31 @@ _theca() {
 	COMPREPLY=()
 	cmd="${COMP_WORDS[1]}"
 	cur="${COMP_WORDS[COMP_CWORD]}"
-	commands="add edit del clear transfer import search info new-profile encrypt-profile decrypt-profile list-profiles --help --version"
+	commands="add edit del clear transfer import search pick replace undo info links ready graph attach attachments extract verify check uncheck recurring archive unarchive pin unpin move start stop timesheet templates today journal view new-profile encrypt-profile decrypt-profile list-profiles --help --version"
 	global_opts="--profile --profile-folder --encrypted --key"
 
 	case "${cmd}" in
 		add)
 			COMPREPLY=( $(compgen -W \
-        		"${global_opts} --started --urgent --body --editor - --yes" -- $cur) )
+        		"${global_opts} --started --urgent --priority --parent --blocked-by --every --due --set --template --body --editor - --yes" -- $cur) )
         	return 0
 			;;
 		edit)
 			COMPREPLY=( $(compgen -W \
-        		"${global_opts} --started --urgent --none --body --editor - --yes" -- $cur) )
+        		"${global_opts} --started --urgent --none --done --priority --parent --blocked-by --unblock --every --due --set --body --editor - --yes --where" -- $cur) )
         	return 0
 			;;
 		search)
 			COMPREPLY=( $(compgen -W \
-        		"${global_opts} --search-body --regex --limit --reverse --datesort --json --condensed" -- $cur) )
+        		"${global_opts} --search-body --everywhere --ignore-case --smart-case --word --regex --query --rank --fuzzy --all-profiles --skip-encrypted --context --limit --reverse --datesort --manual --sort --prioritysort --priority --json --condensed --relative --archived --all --where --columns" -- $cur) )
         	return 0
 			;;
-		del|clear|transfer|import|new-profile)
+		del|transfer|import|archive|unarchive)
+			COMPREPLY=( $(compgen -W \
+        		"${global_opts} --yes --where --archived --all" -- $cur) )
+        	return 0
+			;;
+		clear|new-profile|attach|extract|verify|pin|unpin)
 			COMPREPLY=( $(compgen -W \
         		"${global_opts} --yes" -- $cur) )
         	return 0
@@ -55,11 +60,88 @@ _theca() {
 				"${global_opts}"))
 			return 0
 			;;
-		info)
+		start)
+			COMPREPLY=( $(compgen -W \
+        		"${global_opts} --concurrent" -- $cur) )
+        	return 0
+			;;
+		stop)
 			COMPREPLY=( $(compgen -W \
         		"${global_opts}" -- $cur) )
         	return 0
 			;;
+		timesheet)
+			COMPREPLY=( $(compgen -W \
+        		"${global_opts} --week --csv --json" -- $cur) )
+        	return 0
+			;;
+		replace)
+			COMPREPLY=( $(compgen -W \
+        		"${global_opts} --regex --in --dry-run --archived --all --where --yes" -- $cur) )
+        	return 0
+			;;
+		pick)
+			COMPREPLY=( $(compgen -W \
+        		"${global_opts} --archived --all" -- $cur) )
+        	return 0
+			;;
+		today)
+			COMPREPLY=( $(compgen -W \
+        		"${global_opts} --journal --body --editor - --yes --json --condensed" -- $cur) )
+        	return 0
+			;;
+		journal)
+			COMPREPLY=( $(compgen -W \
+        		"${global_opts} --journal --from --to --json" -- $cur) )
+        	return 0
+			;;
+		view)
+			local views
+			views=$(theca view 2>/dev/null | cut -d: -f1)
+			COMPREPLY=( $(compgen -W \
+        		"${global_opts} save remove ${views}" -- $cur) )
+        	return 0
+			;;
+		templates)
+			COMPREPLY=( $(compgen -W \
+        		"${global_opts} list show edit" -- $cur) )
+        	return 0
+			;;
+		move)
+			COMPREPLY=( $(compgen -W \
+        		"${global_opts} --before" -- $cur) )
+        	return 0
+			;;
+		ready)
+			COMPREPLY=( $(compgen -W \
+        		"${global_opts} --limit --reverse --datesort --manual --sort --json --condensed --relative" -- $cur) )
+        	return 0
+			;;
+		check|uncheck)
+			COMPREPLY=( $(compgen -W \
+        		"${global_opts} --auto-done" -- $cur) )
+        	return 0
+			;;
+		graph|attachments|undo)
+			COMPREPLY=( $(compgen -W \
+        		"${global_opts}" -- $cur) )
+        	return 0
+			;;
+		recurring)
+			COMPREPLY=( $(compgen -W \
+        		"${global_opts} --json --relative" -- $cur) )
+        	return 0
+			;;
+		links)
+			COMPREPLY=( $(compgen -W \
+        		"${global_opts} --broken" -- $cur) )
+        	return 0
+			;;
+		info)
+			COMPREPLY=( $(compgen -W \
+        		"${global_opts} --archived --all" -- $cur) )
+        	return 0
+			;;
 		help|version)
 			return 0
 			;;
@@ -67,13 +149,13 @@ _theca() {
 
 	if [[ "${cmd}" =~ "^[0-9]+$" ]]; then
 		COMPREPLY=( $(compgen -W \
-    		"${global_opts} --json --condensed" -- $cur) )
+    		"${global_opts} --json --condensed --relative" -- $cur) )
     	return 0
 	fi
 
 	if [ ${COMP_CWORD} -eq 1 ]; then
         COMPREPLY=( $(compgen -W \
-        	"${commands} --help --version --limit --reverse --datesort --json --condensed" -- $cur) )
+        	"${commands} --help --version --limit --reverse --datesort --manual --sort --prioritysort --priority --json --condensed --relative --collapse --archived --all --auto-archive --where --columns" -- $cur) )
         return 0
     fi
 } &&
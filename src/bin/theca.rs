@@ -18,7 +18,9 @@ extern crate docopt;
 use docopt::Docopt;
 use theca::{Args, ThecaProfile, setup_args, parse_cmds, version};
 use theca::errors::{ThecaError};
-use std::env::{set_exit_status};
+use theca::profiles::{expand_note_refs, search_profiles};
+use theca::views::{quote_view_args, view_argv, check_view, view_args};
+use std::env::{args, set_exit_status};
 
 static USAGE: &'static str = "
 theca - simple cli note taking tool
@@ -29,15 +31,52 @@ Usage:
     theca [options] decrypt-profile
     theca [options] list-profiles
     theca [options] info
+    theca [options] links [--broken]
+    theca [options] ready
+    theca [options] graph
+    theca [options] recurring
     theca [options] clear
     theca [options] [-s|-u|-n]
     theca [options] <id>
-    theca [options] search [--regex, --search-body] [-s|-u|-n] <pattern>
-    theca [options] transfer <id> to <name>
-    theca [options] import <id> from <name>
-    theca [options] add <title> [-s|-u] [-b BODY|-t|-]
-    theca [options] edit <id> [<title>] [-s|-u|-n] [-b BODY|-t|-]
-    theca [options] del <id>...
+    theca [options] search [--regex, --query, --rank, --fuzzy, --search-body]
+                           [-i|-S] [-w] [--everywhere] [--all-profiles [--skip-encrypted]]
+                           [-C N] [-s|-u|-n] <pattern>
+    theca [options] pick [<pattern>]
+    theca [options] replace <pattern> <replacement> [--regex] [--in WHERE] [--dry-run]
+    theca [options] undo
+    theca [options] transfer (<ids>|--where QUERY) to <name>
+    theca [options] import (<ids>|--where QUERY) from <name>
+    theca [options] add <title> [-s|-u] [-P PRIORITY] [--parent ID] [--blocked-by ID]
+                                 [--every SCHEDULE] [--due DATE] [--set FIELD]...
+                                 [--template NAME] [-b BODY|-t|-]
+    theca [options] edit (<ids>|--where QUERY) [<title>] [-s|-u|-n|-D] [-P PRIORITY]
+                                  [--parent ID] [--blocked-by ID] [--unblock ID]
+                                  [--every SCHEDULE] [--due DATE] [--set FIELD]...
+                                  [-b BODY|-t|-]
+    theca [options] del (<ids>...|--where QUERY)
+    theca [options] archive (<ids>...|--where QUERY)
+    theca [options] unarchive (<ids>...|--where QUERY)
+    theca [options] pin <id>...
+    theca [options] unpin <id>...
+    theca [options] move <id> --before ID
+    theca [options] start <id> [--concurrent]
+    theca [options] stop [<id>]
+    theca [options] timesheet [--week] [--csv]
+    theca [options] templates [list]
+    theca [options] templates show <template>
+    theca [options] templates edit <template>
+    theca [options] today [-b BODY|-t|-]
+    theca [options] journal [--from DATE] [--to DATE]
+    theca [options] view
+    theca [options] view save <view> [--] <args>...
+    theca [options] view remove <view>
+    theca [options] view <view>
+    theca [options] attach <id> <file>
+    theca [options] attachments <id>
+    theca [options] extract <id> <attachment>
+    theca [options] verify
+    theca [options] check <id> <item>
+    theca [options] uncheck <id> <item>
 
 Profiles:
     -f PATH, --profile-folder PATH      Path to folder containing profile.json
@@ -50,12 +89,23 @@ Profiles:
 Printing format:
     -c, --condensed                     Use the condensed printing format.
     -j, --json                          Print list output as a JSON object.
+    --relative                          Print last touched dates relative to
+                                        now (e.g. "3h ago") [default can be
+                                        set with env var THECA_RELATIVE_DATES].
 
 Note list formatting:
     -l LIMIT, --limit LIMIT             Limit output to LIMIT notes
                                         [default: 0].
     -d, --datesort                      Sort notes by date.
+    --manual                            Sort notes by the order set with
+                                        `move`.
+    --sort KEYS                         Sort notes by a comma separated list
+                                        of fields, -field sorts it in
+                                        descending order, e.g.
+                                        status,-due,title.
     -r, --reverse                       Reverse list.
+    --collapse                          Only list top level notes with a count
+                                        of their subtasks.
 
 Input:
     -y, --yes                           Silently agree to any [y/n] prompts.
@@ -64,6 +114,88 @@ Statuses:
     -n, --none                          No status. (note default)
     -s, --started                       Started status.
     -u, --urgent                        Urgent status.
+    -D, --done                          Done status, a note can't be done while
+                                        it is blocked by notes that aren't.
+
+Priorities:
+    -P PRIORITY, --priority PRIORITY    Set the priority of a note (a letter
+                                        A-Z or a number, lower is more
+                                        important, none clears it), when
+                                        listing or searching only show notes
+                                        with PRIORITY (or a range like A-C).
+    --prioritysort                      Sort notes by priority.
+
+Subtasks:
+    --parent ID                         Make the note a subtask of note ID
+                                        [default: 0].
+
+Dependencies:
+    --blocked-by ID                     Mark the note as blocked by note ID
+                                        [default: 0].
+    --unblock ID                        Stop the note being blocked by note ID
+                                        [default: 0].
+
+Checklists:
+    --auto-done                         Mark a note as done once every item
+                                        in its checklist is checked [default
+                                        can be set with env var
+                                        THECA_AUTO_DONE].
+
+Recurring:
+    --every SCHEDULE                    Add the next note in the series when
+                                        the note is done, SCHEDULE is an
+                                        interval (1d, 1w, 3mo, 1y) or a cron
+                                        line ("0 9 * * 1"), never stops it.
+    --due DATE                          Set when the note is due, DATE is
+                                        YYYY-MM-DD or from now (3d, 2w).
+
+Fields:
+    --set FIELD                         Set a custom field on the note,
+                                        FIELD is name=value or
+                                        name:type=value (string, int, date,
+                                        duration or bool), an empty value
+                                        removes the field.
+    --where QUERY                       Only list/search/change notes matching
+                                        QUERY, e.g. "status:started AND
+                                        (title:/deploy/ OR body:rollback)"
+                                        or "estimate>1h,customer=acme".
+    --columns FIELDS                    Comma separated custom fields to show
+                                        as columns when listing notes.
+
+Templates:
+    --template NAME                     Prefill the new note from the
+                                        template NAME kept with the profile.
+
+Journal:
+    --journal PROFILE                   Profile used by today and journal
+                                        [default can be set with env var
+                                        THECA_JOURNAL_PROFILE, otherwise
+                                        journal].
+    --from DATE                         Only print journal days from DATE
+                                        (YYYY-MM-DD, today, yesterday or
+                                        something like 1w ago).
+    --to DATE                           Only print journal days up to DATE.
+
+Time tracking:
+    --concurrent                        Keep any other running timers going
+                                        when starting one.
+    --week                              Only count time logged since monday.
+    --csv                               Print the timesheet as CSV.
+
+Ordering:
+    --before ID                         Note to move the note in front of
+                                        [default: 0].
+
+Archive:
+    --archived                          Only list, search and count archived
+                                        notes.
+    --all                               Include archived notes when listing,
+                                        searching and counting.
+    --auto-archive DAYS                 Archive notes that have been done for
+                                        more than DAYS days whenever the
+                                        profile is saved, can be set with env
+                                        var THECA_AUTO_ARCHIVE_DAYS
+                                        [default: 0].
 
 Body:
     -b BODY, --body BODY                Set body of the note to BODY.
@@ -83,21 +215,65 @@ Encryption:
 Search:
     --search-body                       Search the note bodies instead of
                                         titles.
+    --everywhere                        Search both the titles and bodies.
+    -i, --ignore-case                   Match regardless of case.
+    -S, --smart-case                    Match regardless of case unless the
+                                        pattern has an upper case letter.
+    -w, --word                          Only match whole words.
 
     --regex                             Set search pattern to regex (default
                                         is keyword).
+    -q, --query                         Set search pattern to a query, like
+                                        --where.
+    --rank                              Order results by relevance using the
+                                        full text index and show a snippet
+                                        of each.
+    --fuzzy                             Match the pattern loosely against
+                                        titles and bodies, allowing skipped
+                                        characters and small typos, best
+                                        matches first.
+    --all-profiles                      Search every profile in the profile
+                                        folder, results are grouped by
+                                        profile.
+    --skip-encrypted                    Leave encrypted profiles out of
+                                        --all-profiles instead of asking for
+                                        their key.
+    -C N, --context N                   Print N lines of the body around each
+                                        match with --search-body [default: 0].
+
+Replace:
+    --in WHERE                          Replace in the title, body or both of
+                                        each note [default: both].
+    --dry-run                           Print what would change without
+                                        changing anything.
+
+Links:
+    --broken                            Only list links to notes that don't
+                                        exist.
 
 Miscellaneous:
     -h, --help                          Display this help and exit.
     -v, --version                       Display the version of theca and exit.
 ";
 
+fn decode(argv: Vec<String>) -> Result<Args, ThecaError> {
+    Ok(try!(Docopt::new(USAGE).unwrap()
+                              .argv(argv.into_iter())
+                              .version(Some(version()))
+                              .decode()))
+}
+
 fn theca_main() -> Result<(), ThecaError> {
-    let mut args: Args = try!(Docopt::new(USAGE).unwrap()
-                                                .version(Some(version()))
-                                                .decode());
+    // `profile:id` is turned into `--profile profile id` before parsing
+    let argv = try!(expand_note_refs(quote_view_args(args().collect()), USAGE));
+    let mut args = try!(decode(argv));
     try!(setup_args(&mut args));
 
+    // searching every profile doesn't load the current one
+    if args.cmd_search && args.flag_all_profiles {
+        return search_profiles(&args)
+    }
+
     let (mut profile, profile_fingerprint) = try!(ThecaProfile::new(
         &args.flag_profile,
         &args.flag_profile_folder,
@@ -107,6 +283,16 @@ fn theca_main() -> Result<(), ThecaError> {
         args.flag_yes
     ));
 
+    // views are kept as the arguments they were saved with, which are
+    // checked when saving and parsed again when the view is used
+    if args.cmd_view && args.cmd_save {
+        try!(check_view(&try!(decode(view_argv(&args.arg_args)))));
+    } else if args.cmd_view && !args.cmd_remove && !args.arg_view.is_empty() {
+        let saved = try!(profile.saved_view(&args.arg_view)).args.clone();
+        args = view_args(try!(decode(view_argv(&saved))), &args);
+        try!(setup_args(&mut args));
+    }
+
     try!(parse_cmds(&mut profile, &mut args, &profile_fingerprint));
 
     Ok(())
@@ -0,0 +1,53 @@
+//  _   _                    
+// | |_| |__   ___  ___ __ _ 
+// | __| '_ \ / _ \/ __/ _` |
+// | |_| | | |  __/ (_| (_| |
+//  \__|_| |_|\___|\___\__,_|
+//
+// licensed under the MIT license <http://opensource.org/licenses/MIT>
+//
+// archive.rs
+//   helpers for archived notes, which are kept in the profile but hidden
+//   from listing, searching and the profile info unless asked for.
+
+use time::{Tm, Duration};
+
+use ::{ThecaItem, DONE};
+use errors::{ThecaError};
+use utils::{parse_last_touched};
+
+/// has the note been archived?
+pub fn is_archived(note: &ThecaItem) -> bool {
+    note.archived.is_some()
+}
+
+/// should the note be shown, by default only notes that aren't archived
+/// are, `archived` shows only archived notes and `all` shows everything
+pub fn shown(note: &ThecaItem, archived: bool, all: bool) -> bool {
+    all || is_archived(note) == archived
+}
+
+/// the notes that should be shown (see `shown`)
+pub fn visible(notes: &[ThecaItem], archived: bool, all: bool) -> Vec<ThecaItem> {
+    notes.iter()
+         .filter(|n| shown(n, archived, all))
+         .map(|n| n.clone())
+         .collect()
+}
+
+/// ids of the notes that aren't archived yet but have been done (and not
+/// touched since) for more than `days` days at `now`
+pub fn stale_done(
+    notes: &[ThecaItem],
+    days: usize,
+    now: &Tm
+) -> Result<Vec<usize>, ThecaError> {
+    let cutoff = now.to_timespec() - Duration::days(days as i64);
+    let mut stale: Vec<usize> = vec![];
+    for n in notes.iter().filter(|n| n.status == DONE && !is_archived(n)) {
+        if try!(parse_last_touched(&*n.last_touched)).to_timespec() < cutoff {
+            stale.push(n.id);
+        }
+    }
+    Ok(stale)
+}
@@ -0,0 +1,161 @@
+//  _   _                    
+// | |_| |__   ___  ___ __ _ 
+// | __| '_ \ / _ \/ __/ _` |
+// | |_| | | |  __/ (_| (_| |
+//  \__|_| |_|\___|\___\__,_|
+//
+// licensed under the MIT license <http://opensource.org/licenses/MIT>
+//
+// attachments.rs
+//   the content-addressed store that files attached to notes are kept in,
+//   `<profile folder>/<profile>.attachments/<sha256>`.
+
+use std::fs::{PathExt, File, create_dir, read_dir};
+use std::io::{Read, Write};
+use std::path::{Path, PathBuf};
+
+use crypto::sha2::{Sha256};
+use crypto::digest::{Digest};
+
+use ::{ThecaItem};
+use errors::{ThecaError, GenericError};
+use crypt::{encrypt, decrypt};
+use utils::{find_profile_folder};
+
+/// A file attached to a note
+#[derive(RustcDecodable, RustcEncodable, Clone)]
+pub struct Attachment {
+    pub name: String,
+    pub hash: String,
+    pub size: u64
+}
+
+/// folder the attachments of a profile are stored in
+pub fn store_path(
+    profile_folder: &String,
+    profile_name: &String
+) -> Result<PathBuf, ThecaError> {
+    let mut store = try!(find_profile_folder(profile_folder));
+    store.push(&(profile_name.to_string() + ".attachments"));
+    Ok(store)
+}
+
+/// write a blob to the store (encrypting it first if there is a key) and
+/// return the hash it is stored under, blobs that are already stored
+/// aren't written again
+pub fn store_blob(
+    store: &Path,
+    contents: &[u8],
+    key: Option<&[u8]>
+) -> Result<String, ThecaError> {
+    // encryption is deterministic (the IV is seeded from the data) so the
+    // same file attached twice still ends up as one blob
+    let blob = match key {
+        Some(k) => try!(encrypt(contents, k)),
+        None => contents.to_vec()
+    };
+    let mut sha = Sha256::new();
+    sha.input(&blob);
+    let hash = sha.result_str();
+    if !store.exists() {try!(create_dir(store));}
+    let blob_path = store.join(&hash);
+    if !blob_path.exists() {
+        let mut file = try!(File::create(&blob_path));
+        try!(file.write_all(&blob));
+    }
+    Ok(hash)
+}
+
+/// read a blob back out of the store (decrypting it if there is a key)
+pub fn read_blob(
+    store: &Path,
+    hash: &String,
+    key: Option<&[u8]>
+) -> Result<Vec<u8>, ThecaError> {
+    let blob_path = store.join(hash);
+    if !blob_path.is_file() {
+        specific_fail!(format!("attachment blob {} is missing", hash));
+    }
+    let mut file = try!(File::open(&blob_path));
+    let mut blob: Vec<u8> = vec![];
+    try!(file.read_to_end(&mut blob));
+    match key {
+        Some(k) => Ok(try!(decrypt(&*blob, k))),
+        None => Ok(blob)
+    }
+}
+
+/// store every attachment of the notes again with a different key (or
+/// none), returns the hashes of the blobs that were replaced
+pub fn restore_blobs(
+    store: &Path,
+    notes: &mut Vec<ThecaItem>,
+    from_key: Option<&[u8]>,
+    to_key: Option<&[u8]>
+) -> Result<Vec<String>, ThecaError> {
+    let mut replaced: Vec<String> = vec![];
+    for n in notes.iter_mut() {
+        match n.attachments {
+            Some(ref mut a) => for att in a.iter_mut() {
+                let contents = try!(read_blob(store, &att.hash, from_key));
+                let hash = try!(store_blob(store, &contents, to_key));
+                if hash != att.hash {
+                    if !replaced.contains(&att.hash) {replaced.push(att.hash.clone());}
+                    att.hash = hash;
+                }
+            },
+            None => ()
+        };
+    }
+    Ok(replaced)
+}
+
+/// hashes of every blob referenced by the notes
+pub fn referenced_blobs(notes: &[ThecaItem]) -> Vec<String> {
+    let mut hashes: Vec<String> = vec![];
+    for n in notes.iter() {
+        match n.attachments {
+            Some(ref a) => for att in a.iter() {
+                if !hashes.contains(&att.hash) {hashes.push(att.hash.clone());}
+            },
+            None => ()
+        };
+    }
+    hashes
+}
+
+/// blobs in the store that no note references anymore
+pub fn orphaned_blobs(
+    store: &Path,
+    notes: &[ThecaItem]
+) -> Result<Vec<PathBuf>, ThecaError> {
+    let referenced = referenced_blobs(notes);
+    let mut orphans: Vec<PathBuf> = vec![];
+    if store.is_dir() {
+        for file in try!(read_dir(store)) {
+            let file = try!(file);
+            let name = match file.path().file_name() {
+                Some(n) => n.to_str().unwrap_or("").to_string(),
+                None => continue
+            };
+            if !referenced.contains(&name) {orphans.push(file.path());}
+        }
+    }
+    Ok(orphans)
+}
+
+/// (note id, attachment name) of attachments whose blob is missing
+pub fn missing_blobs(store: &Path, notes: &[ThecaItem]) -> Vec<(usize, String)> {
+    let mut missing: Vec<(usize, String)> = vec![];
+    for n in notes.iter() {
+        match n.attachments {
+            Some(ref a) => for att in a.iter() {
+                if !store.join(&att.hash).is_file() {
+                    missing.push((n.id, att.name.clone()));
+                }
+            },
+            None => ()
+        };
+    }
+    missing
+}
@@ -0,0 +1,99 @@
+//  _   _                    
+// | |_| |__   ___  ___ __ _ 
+// | __| '_ \ / _ \/ __/ _` |
+// | |_| | | |  __/ (_| (_| |
+//  \__|_| |_|\___|\___\__,_|
+//
+// licensed under the MIT license <http://opensource.org/licenses/MIT>
+//
+// checklist.rs
+//   parsing and toggling `- [ ]` / `- [x]` checklist items in note bodies.
+
+use regex::{Regex};
+
+use errors::{ThecaError, GenericError};
+
+fn item_regex() -> Regex {
+    Regex::new(r"^(\s*[-*+]\s+\[)([ xX])(\].*)$").unwrap()
+}
+
+/// (checked, text) of every checklist item in a body
+pub fn items(body: &str) -> Vec<(bool, String)> {
+    let re = item_regex();
+    body.lines().filter_map(|l| match re.captures(l) {
+        Some(caps) => Some((
+            caps.at(2) != Some(" "),
+            caps.at(3).unwrap_or("]")[1..].trim().to_string()
+        )),
+        None => None
+    }).collect()
+}
+
+/// (checked, total) items in a body, None if it doesn't have a checklist
+pub fn progress(body: &str) -> Option<(usize, usize)> {
+    let items = items(body);
+    match items.is_empty() {
+        true => None,
+        false => Some((items.iter().filter(|i| i.0).count(), items.len()))
+    }
+}
+
+/// progress formatted for printing, e.g. "3/7"
+pub fn progress_string(body: &str) -> String {
+    match progress(body) {
+        Some((checked, total)) => format!("{}/{}", checked, total),
+        None => "".to_string()
+    }
+}
+
+/// check (or uncheck) the `n`th (starting at 1) checklist item in a body
+pub fn set_item(body: &str, n: usize, checked: bool) -> Result<String, ThecaError> {
+    let re = item_regex();
+    let mut seen = 0;
+    let mut found = false;
+    let lines: Vec<String> = body.split('\n').map(|l| {
+        // keep any \r around so the line endings come back out the same
+        let (line, cr) = match l.ends_with("\r") {
+            true => (&l[..l.len()-1], "\r"),
+            false => (l, "")
+        };
+        match re.captures(line) {
+            Some(caps) => {
+                seen += 1;
+                match seen == n {
+                    true => {
+                        found = true;
+                        format!(
+                            "{}{}{}{}",
+                            caps.at(1).unwrap_or(""),
+                            match checked {
+                                true => "x",
+                                false => " "
+                            },
+                            caps.at(3).unwrap_or(""),
+                            cr
+                        )
+                    },
+                    false => l.to_string()
+                }
+            },
+            None => l.to_string()
+        }
+    }).collect();
+    if !found {
+        specific_fail!(format!("checklist item {} doesn't exist", n));
+    }
+    Ok(lines.connect("\n"))
+}
+
+/// uncheck every checklist item in a body
+pub fn reset(body: &str) -> String {
+    let mut reset = body.to_string();
+    for n in 1..items(body).len()+1 {
+        reset = match set_item(&*reset, n, false) {
+            Ok(b) => b,
+            Err(_) => break
+        };
+    }
+    reset
+}
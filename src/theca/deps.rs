@@ -0,0 +1,95 @@
+//  _   _                    
+// | |_| |__   ___  ___ __ _ 
+// | __| '_ \ / _ \/ __/ _` |
+// | |_| | | |  __/ (_| (_| |
+//  \__|_| |_|\___|\___\__,_|
+//
+// licensed under the MIT license <http://opensource.org/licenses/MIT>
+//
+// deps.rs
+//   helpers for the `blocked_by` dependencies between notes, used for the
+//   `ready` and `graph` commands.
+
+use ::{ThecaItem, DONE};
+
+/// ids of the notes blocking the note
+pub fn blockers(note: &ThecaItem) -> Vec<usize> {
+    match note.blocked_by {
+        Some(ref b) => b.clone(),
+        None => vec![]
+    }
+}
+
+/// blockers of the note `id` that exist and aren't done yet
+pub fn open_blockers(notes: &[ThecaItem], id: usize) -> Vec<usize> {
+    let note = match notes.iter().find(|n| n.id == id) {
+        Some(n) => n,
+        None => return vec![]
+    };
+    blockers(note).into_iter().filter(|b| {
+        notes.iter().any(|n| &n.id == b && n.status != DONE)
+    }).collect()
+}
+
+/// would making `blocker` block the note `id` create a loop?
+pub fn would_block_cycle(notes: &[ThecaItem], id: usize, blocker: usize) -> bool {
+    if id == blocker {return true;}
+    // walk everything `blocker` (transitively) waits on looking for `id`
+    let mut seen: Vec<usize> = vec![];
+    let mut queue = vec![blocker];
+    while !queue.is_empty() {
+        let next = queue.remove(0);
+        if next == id {return true;}
+        if seen.contains(&next) {continue;}
+        seen.push(next);
+        match notes.iter().find(|n| n.id == next) {
+            Some(n) => queue.push_all(&blockers(n)),
+            None => ()
+        };
+    }
+    false
+}
+
+/// notes that aren't done and aren't waiting on any open notes
+pub fn ready(notes: &[ThecaItem]) -> Vec<ThecaItem> {
+    notes.iter()
+         .filter(|n| n.status != DONE && open_blockers(notes, n.id).is_empty())
+         .map(|n| n.clone())
+         .collect()
+}
+
+/// DOT graph of the dependencies, edges point from a blocker to the note
+/// it blocks
+pub fn to_dot(notes: &[ThecaItem]) -> String {
+    let mut nodes: Vec<usize> = vec![];
+    let mut edges: Vec<(usize, usize)> = vec![];
+    for n in notes.iter() {
+        for b in blockers(n).iter() {
+            if !notes.iter().any(|o| &o.id == b) {continue;}
+            edges.push((*b, n.id));
+            if !nodes.contains(b) {nodes.push(*b);}
+            if !nodes.contains(&n.id) {nodes.push(n.id);}
+        }
+    }
+    nodes.sort();
+    let mut dot = "digraph theca {\n".to_string();
+    for id in nodes.iter() {
+        match notes.iter().find(|n| &n.id == id) {
+            Some(n) => {
+                let label = format!("{}: {}", n.id, n.title).replace("\\", "\\\\")
+                                                             .replace("\"", "\\\"");
+                let style = match n.status == DONE {
+                    true => ", style=dashed",
+                    false => ""
+                };
+                dot.push_str(&*format!("    {} [label=\"{}\"{}];\n", n.id, label, style));
+            },
+            None => ()
+        };
+    }
+    for &(from, to) in edges.iter() {
+        dot.push_str(&*format!("    {} -> {};\n", from, to));
+    }
+    dot.push_str("}");
+    dot
+}
@@ -0,0 +1,131 @@
+//  _   _                    
+// | |_| |__   ___  ___ __ _ 
+// | __| '_ \ / _ \/ __/ _` |
+// | |_| | | |  __/ (_| (_| |
+//  \__|_| |_|\___|\___\__,_|
+//
+// licensed under the MIT license <http://opensource.org/licenses/MIT>
+//
+// fields.rs
+//   custom typed key/value fields on notes (`--set estimate=2h`) and how
+//   their values compare (`--where "estimate>1h"`, see query.rs).
+
+use std::cmp::{Ordering};
+
+use regex::{Regex};
+use time::{strptime};
+
+use ::{ThecaItem};
+use errors::{ThecaError, GenericError};
+use schedule::{parse_duration};
+
+/// the types a field can have
+pub static KINDS: [&'static str; 5] = ["string", "int", "date", "duration", "bool"];
+
+/// A custom field on a note, the value is kept as a string and
+/// interpreted according to its kind
+#[derive(RustcDecodable, RustcEncodable, Clone)]
+pub struct Field {
+    pub name: String,
+    pub kind: String,
+    pub value: String
+}
+
+/// work out the kind of a value that wasn't given one explicitly
+pub fn infer_kind(value: &str) -> &'static str {
+    if value == "true" || value == "false" {
+        "bool"
+    } else if value.parse::<i64>().is_ok() {
+        "int"
+    } else if strptime(value, "%Y-%m-%d").is_ok() {
+        "date"
+    } else if parse_duration(value).is_ok() {
+        "duration"
+    } else {
+        "string"
+    }
+}
+
+/// check a value is valid for `kind`, returning it in its stored form
+pub fn normalize(kind: &str, value: &str) -> Result<String, ThecaError> {
+    let value = value.trim();
+    let normal = match kind {
+        "int" => value.parse::<i64>().ok().map(|i| i.to_string()),
+        "bool" => match value {
+            "true" | "yes" => Some("true".to_string()),
+            "false" | "no" => Some("false".to_string()),
+            _ => None
+        },
+        "date" => strptime(value, "%Y-%m-%d").ok().map(|_| value.to_string()),
+        "duration" => parse_duration(value).ok().map(|_| value.to_string()),
+        "string" => Some(value.to_string()),
+        _ => specific_fail!(format!(
+            "unknown field type: {} (types are {})",
+            kind,
+            KINDS.connect(", ")
+        ))
+    };
+    match normal {
+        Some(n) => Ok(n),
+        None => specific_fail!(format!("'{}' isn't a valid {}", value, kind))
+    }
+}
+
+/// parse a `--set` assignment, `name=value` or `name:kind=value`, an
+/// empty value removes the field (the kind of the field is None)
+pub fn parse_assignment(assignment: &str) -> Result<(String, Option<Field>), ThecaError> {
+    let re = Regex::new(r"^\s*([A-Za-z_][\w-]*)\s*(?::\s*(\w+)\s*)?=(.*)$").unwrap();
+    let caps = match re.captures(assignment) {
+        Some(c) => c,
+        None => specific_fail!(format!(
+            "invalid field: {} (use name=value or name:type=value)",
+            assignment
+        ))
+    };
+    let name = caps.at(1).unwrap_or("").to_string();
+    let value = caps.at(3).unwrap_or("").trim();
+    if value.is_empty() {
+        return Ok((name, None))
+    }
+    let kind = match caps.at(2) {
+        Some(k) => k.to_string(),
+        None => infer_kind(value).to_string()
+    };
+    let normal = try!(normalize(&*kind, value));
+    Ok((name.clone(), Some(Field {name: name, kind: kind, value: normal})))
+}
+
+/// the field `name` on a note, if it has one
+pub fn get_field<'a>(note: &'a ThecaItem, name: &str) -> Option<&'a Field> {
+    match note.fields {
+        Some(ref f) => f.iter().find(|f| f.name == name),
+        None => None
+    }
+}
+
+/// compare the value of a field with `value`, interpreted as the same kind
+pub fn compare(field: &Field, value: &str) -> Result<Ordering, ThecaError> {
+    Ok(match &*field.kind {
+        "int" => {
+            let rhs = try!(normalize("int", value)).parse::<i64>().unwrap();
+            field.value.parse::<i64>().unwrap_or(0).cmp(&rhs)
+        },
+        "duration" => {
+            let rhs = try!(parse_duration(value));
+            try!(parse_duration(&*field.value)).cmp(&rhs)
+        },
+        "date" | "bool" => {
+            let rhs = try!(normalize(&*field.kind, value));
+            field.value.cmp(&rhs)
+        },
+        _ => field.value[..].cmp(value)
+    })
+}
+
+/// the field names given to --columns
+pub fn parse_columns(columns: &str) -> Vec<String> {
+    columns.split(',')
+           .map(|c| c.trim().to_string())
+           .filter(|c| !c.is_empty())
+           .collect()
+}
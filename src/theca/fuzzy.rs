@@ -0,0 +1,178 @@
+//  _   _                    
+// | |_| |__   ___  ___ __ _ 
+// | __| '_ \ / _ \/ __/ _` |
+// | |_| | | |  __/ (_| (_| |
+//  \__|_| |_|\___|\___\__,_|
+//
+// licensed under the MIT license <http://opensource.org/licenses/MIT>
+//
+// fuzzy.rs
+//   fuzzy matching of notes for `search --fuzzy` and `pick`, the pattern
+//   is matched as a subsequence (like fzf) falling back to words within a
+//   small edit distance to catch typos.
+
+use std::cmp::{Ordering, min};
+
+use ::{ThecaItem};
+
+/// score for each matched character and the bonuses on top of it
+static MATCH: i64 = 16;
+static CONSECUTIVE: i64 = 8;
+static WORD_START: i64 = 10;
+/// penalty for skipping characters, and for each one after the first
+static GAP: i64 = 3;
+static GAP_EXTENSION: i64 = 1;
+
+/// lower case a single character (keeping its position in the text)
+fn fold(c: char) -> char {
+    c.to_string().to_lowercase().chars().next().unwrap_or(c)
+}
+
+/// merge the byte ranges of single characters into runs
+fn merge_spans(mut spans: Vec<(usize, usize)>) -> Vec<(usize, usize)> {
+    spans.sort();
+    let mut merged: Vec<(usize, usize)> = vec![];
+    for (s, e) in spans.into_iter() {
+        match merged.last_mut() {
+            Some(last) if last.1 >= s => {
+                if e > last.1 {last.1 = e;}
+                continue;
+            },
+            _ => ()
+        };
+        merged.push((s, e));
+    }
+    merged
+}
+
+/// edit distance between two words
+pub fn levenshtein(a: &[char], b: &[char]) -> usize {
+    let mut previous: Vec<usize> = (0..b.len()+1).collect();
+    for i in 0..a.len() {
+        let mut current = vec![i + 1];
+        for j in 0..b.len() {
+            let cost = match a[i] == b[j] {
+                true => 0,
+                false => 1
+            };
+            let best = min(min(previous[j+1] + 1, current[j] + 1), previous[j] + cost);
+            current.push(best);
+        }
+        previous = current;
+    }
+    previous[b.len()]
+}
+
+/// match a single term (no whitespace) as a subsequence of `text`, trying
+/// each place the first character appears and keeping the best score
+fn subsequence(term: &[char], text: &[(usize, char)]) -> Option<(i64, Vec<usize>)> {
+    let mut best: Option<(i64, Vec<usize>)> = None;
+    for start in 0..text.len() {
+        if fold(text[start].1) != term[0] {continue;}
+        let mut positions = vec![start];
+        let mut t = 1;
+        let mut i = start + 1;
+        while t < term.len() && i < text.len() {
+            if fold(text[i].1) == term[t] {
+                positions.push(i);
+                t += 1;
+            }
+            i += 1;
+        }
+        if t < term.len() {break;}
+        let mut score = 0;
+        for (n, p) in positions.iter().enumerate() {
+            score += MATCH;
+            if *p == 0 || !text[*p - 1].1.is_alphanumeric() {score += WORD_START;}
+            if n > 0 {
+                match positions[n-1] + 1 == *p {
+                    true => score += CONSECUTIVE,
+                    false => score -= GAP + (*p - positions[n-1] - 2) as i64 * GAP_EXTENSION
+                };
+            }
+        }
+        // matches near the start of the text are a little better
+        score -= min(start, 10) as i64;
+        let better = match best {
+            Some((s, _)) => score > s,
+            None => true
+        };
+        if better {best = Some((score, positions));}
+    }
+    best
+}
+
+/// the closest word within a couple of typos of the term
+fn typo(term: &[char], text: &[(usize, char)]) -> Option<(i64, Vec<usize>)> {
+    if term.len() < 4 {
+        return None
+    }
+    let allowed = term.len() / 4;
+    let mut best: Option<(usize, Vec<usize>)> = None;
+    let mut i = 0;
+    while i < text.len() {
+        if !text[i].1.is_alphanumeric() {
+            i += 1;
+            continue;
+        }
+        let start = i;
+        while i < text.len() && text[i].1.is_alphanumeric() {i += 1;}
+        let word: Vec<char> = text[start..i].iter().map(|&(_, c)| fold(c)).collect();
+        let distance = levenshtein(term, &*word);
+        let better = match best {
+            Some((d, _)) => distance < d,
+            None => true
+        };
+        if distance <= allowed && better {
+            best = Some((distance, (start..i).collect()));
+        }
+    }
+    best.map(|(d, positions)| (term.len() as i64 * MATCH / 2 - d as i64 * MATCH, positions))
+}
+
+/// fuzzy match `pattern` against `text`, every whitespace separated term of
+/// the pattern has to match, returns the score and the byte ranges matched
+pub fn fuzzy_match(pattern: &str, text: &str) -> Option<(i64, Vec<(usize, usize)>)> {
+    let chars: Vec<(usize, char)> = text.char_indices().collect();
+    let mut score = 0;
+    let mut spans: Vec<(usize, usize)> = vec![];
+    for term in pattern.split(|c: char| c.is_whitespace()).filter(|t| !t.is_empty()) {
+        let term: Vec<char> = term.chars().map(fold).collect();
+        let found = match subsequence(&*term, &*chars) {
+            Some(m) => Some(m),
+            None => typo(&*term, &*chars)
+        };
+        match found {
+            Some((s, positions)) => {
+                score += s;
+                for p in positions.iter() {
+                    let (i, c) = chars[*p];
+                    spans.push((i, i + c.len_utf8()));
+                }
+            },
+            None => return None
+        };
+    }
+    Some((score, merge_spans(spans)))
+}
+
+/// ids and scores of the notes matching the pattern, best first, bodies
+/// count for half as much as titles
+pub fn rank_notes(notes: &[ThecaItem], pattern: &str) -> Vec<(usize, i64)> {
+    let mut ranked: Vec<(usize, i64)> = vec![];
+    for n in notes.iter() {
+        let title = fuzzy_match(pattern, &*n.title).map(|m| m.0);
+        let body = fuzzy_match(pattern, &*n.body).map(|m| m.0 / 2);
+        match (title, body) {
+            (Some(t), Some(b)) => ranked.push((n.id, if t > b {t} else {b})),
+            (Some(t), None) => ranked.push((n.id, t)),
+            (None, Some(b)) => ranked.push((n.id, b)),
+            (None, None) => ()
+        };
+    }
+    ranked.sort_by(|a, b| match b.1.cmp(&a.1) {
+        Ordering::Equal => a.0.cmp(&b.0),
+        o => o
+    });
+    ranked
+}
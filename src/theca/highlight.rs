@@ -0,0 +1,258 @@
+//  _   _                    
+// | |_| |__   ___  ___ __ _ 
+// | __| '_ \ / _ \/ __/ _` |
+// | |_| | | |  __/ (_| (_| |
+//  \__|_| |_|\___|\___\__,_|
+//
+// licensed under the MIT license <http://opensource.org/licenses/MIT>
+//
+// highlight.rs
+//   where a search matched a note, so the matches can be highlighted,
+//   printed with lines of context around them or given as offsets in JSON.
+
+use regex::{Regex, quote};
+use rustc_serialize::json::{Json, as_json};
+
+use ::{ThecaItem};
+use errors::{ThecaError, GenericError};
+use fuzzy::{fuzzy_match};
+
+/// how a search pattern is matched
+pub enum Pattern {
+    Keyword(String),
+    Regex(Regex),
+    Fuzzy(String)
+}
+
+/// a search pattern, whether it only matches whole words and which parts
+/// of a note it looks at
+pub struct Matcher {
+    pub pattern: Pattern,
+    pub whole_word: bool,
+    pub title: bool,
+    pub body: bool
+}
+
+/// what to highlight in search results and how much of the body to show
+/// around each match
+pub struct Highlight {
+    pub matcher: Matcher,
+    pub context: usize
+}
+
+/// a match in the title or body of a note, `start` and `end` are byte
+/// offsets into the field and `line` counts from 1
+#[derive(RustcEncodable, PartialEq, Debug, Clone)]
+pub struct Match {
+    pub field: String,
+    pub line: usize,
+    pub start: usize,
+    pub end: usize
+}
+
+/// a line of a body printed under a search result
+#[derive(PartialEq, Debug)]
+pub struct ContextLine {
+    pub number: usize,
+    pub text: String,
+    pub spans: Vec<(usize, usize)>
+}
+
+impl Matcher {
+    /// the matcher for a search, queries aren't highlighted since they
+    /// can match on more than the text. `ignore_case` (or `smart_case` with
+    /// a pattern that is all lower case) matches regardless of case, and
+    /// `everywhere` looks at both the title and body
+    pub fn new(
+        pattern: &str,
+        regex: bool,
+        query: bool,
+        fuzzy: bool,
+        search_body: bool,
+        everywhere: bool,
+        ignore_case: bool,
+        smart_case: bool,
+        whole_word: bool
+    ) -> Result<Option<Matcher>, ThecaError> {
+        let ignore_case = ignore_case ||
+                          (smart_case && !pattern.chars().any(|c| c.is_uppercase()));
+        let compiled = match (regex, query, fuzzy) {
+            (false, true, _) => return Ok(None),
+            // fuzzy matching ignores case anyway, and looks at both
+            (false, false, true) => return Ok(Some(Matcher {
+                pattern: Pattern::Fuzzy(pattern.to_string()),
+                whole_word: false,
+                title: true,
+                body: true
+            })),
+            (true, _, _) if ignore_case => Regex::new(&*format!("(?i){}", pattern)),
+            (true, _, _) => Regex::new(pattern),
+            // the regex engine folds case properly for non-ascii text, and
+            // keeps byte offsets into the original text
+            (false, false, false) if ignore_case => Regex::new(&*format!("(?i){}", quote(pattern))),
+            (false, false, false) => return Ok(Some(Matcher {
+                pattern: Pattern::Keyword(pattern.to_string()),
+                whole_word: whole_word,
+                title: everywhere || !search_body,
+                body: everywhere || search_body
+            }))
+        };
+        match compiled {
+            Ok(r) => Ok(Some(Matcher {
+                pattern: Pattern::Regex(r),
+                whole_word: whole_word,
+                title: everywhere || !search_body,
+                body: everywhere || search_body
+            })),
+            Err(e) => specific_fail!(format!("regex error: {}.", e.msg))
+        }
+    }
+
+    /// byte ranges of the matches in `text`
+    pub fn spans(&self, text: &str) -> Vec<(usize, usize)> {
+        let spans: Vec<(usize, usize)> = match self.pattern {
+            Pattern::Keyword(ref k) => {
+                let mut spans = vec![];
+                if k.is_empty() {return spans;}
+                let mut from = 0;
+                loop {
+                    match text[from..].find(&**k) {
+                        Some(i) => {
+                            spans.push((from + i, from + i + k.len()));
+                            from += i + k.len();
+                        },
+                        None => break
+                    };
+                }
+                spans
+            },
+            Pattern::Regex(ref re) => re.find_iter(text).filter(|&(s, e)| e > s).collect(),
+            Pattern::Fuzzy(ref p) => match fuzzy_match(&**p, text) {
+                Some((_, spans)) => spans,
+                None => vec![]
+            }
+        };
+        match self.whole_word {
+            true => spans.into_iter().filter(|&(s, e)| is_word(text, s, e)).collect(),
+            false => spans
+        }
+    }
+
+    /// the matches in a note, in the title and/or the body
+    pub fn matches(&self, note: &ThecaItem) -> Vec<Match> {
+        let mut found = vec![];
+        if self.title {
+            for (start, end) in self.spans(&*note.title).into_iter() {
+                found.push(Match {field: "title".to_string(), line: 1, start: start, end: end});
+            }
+        }
+        if self.body {
+            for (start, end) in self.spans(&*note.body).into_iter() {
+                found.push(Match {
+                    field: "body".to_string(),
+                    line: note.body[..start].split('\n').count(),
+                    start: start,
+                    end: end
+                });
+            }
+        }
+        found
+    }
+}
+
+/// whether the bytes `start..end` of `text` aren't part of a longer word
+pub fn is_word(text: &str, start: usize, end: usize) -> bool {
+    let before = match text[..start].chars().next_back() {
+        Some(c) => c.is_alphanumeric() || c == '_',
+        None => false
+    };
+    let after = match text[end..].chars().next() {
+        Some(c) => c.is_alphanumeric() || c == '_',
+        None => false
+    };
+    !before && !after
+}
+
+/// the spans of `text` that are still visible once it has been padded or
+/// truncated into `field`
+pub fn visible_spans(
+    field: &str,
+    text: &str,
+    spans: &Vec<(usize, usize)>
+) -> Vec<(usize, usize)> {
+    let mut kept = 0;
+    for (a, b) in field.chars().zip(text.chars()) {
+        if a != b {break;}
+        kept += a.len_utf8();
+    }
+    // a truncated field ends in "...", which isn't part of the text
+    if kept < text.len() && field.ends_with("...") && kept + 3 > field.trim_right().len() {
+        kept = field.trim_right().len() - 3;
+    }
+    spans.iter().filter(|&&(s, _)| s < kept)
+                .map(|&(s, e)| (s, if e < kept {e} else {kept}))
+                .collect()
+}
+
+/// the lines of `text` with a match in them and `context` lines either
+/// side, grouped like grep groups them (groups are printed with `--`
+/// between them), spans are moved to be relative to their line
+pub fn context_lines(
+    text: &str,
+    spans: &Vec<(usize, usize)>,
+    context: usize
+) -> Vec<Vec<ContextLine>> {
+    let mut lines: Vec<ContextLine> = vec![];
+    let mut offset = 0;
+    for (i, line) in text.split('\n').enumerate() {
+        let end = offset + line.len();
+        let in_line = spans.iter().filter(|&&(s, e)| s < end && e > offset)
+                                  .map(|&(s, e)| (
+                                      if s > offset {s - offset} else {0},
+                                      if e < end {e - offset} else {line.len()}
+                                  ))
+                                  .filter(|&(s, e)| e > s)
+                                  .collect();
+        lines.push(ContextLine {number: i + 1, text: line.to_string(), spans: in_line});
+        offset = end + 1;
+    }
+    let matched: Vec<usize> = lines.iter().enumerate()
+                                   .filter(|&(_, l)| !l.spans.is_empty())
+                                   .map(|(i, _)| i)
+                                   .collect();
+    let mut groups: Vec<Vec<ContextLine>> = vec![];
+    let mut last: Option<usize> = None;
+    for (i, line) in lines.into_iter().enumerate() {
+        let shown = matched.iter().any(|&m| {
+            i + context >= m && i <= m + context
+        });
+        if !shown {continue;}
+        match last {
+            Some(l) if l + 1 == i => (),
+            _ => groups.push(vec![])
+        };
+        match groups.last_mut() {
+            Some(g) => g.push(line),
+            None => ()
+        };
+        last = Some(i);
+    }
+    groups
+}
+
+/// a note as JSON with its matches added as `matches`
+pub fn note_json(note: &ThecaItem, matches: &Vec<Match>) -> Result<Json, ThecaError> {
+    let mut json = match Json::from_str(&*format!("{}", as_json(note))) {
+        Ok(j) => j,
+        Err(_) => specific_fail_str!("could not encode the note as JSON.")
+    };
+    let matches_json = match Json::from_str(&*format!("{}", as_json(matches))) {
+        Ok(j) => j,
+        Err(_) => specific_fail_str!("could not encode the matches as JSON.")
+    };
+    match json {
+        Json::Object(ref mut o) => {o.insert("matches".to_string(), matches_json);},
+        _ => ()
+    };
+    Ok(json)
+}
@@ -0,0 +1,329 @@
+//  _   _                    
+// | |_| |__   ___  ___ __ _ 
+// | __| '_ \ / _ \/ __/ _` |
+// | |_| | | |  __/ (_| (_| |
+//  \__|_| |_|\___|\___\__,_|
+//
+// licensed under the MIT license <http://opensource.org/licenses/MIT>
+//
+// index.rs
+//   the full text index kept next to a profile, `<profile>.index`, used to
+//   rank `search --rank` results with BM25.
+
+use std::cmp::{Ordering, min};
+use std::collections::{BTreeMap};
+use std::fs::{File};
+use std::io::{Read, Write};
+use std::path::{Path, PathBuf};
+
+use crypto::sha2::{Sha256};
+use crypto::digest::{Digest};
+use rustc_serialize::json::{decode, encode};
+
+use ::{ThecaItem};
+use errors::{ThecaError, GenericError};
+use crypt::{encrypt, decrypt};
+use utils::{find_profile_folder};
+
+/// BM25 parameters, how quickly repeated terms stop adding to the score
+/// and how much long notes are penalised
+static K1: f64 = 1.2;
+static B: f64 = 0.75;
+
+/// bumped whenever tokenizing changes so older indexes get rebuilt
+pub static INDEX_VERSION: usize = 1;
+
+/// how many times a term appears in a note
+#[derive(RustcDecodable, RustcEncodable, Clone)]
+pub struct Posting {
+    pub id: usize,
+    pub count: usize
+}
+
+/// a note in the index, the hash of its title and body tells us when
+/// it needs to be indexed again
+#[derive(RustcDecodable, RustcEncodable, Clone)]
+pub struct Document {
+    pub id: usize,
+    pub hash: String,
+    pub length: usize
+}
+
+/// An inverted index from (stemmed) terms to the notes they appear in
+#[derive(RustcDecodable, RustcEncodable, Clone)]
+pub struct Index {
+    pub version: usize,
+    pub documents: Vec<Document>,
+    pub terms: BTreeMap<String, Vec<Posting>>
+}
+
+/// A note found by a ranked search
+#[derive(RustcEncodable)]
+pub struct Ranked {
+    pub id: usize,
+    pub title: String,
+    pub score: f64,
+    pub snippet: String
+}
+
+/// file the index of a profile is stored in
+pub fn index_path(
+    profile_folder: &String,
+    profile_name: &String
+) -> Result<PathBuf, ThecaError> {
+    let mut index = try!(find_profile_folder(profile_folder));
+    index.push(&(profile_name.to_string() + ".index"));
+    Ok(index)
+}
+
+/// byte ranges of the words (runs of letters and numbers) in `text`
+pub fn word_spans(text: &str) -> Vec<(usize, usize)> {
+    let mut spans: Vec<(usize, usize)> = vec![];
+    let mut start: Option<usize> = None;
+    for (i, c) in text.char_indices() {
+        match (c.is_alphanumeric(), start) {
+            (true, None) => start = Some(i),
+            (false, Some(s)) => {
+                spans.push((s, i));
+                start = None;
+            },
+            _ => ()
+        };
+    }
+    match start {
+        Some(s) => spans.push((s, text.len())),
+        None => ()
+    };
+    spans
+}
+
+/// strip common english suffixes so "deploys", "deployed" and "deploying"
+/// all end up as "deploy", words are left with at least three characters
+pub fn stem(word: &str) -> String {
+    let length = word.chars().count();
+    for &(suffix, replacement) in [
+        ("sses", "ss"),
+        ("ies", "y"),
+        ("ing", ""),
+        ("ed", ""),
+        ("s", "")
+    ].iter() {
+        if word.ends_with(suffix) && length >= suffix.len() + 3 {
+            if suffix == "s" && (word.ends_with("ss") || word.ends_with("us")) {
+                break;
+            }
+            return format!("{}{}", &word[..word.len()-suffix.len()], replacement)
+        }
+    }
+    word.to_string()
+}
+
+/// the lower cased, stemmed terms in `text`
+pub fn tokenize(text: &str) -> Vec<String> {
+    word_spans(text).iter().map(|&(s, e)| stem(&*text[s..e].to_lowercase())).collect()
+}
+
+fn note_hash(note: &ThecaItem) -> String {
+    let mut sha = Sha256::new();
+    sha.input_str(&*note.title);
+    sha.input_str("\n");
+    sha.input_str(&*note.body);
+    sha.result_str()
+}
+
+fn read_index(path: &Path, key: Option<&[u8]>) -> Result<Index, ThecaError> {
+    let mut file = try!(File::open(path));
+    let mut contents: Vec<u8> = vec![];
+    try!(file.read_to_end(&mut contents));
+    let contents = match key {
+        Some(k) => try!(decrypt(&*contents, k)),
+        None => contents
+    };
+    match decode(&*try!(String::from_utf8(contents))) {
+        Ok(i) => Ok(i),
+        Err(_) => specific_fail!(format!("invalid index {}", path.display()))
+    }
+}
+
+impl Index {
+    pub fn new() -> Index {
+        Index {
+            version: INDEX_VERSION,
+            documents: vec![],
+            terms: BTreeMap::new()
+        }
+    }
+
+    /// read the index at `path`, anything wrong with it (it doesn't exist
+    /// yet, is from an older version or the key has changed) just means
+    /// starting again with an empty one
+    pub fn load(path: &Path, key: Option<&[u8]>) -> Index {
+        match read_index(path, key) {
+            Ok(i) => match i.version == INDEX_VERSION {
+                true => i,
+                false => Index::new()
+            },
+            Err(_) => Index::new()
+        }
+    }
+
+    /// write the index to `path`, encrypted if there is a key
+    pub fn save(&self, path: &Path, key: Option<&[u8]>) -> Result<(), ThecaError> {
+        let json = try!(encode(self));
+        let buffer = match key {
+            Some(k) => try!(encrypt(json.as_bytes(), k)),
+            None => json.into_bytes()
+        };
+        let mut file = try!(File::create(path));
+        try!(file.write_all(&buffer));
+        Ok(())
+    }
+
+    /// add a note, the title counts twice so matches there rank higher
+    pub fn add(&mut self, note: &ThecaItem) {
+        let mut tokens = tokenize(&*note.title);
+        tokens.push_all(&tokenize(&*note.title));
+        tokens.push_all(&tokenize(&*note.body));
+        let mut counts: BTreeMap<String, usize> = BTreeMap::new();
+        for t in tokens.iter() {
+            let count = counts.get(t).map(|c| *c).unwrap_or(0);
+            counts.insert(t.clone(), count + 1);
+        }
+        for (term, count) in counts.into_iter() {
+            let posting = Posting {id: note.id, count: count};
+            if self.terms.contains_key(&term) {
+                self.terms.get_mut(&term).unwrap().push(posting);
+            } else {
+                self.terms.insert(term, vec![posting]);
+            }
+        }
+        self.documents.push(Document {
+            id: note.id,
+            hash: note_hash(note),
+            length: tokens.len()
+        });
+    }
+
+    /// drop a note from the index
+    pub fn remove(&mut self, id: usize) {
+        self.documents.retain(|d| d.id != id);
+        let mut empty: Vec<String> = vec![];
+        for (term, postings) in self.terms.iter_mut() {
+            postings.retain(|p| p.id != id);
+            if postings.is_empty() {empty.push(term.clone());}
+        }
+        for term in empty.iter() {
+            self.terms.remove(term);
+        }
+    }
+
+    /// bring the index up to date with the notes, only notes that were
+    /// added, changed or deleted since it was last updated are touched,
+    /// returns whether anything changed
+    pub fn update(&mut self, notes: &[ThecaItem]) -> bool {
+        let hashes: Vec<(usize, String)> = notes.iter().map(|n| (n.id, note_hash(n))).collect();
+        let stale: Vec<usize> = self.documents.iter().filter(|d| {
+            !hashes.iter().any(|h| h.0 == d.id && h.1 == d.hash)
+        }).map(|d| d.id).collect();
+        for id in stale.iter() {
+            self.remove(*id);
+        }
+        let mut changed = !stale.is_empty();
+        for n in notes.iter() {
+            if !self.documents.iter().any(|d| d.id == n.id) {
+                self.add(n);
+                changed = true;
+            }
+        }
+        changed
+    }
+
+    /// BM25 score of every note containing at least one of the query terms,
+    /// best first
+    pub fn score(&self, query: &str) -> Vec<(usize, f64)> {
+        let mut terms = tokenize(query);
+        terms.sort();
+        terms.dedup();
+        let count = self.documents.len() as f64;
+        if count == 0.0 {
+            return vec![]
+        }
+        let mut lengths: BTreeMap<usize, f64> = BTreeMap::new();
+        let mut total = 0.0;
+        for d in self.documents.iter() {
+            lengths.insert(d.id, d.length as f64);
+            total += d.length as f64;
+        }
+        let average = match total > 0.0 {
+            true => total / count,
+            false => 1.0
+        };
+        let mut scores: Vec<(usize, f64)> = vec![];
+        for term in terms.iter() {
+            let postings = match self.terms.get(term) {
+                Some(p) => p,
+                None => continue
+            };
+            let df = postings.len() as f64;
+            let idf = ((count - df + 0.5) / (df + 0.5) + 1.0).ln();
+            for p in postings.iter() {
+                let length = lengths.get(&p.id).map(|l| *l).unwrap_or(average);
+                let tf = p.count as f64;
+                let score = idf * tf * (K1 + 1.0) /
+                            (tf + K1 * (1.0 - B + B * length / average));
+                match scores.iter().position(|s| s.0 == p.id) {
+                    Some(i) => scores[i].1 += score,
+                    None => scores.push((p.id, score))
+                };
+            }
+        }
+        scores.sort_by(|a, b| match b.1.partial_cmp(&a.1) {
+            Some(Ordering::Equal) | None => a.0.cmp(&b.0),
+            Some(o) => o
+        });
+        scores
+    }
+}
+
+/// a single line of about `width` characters from `text` around the first
+/// word matching one of the (stemmed) `terms`, with the byte ranges of the
+/// matching words in it
+pub fn snippet(text: &str, terms: &Vec<String>, width: usize) -> (String, Vec<(usize, usize)>) {
+    let lines: Vec<&str> = text.lines().map(|l| l.trim()).filter(|l| !l.is_empty()).collect();
+    let flat = lines.connect(" ");
+    let matched: Vec<(usize, usize)> = word_spans(&*flat).into_iter().filter(|&(s, e)| {
+        terms.contains(&stem(&*flat[s..e].to_lowercase()))
+    }).collect();
+    let chars: Vec<usize> = flat.char_indices().map(|(i, _)| i).collect();
+    let first = match matched.first() {
+        Some(&(s, _)) => chars.iter().position(|i| *i == s).unwrap_or(0),
+        None => 0
+    };
+    // show a little of what comes before the first match
+    let start_char = match first > width / 4 {
+        true => first - width / 4,
+        false => 0
+    };
+    let end_char = min(chars.len(), start_char + width);
+    let start = match chars.get(start_char) {
+        Some(i) => *i,
+        None => flat.len()
+    };
+    let end = match chars.get(end_char) {
+        Some(i) => *i,
+        None => flat.len()
+    };
+    let prefix = match start > 0 {
+        true => "...",
+        false => ""
+    };
+    let suffix = match end < flat.len() {
+        true => "...",
+        false => ""
+    };
+    let spans = matched.into_iter()
+                       .filter(|&(s, e)| s >= start && e <= end)
+                       .map(|(s, e)| (s - start + prefix.len(), e - start + prefix.len()))
+                       .collect();
+    (format!("{}{}{}", prefix, &flat[start..end], suffix), spans)
+}
@@ -0,0 +1,83 @@
+//  _   _                    
+// | |_| |__   ___  ___ __ _ 
+// | __| '_ \ / _ \/ __/ _` |
+// | |_| | | |  __/ (_| (_| |
+//  \__|_| |_|\___|\___\__,_|
+//
+// licensed under the MIT license <http://opensource.org/licenses/MIT>
+//
+// journal.rs
+//   daily journal notes, one note per day titled with its date that
+//   `theca today` appends timestamped entries to.
+
+use time::{Tm, Duration, strftime, strptime, at};
+
+use ::{ThecaItem};
+use errors::{ThecaError, GenericError};
+use schedule::{parse_duration};
+
+/// format of the title of a journal note
+pub static DAYFMT: &'static str = "%Y-%m-%d";
+
+/// is the title that of a journal note?
+pub fn is_day(title: &str) -> bool {
+    title.len() == 10 && strptime(title, DAYFMT).is_ok()
+}
+
+/// parse a `--from`/`--to` date, either YYYY-MM-DD, today, yesterday or
+/// how long ago (3d, 2w or 2w ago)
+pub fn parse_day(spec: &str, now: &Tm) -> Result<String, ThecaError> {
+    let spec = spec.trim();
+    match spec {
+        "today" => return Ok(try!(strftime(DAYFMT, now))),
+        "yesterday" => return Ok(try!(strftime(
+            DAYFMT,
+            &at(now.to_timespec() - Duration::days(1))
+        ))),
+        _ => ()
+    };
+    if is_day(spec) {
+        return Ok(spec.to_string())
+    }
+    let duration = match spec.ends_with("ago") {
+        true => spec[..spec.len()-3].trim(),
+        false => spec
+    };
+    match parse_duration(duration) {
+        Ok(secs) => Ok(try!(strftime(
+            DAYFMT,
+            &at(now.to_timespec() - Duration::seconds(secs))
+        ))),
+        Err(_) => specific_fail!(format!(
+            "invalid date: {} (use YYYY-MM-DD, today, yesterday or something like 3d ago)",
+            spec
+        ))
+    }
+}
+
+/// the heading an entry made at `time` starts with
+pub fn entry_heading(time: &str) -> String {
+    format!("## {}\n\n", time)
+}
+
+/// add a timestamped entry to the end of a journal body
+pub fn append_entry(body: &str, time: &str, entry: &str) -> String {
+    let body = body.trim_right();
+    let sep = match body.is_empty() {
+        true => "",
+        false => "\n\n"
+    };
+    format!("{}{}{}{}", body, sep, entry_heading(time), entry.trim())
+}
+
+/// the journal notes for the days from `from` to `to` (either can be empty
+/// to leave that end open), oldest first
+pub fn days_between<'a>(notes: &'a [ThecaItem], from: &str, to: &str) -> Vec<&'a ThecaItem> {
+    let mut days: Vec<&ThecaItem> = notes.iter().filter(|n| {
+        is_day(&*n.title) &&
+        (from.is_empty() || &*n.title >= from) &&
+        (to.is_empty() || &*n.title <= to)
+    }).collect();
+    days.sort_by(|a, b| a.title.cmp(&b.title));
+    days
+}
@@ -19,6 +19,7 @@
 #![feature(os)]
 #![feature(fs_time)]
 #![feature(path_ext)]
+#![feature(exit_status)]
 
 
 //! Definitions of ThecaItem and ThecaProfile and their implementations
@@ -35,12 +36,12 @@ extern crate rand;
 extern crate tempdir;
 
 // std lib imports
-use std::env::{var};
+use std::env::{var, set_exit_status};
 use std::old_io::{stdin};
-use std::io::{Read, Write};
+use std::io::{Read, Write, stderr};
 use std::iter::{repeat};
 use std::path::Path;
-use std::fs::{File, PathExt, create_dir};
+use std::fs::{File, PathExt, create_dir, remove_file};
 
 // random things
 use regex::{Regex};
@@ -54,9 +55,36 @@ use utils::c::{istty};
 use utils::{drop_to_editor, pretty_line, format_field,
             get_yn_input, sorted_print, localize_last_touched_string,
             parse_last_touched, find_profile_folder, get_password,
-            profiles_in_folder};
+            profiles_in_folder, format_last_touched, new_uuid, print_highlighted,
+            print_spans, filter_status_priority};
 use errors::{ThecaError, GenericError};
 use crypt::{encrypt, decrypt, password_to_key};
+use tree::{children, descendants, would_cycle};
+use links::{LinkIndex};
+use deps::{blockers, open_blockers, would_block_cycle, ready, to_dot};
+use attachments::{Attachment, store_path, store_blob, read_blob,
+                  orphaned_blobs, missing_blobs, restore_blobs,
+                  referenced_blobs};
+use checklist::{progress, progress_string, set_item, reset};
+use schedule::{Schedule, parse_date};
+use archive::{is_archived, shown, visible, stale_done};
+use order::{is_pinned, manual_order, move_before};
+use fields::{Field, get_field, parse_assignment, parse_columns};
+use query::{filter_notes, filter_where};
+use fuzzy::{rank_notes};
+use highlight::{Highlight, Matcher, Pattern, context_lines, visible_spans};
+use views::{View, check_name, set_view};
+use replace::{Change, parse_target, note_change, print_diff};
+use undo::{undo_path, keep_for_undo};
+use select::{is_bulk, select_ids};
+use pick::{pick};
+use index::{Index, Ranked, index_path, tokenize, snippet};
+use priority::{parse_priority};
+use timetrack::{TimeEntry, running, running_notes, week_start, logged, timesheet,
+                format_duration, csv_field};
+use journal::{DAYFMT, parse_day, append_entry, days_between};
+use templates::{SKELETON, templates_path, template_names, template_file, read_template,
+                expand, parse_template, initial_body, ask};
 
 pub use self::libc::{
     STDIN_FILENO,
@@ -68,6 +96,30 @@ pub use self::libc::{
 pub mod lineformat;
 pub mod utils;
 pub mod crypt;
+pub mod tree;
+pub mod links;
+pub mod deps;
+pub mod attachments;
+pub mod checklist;
+pub mod schedule;
+pub mod archive;
+pub mod order;
+pub mod fields;
+pub mod timetrack;
+pub mod priority;
+pub mod templates;
+pub mod journal;
+pub mod query;
+pub mod index;
+pub mod fuzzy;
+pub mod highlight;
+pub mod pick;
+pub mod profiles;
+pub mod views;
+pub mod replace;
+pub mod undo;
+pub mod select;
+pub mod sort;
 
 /// Current version of theca
 pub fn version() -> String {
@@ -78,40 +130,117 @@ pub fn version() -> String {
 #[derive(RustcDecodable, Clone)]
 pub struct Args {
     pub cmd_add: bool,
+    pub cmd_archive: bool,
+    pub cmd_attach: bool,
+    pub cmd_attachments: bool,
+    pub cmd_check: bool,
     pub cmd_clear: bool,
     pub cmd_del: bool,
     pub cmd_decrypt_profile: bool,
     pub cmd_edit: bool,
     pub cmd_encrypt_profile: bool,
+    pub cmd_extract: bool,
+    pub cmd_graph: bool,
     pub cmd_import: bool,
     pub cmd_info: bool,
+    pub cmd_journal: bool,
+    pub cmd_links: bool,
+    pub cmd_list: bool,
     pub cmd_list_profiles: bool,
+    pub cmd_move: bool,
     pub cmd_new_profile: bool,
+    pub cmd_pick: bool,
+    pub cmd_pin: bool,
+    pub cmd_ready: bool,
+    pub cmd_recurring: bool,
+    pub cmd_remove: bool,
+    pub cmd_replace: bool,
+    pub cmd_save: bool,
     pub cmd_search: bool,
+    pub cmd_show: bool,
+    pub cmd_start: bool,
+    pub cmd_stop: bool,
+    pub cmd_templates: bool,
+    pub cmd_timesheet: bool,
+    pub cmd_today: bool,
     pub cmd_transfer: bool,
+    pub cmd_unarchive: bool,
+    pub cmd_uncheck: bool,
+    pub cmd_undo: bool,
+    pub cmd_unpin: bool,
+    pub cmd_verify: bool,
+    pub cmd_view: bool,
     pub cmd__: bool,
+    pub arg_args: Vec<String>,
+    pub arg_attachment: String,
+    pub arg_file: String,
     pub arg_id: Vec<usize>,
+    pub arg_ids: Vec<String>,
+    pub arg_item: usize,
     pub arg_name: Vec<String>,
     pub arg_pattern: String,
+    pub arg_replacement: String,
+    pub arg_template: String,
     pub arg_title: String,
+    pub arg_view: String,
+    pub flag_all: bool,
+    pub flag_all_profiles: bool,
+    pub flag_archived: bool,
+    pub flag_auto_archive: usize,
+    pub flag_auto_done: bool,
+    pub flag_before: usize,
+    pub flag_blocked_by: usize,
     pub flag_body: Vec<String>,
+    pub flag_broken: bool,
+    pub flag_collapse: bool,
+    pub flag_columns: String,
+    pub flag_concurrent: bool,
     pub flag_condensed: bool,
+    pub flag_context: usize,
+    pub flag_csv: bool,
     pub flag_datesort: bool,
+    pub flag_done: bool,
+    pub flag_dry_run: bool,
+    pub flag_due: String,
     pub flag_editor: bool,
     pub flag_encrypted: bool,
+    pub flag_every: String,
+    pub flag_everywhere: bool,
+    pub flag_from: String,
+    pub flag_fuzzy: bool,
+    pub flag_ignore_case: bool,
+    pub flag_in: String,
     pub flag_json: bool,
+    pub flag_journal: String,
     pub flag_key: String,
     pub flag_limit: usize,
+    pub flag_manual: bool,
     pub flag_new_key: String,
     pub flag_none: bool,
+    pub flag_parent: usize,
+    pub flag_priority: String,
+    pub flag_prioritysort: bool,
     pub flag_profile: String,
     pub flag_profile_folder: String,
+    pub flag_query: bool,
+    pub flag_rank: bool,
     pub flag_regex: bool,
+    pub flag_relative: bool,
     pub flag_reverse: bool,
     pub flag_search_body: bool,
+    pub flag_set: Vec<String>,
+    pub flag_skip_encrypted: bool,
+    pub flag_smart_case: bool,
+    pub flag_sort: String,
     pub flag_started: bool,
+    pub flag_template: String,
+    pub flag_to: String,
+    pub flag_unblock: usize,
     pub flag_urgent: bool,
     pub flag_version: bool,
+    pub flag_week: bool,
+    pub flag_where: String,
+    pub flag_word: bool,
     pub flag_yes: bool
 }
 
@@ -121,6 +250,8 @@ static NOSTATUS: &'static str = "";
 static STARTED: &'static str = "Started";
 /// Urgent status text
 static URGENT: &'static str = "Urgent";
+/// Done status text
+static DONE: &'static str = "Done";
 
 /// datetime formating string
 static DATEFMT: &'static str = "%F %T %z";
@@ -128,13 +259,25 @@ static DATEFMT: &'static str = "%F %T %z";
 static DATEFMT_SHORT: &'static str = "%F %T";
 
 /// Represents a note within a profile
-#[derive(RustcDecodable, RustcEncodable, Clone)]
+#[derive(RustcDecodable, RustcEncodable, Clone, Default)]
 pub struct ThecaItem {
     pub id: usize,
     pub title: String,
     pub status: String,
     pub body: String,
-    pub last_touched: String
+    pub last_touched: String,
+    pub uuid: Option<String>,
+    pub parent: Option<usize>,
+    pub blocked_by: Option<Vec<usize>>,
+    pub attachments: Option<Vec<Attachment>>,
+    pub due: Option<String>,
+    pub every: Option<String>,
+    pub series: Option<String>,
+    pub archived: Option<String>,
+    pub pinned: Option<bool>,
+    pub fields: Option<Vec<Field>>,
+    pub time_log: Option<Vec<TimeEntry>>,
+    pub priority: Option<String>
 }
 
 impl ThecaItem {
@@ -142,34 +285,63 @@ impl ThecaItem {
     fn print(
         &self,
         line_format: &LineFormat,
-        search_body: bool
+        search_body: bool,
+        relative: bool,
+        highlight: Option<&Highlight>
     ) -> Result<(), ThecaError> {
         let column_seperator: String = repeat(' ').take(line_format.colsep)
                                                   .collect();
+        let tty = istty(STDOUT_FILENO);
+        let matches = match highlight {
+            Some(h) => h.matcher.matches(self),
+            None => vec![]
+        };
+        let spans = |field: &str| -> Vec<(usize, usize)> {
+            matches.iter().filter(|m| &*m.field == field).map(|m| (m.start, m.end)).collect()
+        };
         print!("{}", format_field(
             &self.id.to_string(),
             line_format.id_width,
             false
         ));
         print!("{}", column_seperator);
-        match !self.body.is_empty() && !search_body {
-            true => {
-                print!("{}", format_field(
-                    &self.title,
-                    line_format.title_width-4,
-                    true
-                ));
-                print!("{}", format_field(&" (+)".to_string(), 4, false));
-            },
-            false => {
-                print!("{}", format_field(
-                    &self.title,
-                    line_format.title_width,
-                    true
-                ));
-            }
+        if line_format.priority_width != 0 {
+            print!("{}", format_field(
+                &self.priority.clone().unwrap_or("".to_string()),
+                line_format.priority_width,
+                false
+            ));
+            print!("{}", column_seperator);
         }
+        let title = match !self.body.is_empty() && !search_body {
+            true => format_field(
+                &self.title,
+                line_format.title_width-4,
+                true
+            ) + &*format_field(&" (+)".to_string(), 4, false),
+            false => format_field(
+                &self.title,
+                line_format.title_width,
+                true
+            )
+        };
+        match highlight {
+            Some(_) => try!(print_spans(
+                &*title,
+                &visible_spans(&*title, &*self.title, &spans("title")),
+                tty
+            )),
+            None => print!("{}", title)
+        };
         print!("{}", column_seperator);
+        if line_format.progress_width != 0 {
+            print!("{}", format_field(
+                &progress_string(&*self.body),
+                line_format.progress_width,
+                false
+            ));
+            print!("{}", column_seperator);
+        }
         if line_format.status_width != 0 {
             print!("{}", format_field(
                 &self.status,
@@ -178,26 +350,50 @@ impl ThecaItem {
             ));
             print!("{}", column_seperator);
         }
+        for &(ref name, width) in line_format.field_widths.iter() {
+            let value = match get_field(self, &*name) {
+                Some(f) => f.value.clone(),
+                None => "".to_string()
+            };
+            print!("{}", format_field(&value, width, true));
+            print!("{}", column_seperator);
+        }
         print!("{}", format_field(
-            &try!(localize_last_touched_string(&*self.last_touched)),
+            &try!(format_last_touched(&*self.last_touched, relative)),
             line_format.touched_width,
             false
         ));
         print!("\n");
         if search_body {
-            for l in self.body.lines() {
-                println!("\t{}", l);
-            }
+            match highlight {
+                // only the lines that matched, and any context asked for
+                Some(h) => {
+                    let groups = context_lines(&*self.body, &spans("body"), h.context);
+                    for (i, group) in groups.iter().enumerate() {
+                        if i > 0 {println!("\t--");}
+                        for l in group.iter() {
+                            print!("\t");
+                            try!(print_highlighted(&*l.text, &l.spans, tty));
+                        }
+                    }
+                },
+                None => for l in self.body.lines() {
+                    println!("\t{}", l);
+                }
+            };
         }
         Ok(())
     }
 }
 
 /// Main container of a theca profile file
-#[derive(RustcDecodable, RustcEncodable, Clone)]
+#[derive(RustcDecodable, RustcEncodable, Clone, Default)]
 pub struct ThecaProfile {
     pub encrypted: bool,
-    pub notes: Vec<ThecaItem>
+    pub notes: Vec<ThecaItem>,
+    pub next_id: Option<usize>,
+    pub order: Option<Vec<usize>>,
+    pub views: Option<Vec<View>>
 }
 
 impl ThecaProfile {
@@ -228,7 +424,10 @@ impl ThecaProfile {
             }
             Ok((ThecaProfile {
                 encrypted: encrypted,
-                notes: vec![]
+                notes: vec![],
+                next_id: Some(1),
+                order: None,
+                views: None
             }, 0u64))
         } else {
             // set profile folder
@@ -279,29 +478,50 @@ impl ThecaProfile {
                     let mut file = try!(File::open(profile_path));
                     let mut contents_buf: Vec<u8> = vec![];
                     try!(file.read_to_end(&mut contents_buf));
-                    let contents = match encrypted {
-                        false => try!(String::from_utf8(contents_buf)),
-                        true => {
-                            let key = password_to_key(&key[..]);
-                                try!(String::from_utf8(try!(decrypt(
-                                    &*contents_buf,
-                                    &*key
-                                ))))
-                        }
-                    };
-                    let decoded: ThecaProfile = match decode(&*contents) {
-                        Ok(s) => s,
-                        Err(_) => specific_fail!(format!(
-                            "invalid JSON in {}",
-                            profile_path.display()
-                        ))
-                    };
+                    let decoded = try!(ThecaProfile::from_bytes(
+                        contents_buf,
+                        key,
+                        encrypted,
+                        profile_path
+                    ));
                     Ok((decoded, try!(profile_path.metadata()).modified()))
                 }
             }
         }
     }
 
+    /// decode a profile read from `path`
+    pub fn from_bytes(
+        contents_buf: Vec<u8>,
+        key: &String,
+        encrypted: bool,
+        path: &Path
+    ) -> Result<ThecaProfile, ThecaError> {
+        let contents = match encrypted {
+            false => try!(String::from_utf8(contents_buf)),
+            true => {
+                let key = password_to_key(&key[..]);
+                try!(String::from_utf8(try!(decrypt(&*contents_buf, &*key))))
+            }
+        };
+        match decode(&*contents) {
+            Ok(s) => Ok(s),
+            Err(_) => specific_fail!(format!(
+                "invalid JSON in {}",
+                path.display()
+            ))
+        }
+    }
+
+    /// give notes from older profiles a uuid, this is only done right
+    /// before the profile is changed (and saved) so a uuid is never shown
+    /// that isn't kept
+    pub fn assign_uuids(&mut self) {
+        for n in self.notes.iter_mut() {
+            if n.uuid.is_none() {n.uuid = Some(new_uuid());}
+        }
+    }
+
     /// remove all notes from the profile
     pub fn clear(&mut self, yes: bool) -> Result<(), ThecaError> {
         if !yes {
@@ -314,6 +534,22 @@ impl ThecaProfile {
         Ok(())
     }
 
+    /// hand out the next note id, ids are never reused within a profile
+    pub fn next_note_id(&mut self) -> usize {
+        let after_last = match self.notes.iter().max_by(|n| n.id) {
+            Some(n) => n.id + 1,
+            None => 1
+        };
+        // profiles from before the counter existed (or merged by hand)
+        // can't be trusted to have a counter ahead of every note
+        let next_id = match self.next_id {
+            Some(n) if n > after_last => n,
+            _ => after_last
+        };
+        self.next_id = Some(next_id + 1);
+        next_id
+    }
+
     // FIXME (this as well as transfer_note, shouldn't *need* to take all of `args`)
     /// save the profile back to file (either plaintext or encrypted)
     pub fn save_to_file(
@@ -382,9 +618,23 @@ impl ThecaProfile {
             }
         }
 
+        // the profile as it was can be brought back with `theca undo`
+        let profile_name = match args.cmd_new_profile {
+            true => args.arg_name[0].clone(),
+            false => args.flag_profile.clone()
+        };
+        if profile_path.is_file() {
+            try!(keep_for_undo(
+                profile_path,
+                &try!(undo_path(&args.flag_profile_folder, &profile_name))
+            ));
+        }
+
         // open file
         let mut file = try!(File::create(profile_path));
 
+        self.assign_uuids();
+
         // encode to buffer
         let mut json_prof = String::new();
         {
@@ -407,6 +657,17 @@ impl ThecaProfile {
         // write buffer to file
         try!(file.write_all(&buffer));
 
+        // keep the search index in step, only changed notes are reindexed
+        let index_file = try!(index_path(&args.flag_profile_folder, &profile_name));
+        let index_key = match self.encrypted {
+            true => Some(password_to_key(&*args.flag_key)),
+            false => None
+        };
+        let mut index = Index::load(&index_file, index_key.as_ref().map(|k| &**k));
+        if index.update(&self.notes) || !index_file.exists() {
+            try!(index.save(&index_file, index_key.as_ref().map(|k| &**k)));
+        }
+
         Ok(())
     }
 
@@ -432,53 +693,120 @@ impl ThecaProfile {
             args.flag_yes
         ));
 
-        match self.notes.iter().find(|n| n.id == args.arg_id[0])
-                        .map(|n| {
-                            let (started, urgent) = (
-                                n.status == STARTED,
-                                n.status == URGENT
-                            );
-                            trans_profile.add_note(
-                                &n.title,
-                                &vec![n.body.clone()],
-                                started,
-                                urgent,
-                                false,
-                                false,
-                                false
-                            )
-                        }).is_some() {
+        // the notes and all of their subtasks move together, they keep their
+        // uuids (and everything else) but get new short ids from the counter
+        // of the profile they are moving to
+        let mut subtree: Vec<usize> = vec![];
+        for id in args.arg_id.iter() {
+            let mut below = vec![*id];
+            below.push_all(&descendants(&self.notes, *id));
+            for b in below.into_iter() {
+                if !subtree.contains(&b) {subtree.push(b);}
+            }
+        }
+        let mut moved: Vec<(usize, usize)> = vec![];
+        for old_id in subtree.iter() {
+            match self.notes.iter().find(|n| &n.id == old_id) {
+                Some(n) => {
+                    let mut trans_note = n.clone();
+                    trans_note.id = trans_profile.next_note_id();
+                    moved.push((n.id, trans_note.id));
+                    trans_profile.notes.push(trans_note);
+                },
+                None => ()
+            }
+        }
+        // parents only survive between notes that moved together (the
+        // notes aren't necessarily moved parents first)
+        for &(_, new_id) in moved.iter() {
+            match trans_profile.notes.iter_mut().find(|n| n.id == new_id) {
+                Some(n) => n.parent = match n.parent {
+                    Some(p) => moved.iter().find(|m| m.0 == p).map(|m| m.1),
+                    None => None
+                },
+                None => ()
+            };
+        }
+        // attachments have to be copied into the store of the other profile
+        let from_store = try!(store_path(
+            &args.flag_profile_folder,
+            &args.flag_profile
+        ));
+        let to_store = try!(store_path(
+            &args.flag_profile_folder,
+            &args.arg_name[0]
+        ));
+        let key = password_to_key(&*args.flag_key);
+        let from_key = match self.encrypted {
+            true => Some(&*key),
+            false => None
+        };
+        let to_key = match trans_profile.encrypted {
+            true => Some(&*key),
+            false => None
+        };
+        for &(_, new_id) in moved.iter() {
+            match trans_profile.notes.iter_mut().find(|n| n.id == new_id) {
+                Some(n) => match n.attachments {
+                    Some(ref mut a) => for att in a.iter_mut() {
+                        let contents = try!(read_blob(&from_store, &att.hash, from_key));
+                        att.hash = try!(store_blob(&to_store, &contents, to_key));
+                    },
+                    None => ()
+                },
+                None => ()
+            };
+        }
+
+        // dependencies only survive between notes that moved together
+        for &(_, new_id) in moved.iter() {
+            match trans_profile.notes.iter_mut().find(|n| n.id == new_id) {
+                Some(n) => {
+                    let remapped: Vec<usize> = blockers(n).iter().filter_map(|b| {
+                        moved.iter().find(|m| &m.0 == b).map(|m| m.1)
+                    }).collect();
+                    n.blocked_by = match remapped.is_empty() {
+                        true => None,
+                        false => Some(remapped)
+                    };
+                },
+                None => ()
+            };
+        }
+        match !moved.is_empty() {
             true =>  {
-                match self.notes.iter().position(|n| n.id == args.arg_id[0])
-                                   .map(|e| self.notes.remove(e)).is_some() {
-                    true => try!(trans_profile.save_to_file(
-                        &trans_args,
-                        &trans_fingerprint
-                    )),
-                    false => specific_fail!(format!(
-                        "couldn't remove note {} in {}, aborting nothing will be saved",
-                        args.arg_id[0],
-                        args.flag_profile
-                    ))
-                };
+                for &(old_id, _) in moved.iter() {
+                    match self.notes.iter().position(|n| n.id == old_id)
+                                    .map(|e| self.notes.remove(e)).is_some() {
+                        true => (),
+                        false => specific_fail!(format!(
+                            "couldn't remove note {} in {}, aborting nothing will be saved",
+                            old_id,
+                            args.flag_profile
+                        ))
+                    };
+                }
+                try!(trans_profile.save_to_file(
+                    &trans_args,
+                    &trans_fingerprint
+                ));
             },
             false => specific_fail!(format!(
-                "could not transfer note {} from {} -> {}",
-                args.arg_id[0],
+                "could not transfer note(s) {} from {} -> {}",
+                args.arg_id.iter().map(|i| i.to_string()).collect::<Vec<String>>().connect(", "),
                 args.flag_profile,
                 args.arg_name[0]
             ))
         };
-        println!(
-            "transfered [{}: note {} -> {}: note {}]",
-            args.flag_profile,
-            args.arg_id[0],
-            args.arg_name[0],
-            match trans_profile.notes.last() {
-                Some(n) => n.id,
-                None => 0
-            }
-        );
+        for &(old_id, new_id) in moved.iter() {
+            println!(
+                "transfered [{}: note {} -> {}: note {}]",
+                args.flag_profile,
+                old_id,
+                args.arg_name[0],
+                new_id
+            );
+        }
         Ok(())
     }
 
@@ -518,177 +846,1264 @@ impl ThecaProfile {
             true => { try!(stdin().read_to_string()) }
         };
 
-        let new_id = match self.notes.last() {
-            Some(n) => n.id,
-            None => 0
-        };
+        let new_id = self.next_note_id();
         self.notes.push(ThecaItem {
-            id: new_id + 1,
+            id: new_id,
             title: title,
             status: status,
             body: body,
-            last_touched: try!(strftime(DATEFMT, &now()))
+            last_touched: try!(strftime(DATEFMT, &now())),
+            uuid: Some(new_uuid()),
+            parent: None,
+            blocked_by: None,
+            attachments: None,
+            due: None,
+            every: None,
+            series: None,
+            archived: None,
+            pinned: None,
+            fields: None,
+            time_log: None,
+            priority: None
         });
-        if print_msg { println!("note {} added", new_id+1); }
+        if print_msg { println!("note {} added", new_id); }
         Ok(())
     }
 
-    /// delete an item from the profile
-    pub fn delete_note(&mut self, id: &Vec<usize>) {
-        for nid in id.iter() {
-            let remove = self.notes.iter()
-                .position(|n| &n.id == nid)
-                .map(|e| self.notes.remove(e))
-                .is_some();
-            match remove {
-                true => {
-                    println!("deleted note {}", nid);
-                }
-                false => {
-                    println!("note {} doesn't exist", nid);
-                }
-            }
-        }
-    }
-
-    /// edit an item in the profile
-    pub fn edit_note(
+    /// open today's journal note (adding it if there isn't one yet) and
+    /// append a timestamped entry to it from `body`, stdin or the editor,
+    /// without any of those the note is just shown
+    pub fn today(
         &mut self,
-        id: usize,
-        title: &String,
         body: &Vec<String>,
-        started: bool,
-        urgent: bool,
-        no_status: bool,
         use_stdin: bool,
         use_editor: bool,
         encrypted: bool,
-        yes: bool
+        yes: bool,
+        json: bool,
+        condensed: bool,
+        relative: bool
     ) -> Result<(), ThecaError> {
-        // let id = args.arg_id[0];
-        let item_pos: usize = match self.notes.iter().position(|n| n.id == id) {
+        let date = try!(strftime(DAYFMT, &now()));
+        let existing = self.notes.iter().find(|n| n.title == date).map(|n| n.id);
+        let id = match existing {
             Some(i) => i,
-            None => specific_fail!(format!("note {} doesn't exist", id))
-        };
-        if !title.is_empty() {
-            match title.replace("\n", "") == "-" {
-                true => match !use_stdin {
-                    true => self.notes[item_pos].body = try!(
-                        stdin().read_to_string()
-                    ),
-                    false => self.notes[item_pos].title = title.replace("\n", "")
-                                                               .to_string()
-                },
-                false => self.notes[item_pos].title = title.replace("\n", "")
-                                                           .to_string()
+            None => {
+                try!(self.add_note(&date, &vec![], false, false, false, false, true));
+                match self.notes.last() {
+                    Some(n) => n.id,
+                    None => 0
+                }
             }
-            // change title
+        };
+        if body.is_empty() && !use_stdin && !use_editor {
+            return self.view_note(id, json, condensed, relative)
         }
-        if started || urgent || no_status {
-            // change status
-            if started {
-                self.notes[item_pos].status = STARTED.to_string();
-            } else if urgent {
-                self.notes[item_pos].status = URGENT.to_string();
-            } else if no_status {
-                self.notes[item_pos].status = NOSTATUS.to_string();
+        let time = try!(strftime("%H:%M", &now()));
+        let item_pos = self.notes.iter().position(|n| n.id == id).unwrap();
+        let old_body = self.notes[item_pos].body.clone();
+        match use_editor && !use_stdin && body.is_empty() {
+            true => {
+                // the whole day is opened with an empty entry at the end to
+                // write in, through edit_note so encrypted profiles get warned
+                let prepared = append_entry(&*old_body, &*time, "");
+                self.notes[item_pos].body = prepared.clone();
+                try!(self.edit_note(
+                    id,
+                    &"".to_string(),
+                    &vec![],
+                    false,
+                    false,
+                    false,
+                    false,
+                    true,
+                    encrypted,
+                    yes
+                ));
+                let item_pos = self.notes.iter().position(|n| n.id == id).unwrap();
+                if self.notes[item_pos].body.trim_right() == prepared.trim_right() {
+                    self.notes[item_pos].body = old_body;
+                    specific_fail_str!("nothing was written, no entry added");
+                }
+            },
+            false => {
+                let entry = match use_stdin {
+                    true => try!(stdin().read_to_string()),
+                    false => body[0].clone()
+                };
+                if entry.trim().is_empty() {
+                    specific_fail_str!("the entry is empty, no entry added");
+                }
+                try!(self.edit_note(
+                    id,
+                    &"".to_string(),
+                    &vec![append_entry(&*old_body, &*time, &*entry)],
+                    false,
+                    false,
+                    false,
+                    false,
+                    false,
+                    encrypted,
+                    yes
+                ));
             }
-        }
+        };
+        Ok(())
+    }
 
-        if !body.is_empty() || use_editor || use_stdin {
-            // change body
-            self.notes[item_pos].body = match use_stdin {
-                true => try!(stdin().read_to_string()),
-                false => match use_editor {
-                    true => {
-                        match istty(STDOUT_FILENO) && istty(STDIN_FILENO) {
-                            true => {
-                                if encrypted && !yes {
-                                    println!(
-                                        "{0}\n\n{1}\n{2}\n\n{0}\n{3}\n",
-                                        "## [WARNING] ##",
-                                        "continuing will write the body of the decrypted note to a temporary",
-                                        "file, increasing the possibilty it could be recovered later.",
-                                        "Are you sure you want to continue?"
-                                    );
-                                    if !try!(get_yn_input()) {specific_fail_str!("ok bye ♥");}
-                                }
-                                let new_body = try!(
-                                    drop_to_editor(&self.notes[item_pos].body)
-                                );
-                                match self.notes[item_pos].body != new_body {
-                                    true => new_body,
-                                    false => self.notes[item_pos].body.clone()
-                                }     
-                            },
-                            false => self.notes[item_pos].body.clone()
-                        }
-                    },
-                    false => body[0].clone()
+    /// print the journal notes between two dates, oldest first
+    pub fn print_journal(&self, from: &String, to: &String, json: bool) -> Result<(), ThecaError> {
+        let today = now();
+        let from = match from.is_empty() {
+            true => "".to_string(),
+            false => try!(parse_day(&*from, &today))
+        };
+        let to = match to.is_empty() {
+            true => "".to_string(),
+            false => try!(parse_day(&*to, &today))
+        };
+        let days: Vec<ThecaItem> = days_between(&self.notes, &*from, &*to).into_iter()
+                                                                          .map(|n| n.clone())
+                                                                          .collect();
+        match json {
+            true => println!("{}", as_pretty_json(&days)),
+            false => match days.is_empty() {
+                true => println!("no journal entries"),
+                false => {
+                    let pages: Vec<String> = days.iter().map(|n| {
+                        format!("# {}\n\n{}", n.title, n.body.trim())
+                    }).collect();
+                    println!("{}", pages.connect("\n\n"));
                 }
-            };
+            }
+        };
+        Ok(())
+    }
+
+    /// set the parent of a note, making it a subtask
+    pub fn set_parent(&mut self, id: usize, parent: usize) -> Result<(), ThecaError> {
+        if !self.notes.iter().any(|n| n.id == parent) {
+            specific_fail!(format!("note {} doesn't exist", parent));
+        }
+        if would_cycle(&self.notes, id, parent) {
+            specific_fail!(format!(
+                "note {} can't be a subtask of note {}, it would be its own parent",
+                id,
+                parent
+            ));
         }
+        match self.notes.iter_mut().find(|n| n.id == id) {
+            Some(n) => n.parent = Some(parent),
+            None => specific_fail!(format!("note {} doesn't exist", id))
+        };
+        Ok(())
+    }
 
-        // update last_touched
-        self.notes[item_pos].last_touched = try!(strftime(DATEFMT, &now()));
-        println!("edited note {}", self.notes[item_pos].id);
+    /// mark the note `id` as blocked by the note `blocker`
+    pub fn add_blocker(&mut self, id: usize, blocker: usize) -> Result<(), ThecaError> {
+        if !self.notes.iter().any(|n| n.id == blocker) {
+            specific_fail!(format!("note {} doesn't exist", blocker));
+        }
+        if would_block_cycle(&self.notes, id, blocker) {
+            specific_fail!(format!(
+                "note {} can't be blocked by note {}, it would be waiting on itself",
+                id,
+                blocker
+            ));
+        }
+        match self.notes.iter_mut().find(|n| n.id == id) {
+            Some(n) => {
+                let mut blocked_by = blockers(n);
+                if !blocked_by.contains(&blocker) {blocked_by.push(blocker);}
+                n.blocked_by = Some(blocked_by);
+            },
+            None => specific_fail!(format!("note {} doesn't exist", id))
+        };
         Ok(())
     }
 
-    /// print information about the profile
-    pub fn stats(&mut self, name: &String) -> Result<(), ThecaError> {
-        let no_s = self.notes.iter().filter(|n| n.status == "").count();
-        let started_s = self.notes.iter().filter(|n| n.status == "Started")
-                                         .count();
-        let urgent_s = self.notes.iter().filter(|n| n.status == "Urgent")
-                                        .count();
-        let tty = istty(STDOUT_FILENO);
-        let min = match self.notes.iter()
-                            .min_by(|n| match parse_last_touched(&*n.last_touched) {
-            Ok(o) => o,
-            Err(_) => now()
-        }) {
-            Some(n) => try!(localize_last_touched_string(&*n.last_touched)),
-            None => specific_fail_str!(
-                "last_touched is not properly formated"
-            )
+    /// stop the note `id` being blocked by the note `blocker`
+    pub fn remove_blocker(&mut self, id: usize, blocker: usize) -> Result<(), ThecaError> {
+        match self.notes.iter_mut().find(|n| n.id == id) {
+            Some(n) => {
+                let blocked_by: Vec<usize> = blockers(n).into_iter()
+                                                        .filter(|b| b != &blocker)
+                                                        .collect();
+                n.blocked_by = match blocked_by.is_empty() {
+                    true => None,
+                    false => Some(blocked_by)
+                };
+            },
+            None => specific_fail!(format!("note {} doesn't exist", id))
         };
-        let max = match self.notes.iter()
-                            .max_by(|n| match parse_last_touched(&*n.last_touched) {
-            Ok(o) => o,
-            Err(_) => now()
-        }) {
-            Some(n) => try!(localize_last_touched_string(&*n.last_touched)),
-            None => specific_fail_str!(
-                "last_touched is not properly formated"
-            )
+        Ok(())
+    }
+
+    /// set the status of a note to Done, as long as nothing is blocking it
+    pub fn mark_done(&mut self, id: usize) -> Result<(), ThecaError> {
+        let open: Vec<String> = open_blockers(&self.notes, id).iter()
+                                                              .map(|b| b.to_string())
+                                                              .collect();
+        if !open.is_empty() {
+            specific_fail!(format!(
+                "note {} can't be done, it is still blocked by note(s) {}",
+                id,
+                open.connect(", ")
+            ));
+        }
+        match self.notes.iter_mut().find(|n| n.id == id) {
+            Some(n) => {
+                n.status = DONE.to_string();
+                n.last_touched = try!(strftime(DATEFMT, &now()));
+            },
+            None => specific_fail!(format!("note {} doesn't exist", id))
         };
-        try!(pretty_line("name: ", &format!("{}\n", name), tty));
-        try!(pretty_line("encrypted: ", &format!("{}\n", self.encrypted), tty));
-        try!(pretty_line("notes: ", &format!("{}\n", self.notes.len()), tty));
-        try!(pretty_line("statuses: ", &format!(
-            "none: {}, started: {}, urgent: {}\n",
-            no_s,
-            started_s,
-            urgent_s
-        ), tty));
-        try!(pretty_line("note ages: ", &format!(
-            "oldest: {}, newest: {}\n",
-            min,
-            max
-        ), tty));
+        try!(self.add_next_occurrence(id));
         Ok(())
     }
 
-    /// print a full item
-    pub fn view_note(
+    /// set when a note is due and/or how often it recurs, `every` can be
+    /// "never" to stop a note recurring
+    pub fn set_schedule(
         &mut self,
         id: usize,
-        json: bool,
-        condensed: bool
+        every: &String,
+        due: &String
     ) -> Result<(), ThecaError> {
-        let id = id;
-        let note_pos = match self.notes.iter().position(|n| n.id == id) {
+        let item_pos: usize = match self.notes.iter().position(|n| n.id == id) {
+            Some(i) => i,
+            None => specific_fail!(format!("note {} doesn't exist", id))
+        };
+        if !due.is_empty() {
+            self.notes[item_pos].due = Some(try!(parse_date(&*due)));
+        }
+        if !every.is_empty() {
+            match &every[..] {
+                "never" => self.notes[item_pos].every = None,
+                _ => {
+                    let schedule = try!(Schedule::parse(&*every));
+                    // the first occurrence is due one interval from now
+                    if self.notes[item_pos].due.is_none() {
+                        let first = try!(schedule.next_after(&now()));
+                        self.notes[item_pos].due = Some(try!(strftime(DATEFMT, &first)));
+                    }
+                    self.notes[item_pos].every = Some(every.clone());
+                    // the series is named after the note that started it
+                    if self.notes[item_pos].series.is_none() {
+                        self.notes[item_pos].series = self.notes[item_pos].uuid.clone();
+                    }
+                }
+            };
+        }
+        Ok(())
+    }
+
+    /// when a recurring note is done add the next note in its series with
+    /// the same title and body (with any checklist unchecked), only the
+    /// newest note in a series recurs
+    fn add_next_occurrence(&mut self, id: usize) -> Result<(), ThecaError> {
+        let item_pos: usize = match self.notes.iter().position(|n| n.id == id) {
+            Some(i) => i,
+            None => return Ok(())
+        };
+        let every = match self.notes[item_pos].every {
+            Some(ref e) => e.clone(),
+            None => return Ok(())
+        };
+        let schedule = try!(Schedule::parse(&*every));
+        let now_tm = now();
+        let due = match self.notes[item_pos].due {
+            Some(ref d) => try!(parse_last_touched(&*d)),
+            None => now_tm
+        };
+        let next_due = try!(schedule.next_occurrence(&due, &now_tm));
+
+        let mut next = self.notes[item_pos].clone();
+        next.id = self.next_note_id();
+        next.uuid = Some(new_uuid());
+        next.status = NOSTATUS.to_string();
+        next.body = reset(&*next.body);
+        next.blocked_by = None;
+        next.archived = None;
+        next.time_log = None;
+        next.due = Some(try!(strftime(DATEFMT, &next_due)));
+        next.last_touched = try!(strftime(DATEFMT, &now_tm));
+        println!(
+            "added note {}, the next in the series (due {})",
+            next.id,
+            try!(localize_last_touched_string(&*next.due.clone().unwrap()))
+        );
+        self.notes[item_pos].every = None;
+        self.notes.push(next);
+        Ok(())
+    }
+
+    /// check (or uncheck) the `item`th checklist item in the body of a note,
+    /// if `auto_done` is set the note is marked done once every item is
+    /// checked (and put back to started if one is unchecked again)
+    pub fn check_item(
+        &mut self,
+        id: usize,
+        item: usize,
+        checked: bool,
+        auto_done: bool
+    ) -> Result<(), ThecaError> {
+        let item_pos: usize = match self.notes.iter().position(|n| n.id == id) {
+            Some(i) => i,
+            None => specific_fail!(format!("note {} doesn't exist", id))
+        };
+        self.notes[item_pos].body = try!(set_item(
+            &*self.notes[item_pos].body,
+            item,
+            checked
+        ));
+        self.notes[item_pos].last_touched = try!(strftime(DATEFMT, &now()));
+        println!(
+            "{} item {} of note {} ({})",
+            match checked {
+                true => "checked",
+                false => "unchecked"
+            },
+            item,
+            id,
+            progress_string(&*self.notes[item_pos].body)
+        );
+        if auto_done {
+            match progress(&*self.notes[item_pos].body) {
+                Some((c, t)) if c == t && self.notes[item_pos].status != DONE => {
+                    match open_blockers(&self.notes, id).is_empty() {
+                        true => {
+                            try!(self.mark_done(id));
+                            println!("marked note {} as done", id);
+                        },
+                        false => println!(
+                            "note {} is still blocked, it won't be marked as done",
+                            id
+                        )
+                    };
+                },
+                Some((c, t)) if c < t && self.notes[item_pos].status == DONE => {
+                    self.notes[item_pos].status = STARTED.to_string();
+                    println!("marked note {} as started", id);
+                },
+                _ => ()
+            };
+        }
+        Ok(())
+    }
+
+    /// work out which notes a delete should remove, if any of them have
+    /// subtasks ask whether those should go as well or be moved up a level
+    pub fn delete_ids(
+        &self,
+        id: &Vec<usize>,
+        yes: bool
+    ) -> Result<Vec<usize>, ThecaError> {
+        let mut ids = id.clone();
+        for nid in id.iter() {
+            let below = descendants(&self.notes, *nid);
+            if below.iter().all(|d| ids.contains(d)) {continue;}
+            if !yes {
+                println!(
+                    "note {} has {} subtask(s), delete them as well? (otherwise they will be moved up to its parent)",
+                    nid,
+                    below.len()
+                );
+                if !try!(get_yn_input()) {continue;}
+            }
+            for d in below.iter() {
+                if !ids.contains(d) {ids.push(*d);}
+            }
+        }
+        // warn about links that are going to be left dangling
+        let index = LinkIndex::new(&self.notes);
+        let mut dangling = false;
+        for nid in ids.iter() {
+            let referrers: Vec<String> = index.backlinks(*nid).iter()
+                                              .filter(|b| !ids.contains(b))
+                                              .map(|b| b.to_string())
+                                              .collect();
+            if !referrers.is_empty() && self.notes.iter().any(|n| &n.id == nid) {
+                println!(
+                    "note {} is linked to from note(s) {}",
+                    nid,
+                    referrers.connect(", ")
+                );
+                dangling = true;
+            }
+        }
+        if dangling && !yes {
+            println!("are you sure you want to delete it?");
+            if !try!(get_yn_input()) {specific_fail_str!("ok bye ♥");}
+        }
+        Ok(ids)
+    }
+
+    /// delete an item from the profile
+    pub fn delete_note(&mut self, id: &Vec<usize>) {
+        for nid in id.iter() {
+            let parent = match self.notes.iter().find(|n| &n.id == nid) {
+                Some(n) => n.parent,
+                None => None
+            };
+            let remove = self.notes.iter()
+                .position(|n| &n.id == nid)
+                .map(|e| self.notes.remove(e))
+                .is_some();
+            match remove {
+                true => {
+                    // subtasks of a deleted note move up a level
+                    for n in self.notes.iter_mut()
+                                       .filter(|n| n.parent == Some(*nid)) {
+                        n.parent = parent;
+                    }
+                    // and nothing is blocked by it anymore
+                    for n in self.notes.iter_mut()
+                                       .filter(|n| blockers(n).contains(nid)) {
+                        let blocked_by: Vec<usize> = blockers(n).into_iter()
+                                                                .filter(|b| b != nid)
+                                                                .collect();
+                        n.blocked_by = match blocked_by.is_empty() {
+                            true => None,
+                            false => Some(blocked_by)
+                        };
+                    }
+                    // and it drops out of the manual order
+                    match self.order {
+                        Some(ref mut o) => o.retain(|i| i != nid),
+                        None => ()
+                    };
+                    println!("deleted note {}", nid);
+                }
+                false => {
+                    println!("note {} doesn't exist", nid);
+                }
+            }
+        }
+    }
+
+    /// archive (or unarchive) notes, archived notes are kept in the profile
+    /// but hidden unless --archived or --all are used
+    pub fn archive_notes(
+        &mut self,
+        id: &Vec<usize>,
+        archive: bool
+    ) -> Result<(), ThecaError> {
+        let now_str = try!(strftime(DATEFMT, &now()));
+        for nid in id.iter() {
+            match self.notes.iter_mut().find(|n| &n.id == nid) {
+                Some(n) => {
+                    match (archive, is_archived(n)) {
+                        (true, true) => println!("note {} is already archived", nid),
+                        (false, false) => println!("note {} isn't archived", nid),
+                        (true, false) => {
+                            n.archived = Some(now_str.clone());
+                            println!("archived note {}", nid);
+                        },
+                        (false, true) => {
+                            n.archived = None;
+                            println!("unarchived note {}", nid);
+                        }
+                    };
+                },
+                None => println!("note {} doesn't exist", nid)
+            };
+        }
+        Ok(())
+    }
+
+    /// archive notes that have been done for more than `days` days
+    pub fn auto_archive(&mut self, days: usize) -> Result<(), ThecaError> {
+        let stale = try!(stale_done(&self.notes, days, &now()));
+        let now_str = try!(strftime(DATEFMT, &now()));
+        for n in self.notes.iter_mut().filter(|n| stale.contains(&n.id)) {
+            n.archived = Some(now_str.clone());
+            println!(
+                "archived note {} (done for more than {} days)",
+                n.id,
+                days
+            );
+        }
+        Ok(())
+    }
+
+    /// pin (or unpin) notes to the top of lists
+    pub fn pin_notes(&mut self, id: &Vec<usize>, pin: bool) -> Result<(), ThecaError> {
+        for nid in id.iter() {
+            match self.notes.iter_mut().find(|n| &n.id == nid) {
+                Some(n) => {
+                    match (pin, is_pinned(n)) {
+                        (true, true) => println!("note {} is already pinned", nid),
+                        (false, false) => println!("note {} isn't pinned", nid),
+                        (true, false) => {
+                            n.pinned = Some(true);
+                            println!("pinned note {}", nid);
+                        },
+                        (false, true) => {
+                            n.pinned = None;
+                            println!("unpinned note {}", nid);
+                        }
+                    };
+                },
+                None => println!("note {} doesn't exist", nid)
+            };
+        }
+        Ok(())
+    }
+
+    /// the ids of the notes picked by id specs and/or a query, selections
+    /// that could be more than the ids given (ranges, exclusions or a
+    /// query) say how many notes are going to change and ask first
+    pub fn select_notes(
+        &self,
+        specs: &Vec<String>,
+        where_clause: &String,
+        archived: bool,
+        all: bool,
+        action: &str,
+        yes: bool
+    ) -> Result<Vec<usize>, ThecaError> {
+        let existing: Vec<usize> = self.notes.iter().map(|n| n.id).collect();
+        let matched: Option<Vec<usize>> = match where_clause.is_empty() {
+            true => None,
+            false => Some(try!(filter_where(
+                visible(&self.notes, archived, all),
+                &*where_clause
+            )).iter().map(|n| n.id).collect())
+        };
+        let ids = try!(select_ids(&specs[..], &existing, matched.as_ref().map(|m| &m[..])));
+        if (matched.is_some() || try!(is_bulk(&specs[..]))) && !yes {
+            let listed: Vec<String> = ids.iter().map(|i| i.to_string()).collect();
+            println!(
+                "{} {} note(s) ({})?",
+                action,
+                ids.len(),
+                listed.connect(", ")
+            );
+            if !try!(get_yn_input()) {specific_fail_str!("ok bye ♥");}
+        }
+        Ok(ids)
+    }
+
+    /// store the attachments again after the encryption (or key) of the
+    /// profile changes, so the blobs of an encrypted profile are always
+    /// encrypted with its key, the blobs they replace are removed
+    pub fn rekey_attachments(
+        &mut self,
+        profile_folder: &String,
+        profile_name: &String,
+        old_key: Option<&String>,
+        new_key: Option<&String>
+    ) -> Result<(), ThecaError> {
+        let store = try!(store_path(profile_folder, profile_name));
+        let from_key = old_key.map(|k| password_to_key(&**k));
+        let to_key = new_key.map(|k| password_to_key(&**k));
+        let replaced = try!(restore_blobs(
+            &store,
+            &mut self.notes,
+            from_key.as_ref().map(|k| &**k),
+            to_key.as_ref().map(|k| &**k)
+        ));
+        let referenced = referenced_blobs(&self.notes);
+        for hash in replaced.iter().filter(|h| !referenced.contains(*h)) {
+            try!(remove_file(&store.join(hash)));
+        }
+        Ok(())
+    }
+
+    /// save the arguments of a named view
+    pub fn save_view(&mut self, name: &String, args: &Vec<String>) -> Result<(), ThecaError> {
+        try!(check_name(&*name));
+        let mut views = self.views.clone().unwrap_or(vec![]);
+        match set_view(&mut views, &*name, args) {
+            true => println!("view {} updated", name),
+            false => println!("view {} saved", name)
+        };
+        self.views = Some(views);
+        Ok(())
+    }
+
+    /// forget a named view
+    pub fn remove_view(&mut self, name: &String) -> Result<(), ThecaError> {
+        let mut views = self.views.clone().unwrap_or(vec![]);
+        match views.iter().position(|v| &v.name == name) {
+            Some(i) => {views.remove(i);},
+            None => specific_fail!(format!("there is no view called {}", name))
+        };
+        self.views = match views.is_empty() {
+            true => None,
+            false => Some(views)
+        };
+        println!("view {} removed", name);
+        Ok(())
+    }
+
+    /// a named view
+    pub fn saved_view(&self, name: &String) -> Result<&View, ThecaError> {
+        let found = match self.views {
+            Some(ref views) => views.iter().find(|v| &v.name == name),
+            None => None
+        };
+        match found {
+            Some(v) => Ok(v),
+            None => specific_fail!(format!("there is no view called {}", name))
+        }
+    }
+
+    /// print the named views and their arguments (one per line so shell
+    /// completion can use them)
+    pub fn list_views(&self, json: bool) -> Result<(), ThecaError> {
+        let views = self.views.clone().unwrap_or(vec![]);
+        match json {
+            true => println!("{}", as_pretty_json(&views)),
+            false => for v in views.iter() {
+                println!("{}: {}", v.name, v.args.connect(" "));
+            }
+        };
+        Ok(())
+    }
+
+    /// find and replace in the notes shown (that match `where_clause`),
+    /// returns false if there's nothing to save, after a dry run or when
+    /// the replacement wouldn't change anything
+    pub fn replace(
+        &mut self,
+        pattern: &String,
+        replacement: &String,
+        regex: bool,
+        target: &String,
+        archived: bool,
+        all: bool,
+        where_clause: &String,
+        dry_run: bool,
+        yes: bool
+    ) -> Result<bool, ThecaError> {
+        if pattern.is_empty() {
+            specific_fail_str!("there is nothing to replace, the pattern is empty.");
+        }
+        let target = try!(parse_target(&*target));
+        let re = match regex {
+            true => match Regex::new(&*pattern) {
+                Ok(r) => Some(r),
+                Err(e) => specific_fail!(format!("regex error: {}.", e.msg))
+            },
+            false => None
+        };
+        let candidates = try!(filter_where(
+            visible(&self.notes, archived, all),
+            &*where_clause
+        ));
+        let changes: Vec<(&ThecaItem, Change)> = candidates.iter().filter_map(|n| {
+            note_change(n, &*pattern, re.as_ref(), &*replacement, target).map(|c| (n, c))
+        }).collect();
+        if changes.is_empty() {
+            println!("no notes would be changed");
+            return Ok(false)
+        }
+        let tty = istty(STDOUT_FILENO);
+        for &(n, ref c) in changes.iter() {
+            try!(print_diff(n, c, tty));
+        }
+        if dry_run {return Ok(false)}
+        if !yes {
+            println!("make these changes to {} note(s)?", changes.len());
+            if !try!(get_yn_input()) {specific_fail_str!("ok bye ♥");}
+        }
+        // only the notes that actually changed count as touched
+        let touched = try!(strftime(DATEFMT, &now()));
+        let count = changes.len();
+        for (_, c) in changes.into_iter() {
+            match self.notes.iter_mut().find(|n| n.id == c.id) {
+                Some(n) => {
+                    n.title = c.title;
+                    n.body = c.body;
+                    n.last_touched = touched.clone();
+                },
+                None => ()
+            };
+        }
+        println!("changed {} note(s)", count);
+        Ok(true)
+    }
+
+    /// put the profile back the way it was before the last change saved
+    /// to it, returns the key the restored profile should be saved with
+    pub fn undo(
+        &mut self,
+        profile_folder: &String,
+        profile: &String,
+        key: &String
+    ) -> Result<String, ThecaError> {
+        let path = try!(undo_path(profile_folder, profile));
+        if !path.is_file() {
+            specific_fail!(format!("there is nothing to undo in '{}'.", profile));
+        }
+        let mut file = try!(File::open(&path));
+        let mut contents_buf: Vec<u8> = vec![];
+        try!(file.read_to_end(&mut contents_buf));
+        // the profile wasn't necessarily encrypted (or encrypted with the
+        // same key) back then, so try it as plain JSON first
+        let (restored, undo_key) = match ThecaProfile::from_bytes(
+            contents_buf.clone(),
+            key,
+            false,
+            &path
+        ) {
+            Ok(p) => (p, "".to_string()),
+            Err(_) => {
+                let undo_key = match key.is_empty() {
+                    true => try!(get_password()),
+                    false => key.clone()
+                };
+                match ThecaProfile::from_bytes(contents_buf.clone(), &undo_key, true, &path) {
+                    Ok(p) => (p, undo_key),
+                    Err(_) if !key.is_empty() => {
+                        println!("the last state of '{}' uses a different key", profile);
+                        let undo_key = try!(get_password());
+                        (try!(ThecaProfile::from_bytes(contents_buf, &undo_key, true, &path)), undo_key)
+                    },
+                    Err(e) => return Err(e)
+                }
+            }
+        };
+        // encrypt-profile and decrypt-profile replace the attachment blobs,
+        // store them again the way the restored state expects them
+        let store = try!(store_path(profile_folder, profile));
+        if !missing_blobs(&store, &restored.notes).is_empty() {
+            let from_key = match self.encrypted {
+                true => Some(password_to_key(&key[..])),
+                false => None
+            };
+            let to_key = match restored.encrypted {
+                true => Some(password_to_key(&undo_key[..])),
+                false => None
+            };
+            let mut current = self.notes.clone();
+            try!(restore_blobs(
+                &store,
+                &mut current,
+                from_key.as_ref().map(|k| &**k),
+                to_key.as_ref().map(|k| &**k)
+            ));
+        }
+        *self = restored;
+        println!("undid the last change to '{}'", profile);
+        Ok(undo_key)
+    }
+
+    /// move a note directly before another in the manual order of the
+    /// profile (used by --manual)
+    pub fn move_note(&mut self, id: usize, before: usize) -> Result<(), ThecaError> {
+        for nid in [id, before].iter() {
+            if !self.notes.iter().any(|n| &n.id == nid) {
+                specific_fail!(format!("note {} doesn't exist", nid));
+            }
+        }
+        if id == before {
+            specific_fail!(format!("cannot move note {} before itself", id));
+        }
+        let order = manual_order(&self.notes, &self.order);
+        self.order = Some(move_before(&order, id, before));
+        println!("moved note {} before note {}", id, before);
+        Ok(())
+    }
+
+    /// set the priority of a note, "none" clears it
+    pub fn set_priority(&mut self, id: usize, priority: &String) -> Result<(), ThecaError> {
+        let priority = try!(parse_priority(&*priority));
+        match self.notes.iter_mut().find(|n| n.id == id) {
+            Some(n) => {
+                n.priority = priority;
+                n.last_touched = try!(strftime(DATEFMT, &now()));
+            },
+            None => specific_fail!(format!("note {} doesn't exist", id))
+        };
+        Ok(())
+    }
+
+    /// set (or with an empty value remove) custom fields on a note from
+    /// `name=value` or `name:type=value` assignments
+    pub fn set_fields(
+        &mut self,
+        id: usize,
+        assignments: &Vec<String>
+    ) -> Result<(), ThecaError> {
+        let item_pos: usize = match self.notes.iter().position(|n| n.id == id) {
+            Some(i) => i,
+            None => specific_fail!(format!("note {} doesn't exist", id))
+        };
+        let mut fields = match self.notes[item_pos].fields {
+            Some(ref f) => f.clone(),
+            None => vec![]
+        };
+        for a in assignments.iter() {
+            let (name, field) = try!(parse_assignment(&*a));
+            match (fields.iter().position(|f| f.name == name), field) {
+                (Some(i), Some(f)) => fields[i] = f,
+                (None, Some(f)) => fields.push(f),
+                (Some(i), None) => {fields.remove(i);},
+                (None, None) => println!("note {} has no field {}", id, name)
+            };
+        }
+        self.notes[item_pos].fields = match fields.is_empty() {
+            true => None,
+            false => Some(fields)
+        };
+        self.notes[item_pos].last_touched = try!(strftime(DATEFMT, &now()));
+        Ok(())
+    }
+
+    /// start a timer on a note (and mark it as started), unless
+    /// `concurrent` is set any other running timers are stopped first
+    pub fn start_timer(&mut self, id: usize, concurrent: bool) -> Result<(), ThecaError> {
+        let item_pos: usize = match self.notes.iter().position(|n| n.id == id) {
+            Some(i) => i,
+            None => specific_fail!(format!("note {} doesn't exist", id))
+        };
+        if running(&self.notes[item_pos]) {
+            specific_fail!(format!("a timer is already running on note {}", id));
+        }
+        if !concurrent {
+            let others = running_notes(&self.notes);
+            if !others.is_empty() {
+                try!(self.stop_timer(&others));
+            }
+        }
+        let now_str = try!(strftime(DATEFMT, &now()));
+        let note = &mut self.notes[item_pos];
+        let mut log = match note.time_log {
+            Some(ref l) => l.clone(),
+            None => vec![]
+        };
+        log.push(TimeEntry {start: now_str.clone(), stop: None});
+        note.time_log = Some(log);
+        if note.status == NOSTATUS {
+            note.status = STARTED.to_string();
+        }
+        note.last_touched = now_str;
+        println!("started timer on note {}", id);
+        Ok(())
+    }
+
+    /// stop the running timers on the notes `id`, or on every note if no
+    /// ids are given
+    pub fn stop_timer(&mut self, id: &Vec<usize>) -> Result<(), ThecaError> {
+        let ids = match id.is_empty() {
+            true => running_notes(&self.notes),
+            false => id.clone()
+        };
+        if ids.is_empty() {
+            specific_fail_str!("there are no running timers");
+        }
+        let now_tm = now();
+        let now_str = try!(strftime(DATEFMT, &now_tm));
+        for nid in ids.iter() {
+            let note = match self.notes.iter_mut().find(|n| &n.id == nid) {
+                Some(n) => n,
+                None => specific_fail!(format!("note {} doesn't exist", nid))
+            };
+            if !running(note) {
+                println!("no timer is running on note {}", nid);
+                continue;
+            }
+            let mut started = now_str.clone();
+            match note.time_log {
+                Some(ref mut l) => for e in l.iter_mut().filter(|e| e.stop.is_none()) {
+                    started = e.start.clone();
+                    e.stop = Some(now_str.clone());
+                },
+                None => ()
+            };
+            note.last_touched = now_str.clone();
+            let spent = (now_tm.to_timespec() -
+                         try!(parse_last_touched(&*started)).to_timespec()).num_seconds();
+            println!("stopped timer on note {} ({})", nid, format_duration(spent));
+        }
+        Ok(())
+    }
+
+    /// print the time logged per note and per tag, for the current week
+    /// if `week` is set, as text, JSON or CSV
+    pub fn print_timesheet(&self, week: bool, json: bool, csv: bool) -> Result<(), ThecaError> {
+        let now_tm = now();
+        let from = week_start(&now_tm);
+        let sheet = try!(timesheet(
+            &self.notes,
+            match week {
+                true => Some(&from),
+                false => None
+            },
+            &now_tm
+        ));
+        if json {
+            println!("{}", as_pretty_json(&sheet));
+        } else if csv {
+            println!("kind,id,name,seconds");
+            for n in sheet.notes.iter() {
+                println!("note,{},{},{}", n.id, csv_field(&*n.title), n.seconds);
+            }
+            for t in sheet.tags.iter() {
+                println!("tag,,{},{}", csv_field(&*t.tag), t.seconds);
+            }
+        } else {
+            if sheet.notes.is_empty() {
+                match week {
+                    true => println!("no time has been logged this week"),
+                    false => println!("no time has been logged")
+                };
+                return Ok(())
+            }
+            let tty = istty(STDOUT_FILENO);
+            let title_width = match sheet.notes.iter().max_by(|n| n.title.len()) {
+                Some(n) => n.title.len(),
+                None => 0
+            };
+            let id_width = match sheet.notes.iter().max_by(|n| n.id.to_string().len()) {
+                Some(n) => n.id.to_string().len(),
+                None => 0
+            };
+            try!(pretty_line("notes\n-----\n", &"".to_string(), tty));
+            for n in sheet.notes.iter() {
+                println!(
+                    "{}  {}  {}{}",
+                    format_field(&n.id.to_string(), id_width, false),
+                    format_field(&n.title, title_width, false),
+                    format_duration(n.seconds),
+                    match self.notes.iter().any(|o| o.id == n.id && running(o)) {
+                        true => " (running)",
+                        false => ""
+                    }
+                );
+            }
+            if !sheet.tags.is_empty() {
+                let tag_width = match sheet.tags.iter().max_by(|t| t.tag.len()) {
+                    Some(t) => t.tag.len()+1,
+                    None => 0
+                };
+                try!(pretty_line("\ntags\n----\n", &"".to_string(), tty));
+                for t in sheet.tags.iter() {
+                    println!(
+                        "{}  {}",
+                        format_field(&format!("#{}", t.tag), tag_width, false),
+                        format_duration(t.seconds)
+                    );
+                }
+            }
+            try!(pretty_line("\ntotal: ", &format!("{}\n", format_duration(sheet.total)), tty));
+        }
+        Ok(())
+    }
+
+    /// edit an item in the profile
+    pub fn edit_note(
+        &mut self,
+        id: usize,
+        title: &String,
+        body: &Vec<String>,
+        started: bool,
+        urgent: bool,
+        no_status: bool,
+        use_stdin: bool,
+        use_editor: bool,
+        encrypted: bool,
+        yes: bool
+    ) -> Result<(), ThecaError> {
+        // let id = args.arg_id[0];
+        let item_pos: usize = match self.notes.iter().position(|n| n.id == id) {
+            Some(i) => i,
+            None => specific_fail!(format!("note {} doesn't exist", id))
+        };
+        if !title.is_empty() {
+            match title.replace("\n", "") == "-" {
+                true => match !use_stdin {
+                    true => self.notes[item_pos].body = try!(
+                        stdin().read_to_string()
+                    ),
+                    false => self.notes[item_pos].title = title.replace("\n", "")
+                                                               .to_string()
+                },
+                false => self.notes[item_pos].title = title.replace("\n", "")
+                                                           .to_string()
+            }
+            // change title
+        }
+        if started || urgent || no_status {
+            // change status
+            if started {
+                self.notes[item_pos].status = STARTED.to_string();
+            } else if urgent {
+                self.notes[item_pos].status = URGENT.to_string();
+            } else if no_status {
+                self.notes[item_pos].status = NOSTATUS.to_string();
+            }
+        }
+
+        if !body.is_empty() || use_editor || use_stdin {
+            // change body
+            self.notes[item_pos].body = match use_stdin {
+                true => try!(stdin().read_to_string()),
+                false => match use_editor {
+                    true => {
+                        match istty(STDOUT_FILENO) && istty(STDIN_FILENO) {
+                            true => {
+                                if encrypted && !yes {
+                                    println!(
+                                        "{0}\n\n{1}\n{2}\n\n{0}\n{3}\n",
+                                        "## [WARNING] ##",
+                                        "continuing will write the body of the decrypted note to a temporary",
+                                        "file, increasing the possibilty it could be recovered later.",
+                                        "Are you sure you want to continue?"
+                                    );
+                                    if !try!(get_yn_input()) {specific_fail_str!("ok bye ♥");}
+                                }
+                                let new_body = try!(
+                                    drop_to_editor(&self.notes[item_pos].body)
+                                );
+                                match self.notes[item_pos].body != new_body {
+                                    true => new_body,
+                                    false => self.notes[item_pos].body.clone()
+                                }     
+                            },
+                            false => self.notes[item_pos].body.clone()
+                        }
+                    },
+                    false => body[0].clone()
+                }
+            };
+        }
+
+        // update last_touched
+        self.notes[item_pos].last_touched = try!(strftime(DATEFMT, &now()));
+        println!("edited note {}", self.notes[item_pos].id);
+        Ok(())
+    }
+
+    /// copy a file into the attachment store and attach it to a note
+    pub fn attach(
+        &mut self,
+        id: usize,
+        file: &String,
+        profile_folder: &String,
+        profile_name: &String,
+        key: &String
+    ) -> Result<(), ThecaError> {
+        let item_pos: usize = match self.notes.iter().position(|n| n.id == id) {
+            Some(i) => i,
+            None => specific_fail!(format!("note {} doesn't exist", id))
+        };
+        let file_path = Path::new(&file[..]);
+        if !file_path.is_file() {
+            specific_fail!(format!("{} is not a file.", file_path.display()));
+        }
+        let name = match file_path.file_name() {
+            Some(n) => n.to_str().unwrap_or("").to_string(),
+            None => specific_fail!(format!("{} is not a file.", file_path.display()))
+        };
+        let mut contents: Vec<u8> = vec![];
+        try!(try!(File::open(file_path)).read_to_end(&mut contents));
+
+        let store = try!(store_path(profile_folder, profile_name));
+        let hash = match self.encrypted {
+            true => {
+                let key = password_to_key(&key[..]);
+                try!(store_blob(&store, &contents, Some(&*key)))
+            },
+            false => try!(store_blob(&store, &contents, None))
+        };
+
+        let mut attachments = match self.notes[item_pos].attachments {
+            Some(ref a) => a.clone(),
+            None => vec![]
+        };
+        // attaching a file with the same name again replaces it
+        attachments.retain(|a| a.name != name);
+        attachments.push(Attachment {
+            name: name.clone(),
+            hash: hash,
+            size: contents.len() as u64
+        });
+        self.notes[item_pos].attachments = Some(attachments);
+        self.notes[item_pos].last_touched = try!(strftime(DATEFMT, &now()));
+        println!("attached {} to note {}", name, id);
+        Ok(())
+    }
+
+    /// print the files attached to a note
+    pub fn list_attachments(&self, id: usize, json: bool) -> Result<(), ThecaError> {
+        let attachments = match self.notes.iter().find(|n| n.id == id) {
+            Some(n) => match n.attachments {
+                Some(ref a) => a.clone(),
+                None => vec![]
+            },
+            None => specific_fail!(format!("note {} doesn't exist", id))
+        };
+        match json {
+            true => println!("{}", as_pretty_json(&attachments)),
+            false => {
+                if attachments.is_empty() {
+                    println!("note {} has no attachments", id);
+                }
+                for a in attachments.iter() {
+                    println!("{}  {} bytes  {}", a.name, a.size, &a.hash[0..12]);
+                }
+            }
+        };
+        Ok(())
+    }
+
+    /// write an attachment of a note back out to the current folder
+    pub fn extract(
+        &self,
+        id: usize,
+        name: &String,
+        profile_folder: &String,
+        profile_name: &String,
+        key: &String,
+        yes: bool
+    ) -> Result<(), ThecaError> {
+        let attachment = match self.notes.iter().find(|n| n.id == id) {
+            Some(n) => match n.attachments {
+                Some(ref a) => match a.iter().find(|a| &a.name == name) {
+                    Some(a) => a.clone(),
+                    None => specific_fail!(format!(
+                        "note {} has no attachment called {}",
+                        id,
+                        name
+                    ))
+                },
+                None => specific_fail!(format!("note {} has no attachments", id))
+            },
+            None => specific_fail!(format!("note {} doesn't exist", id))
+        };
+        let store = try!(store_path(profile_folder, profile_name));
+        let contents = match self.encrypted {
+            true => {
+                let key = password_to_key(&key[..]);
+                try!(read_blob(&store, &attachment.hash, Some(&*key)))
+            },
+            false => try!(read_blob(&store, &attachment.hash, None))
+        };
+        let out_path = Path::new(&name[..]);
+        if out_path.exists() && !yes {
+            println!(
+                "{} already exists, would you like to overwrite it?",
+                out_path.display()
+            );
+            if !try!(get_yn_input()) {specific_fail_str!("ok bye ♥");}
+        }
+        let mut file = try!(File::create(out_path));
+        try!(file.write_all(&contents));
+        println!("extracted {} from note {}", name, id);
+        Ok(())
+    }
+
+    /// check the attachment store, reporting attachments whose blobs are
+    /// missing and removing blobs no note references anymore
+    pub fn verify(
+        &self,
+        profile_folder: &String,
+        profile_name: &String,
+        yes: bool
+    ) -> Result<(), ThecaError> {
+        let store = try!(store_path(profile_folder, profile_name));
+        let missing = missing_blobs(&store, &self.notes);
+        for &(id, ref name) in missing.iter() {
+            println!("note {}: attachment {} is missing", id, name);
+        }
+        let orphans = try!(orphaned_blobs(&store, &self.notes));
+        if !orphans.is_empty() {
+            println!("{} attachment blob(s) aren't used by any note", orphans.len());
+            if !yes {
+                println!("would you like to remove them?");
+            }
+            if yes || try!(get_yn_input()) {
+                for o in orphans.iter() {try!(remove_file(o));}
+                println!("removed {} blob(s)", orphans.len());
+            }
+        }
+        if missing.is_empty() && orphans.is_empty() {
+            println!("no problems found");
+        }
+        Ok(())
+    }
+
+    /// print information about the profile
+    pub fn stats(
+        &mut self,
+        name: &String,
+        archived: bool,
+        all: bool
+    ) -> Result<(), ThecaError> {
+        let notes = visible(&self.notes, archived, all);
+        let no_s = notes.iter().filter(|n| n.status == "").count();
+        let started_s = notes.iter().filter(|n| n.status == "Started")
+                                    .count();
+        let urgent_s = notes.iter().filter(|n| n.status == "Urgent")
+                                   .count();
+        let done_s = notes.iter().filter(|n| n.status == "Done")
+                                 .count();
+        let archived_s = self.notes.iter().filter(|n| is_archived(n)).count();
+        let tty = istty(STDOUT_FILENO);
+        let min = notes.iter().min_by(|n| match parse_last_touched(&*n.last_touched) {
+            Ok(o) => o,
+            Err(_) => now()
+        });
+        let max = notes.iter().max_by(|n| match parse_last_touched(&*n.last_touched) {
+            Ok(o) => o,
+            Err(_) => now()
+        });
+        // there are no ages when none of the notes are shown (every note
+        // archived, say)
+        let ages = match (min, max) {
+            (Some(oldest), Some(newest)) => format!(
+                "oldest: {}, newest: {}\n",
+                try!(localize_last_touched_string(&*oldest.last_touched)),
+                try!(localize_last_touched_string(&*newest.last_touched))
+            ),
+            _ => "none\n".to_string()
+        };
+        try!(pretty_line("name: ", &format!("{}\n", name), tty));
+        try!(pretty_line("encrypted: ", &format!("{}\n", self.encrypted), tty));
+        try!(pretty_line("notes: ", &format!("{}\n", notes.len()), tty));
+        try!(pretty_line("archived: ", &format!("{}\n", archived_s), tty));
+        try!(pretty_line("statuses: ", &format!(
+            "none: {}, started: {}, urgent: {}, done: {}\n",
+            no_s,
+            started_s,
+            urgent_s,
+            done_s
+        ), tty));
+        try!(pretty_line("note ages: ", &ages, tty));
+        let now_tm = now();
+        let mut timers: Vec<String> = vec![];
+        for n in self.notes.iter().filter(|n| running(n)) {
+            let since = match n.time_log {
+                Some(ref l) => match l.iter().find(|e| e.stop.is_none()) {
+                    Some(e) => (now_tm.to_timespec() -
+                                try!(parse_last_touched(&*e.start)).to_timespec())
+                               .num_seconds(),
+                    None => 0
+                },
+                None => 0
+            };
+            timers.push(format!("note {} ({})", n.id, format_duration(since)));
+        }
+        if !timers.is_empty() {
+            try!(pretty_line("active timer: ", &format!("{}\n", timers.connect(", ")), tty));
+        }
+        Ok(())
+    }
+
+    /// print a full item
+    pub fn view_note(
+        &mut self,
+        id: usize,
+        json: bool,
+        condensed: bool,
+        relative: bool
+    ) -> Result<(), ThecaError> {
+        let id = id;
+        let note_pos = match self.notes.iter().position(|n| n.id == id) {
             Some(i) => i,
             None => specific_fail!(format!("note {} doesn't exist", id))
         };
@@ -708,6 +2123,22 @@ impl ThecaProfile {
                             self.notes[note_pos].title),
                             tty
                         ));
+                        match self.notes[note_pos].uuid {
+                            Some(ref u) => try!(pretty_line(
+                                "uuid: ",
+                                &format!("{}\n", u),
+                                tty
+                            )),
+                            None => ()
+                        };
+                        match self.notes[note_pos].priority {
+                            Some(ref p) => try!(pretty_line(
+                                "priority: ",
+                                &format!("{}\n", p),
+                                tty
+                            )),
+                            None => ()
+                        };
                         if !self.notes[note_pos].status.is_empty() {
                             try!(pretty_line("status: ", &format!(
                                 "{}\n",
@@ -720,8 +2151,9 @@ impl ThecaProfile {
                             &format!(
                                 "{}\n",
                                 try!(
-                                    localize_last_touched_string(
-                                        &*self.notes[note_pos].last_touched
+                                    format_last_touched(
+                                        &*self.notes[note_pos].last_touched,
+                                        relative
                                     )
                                 )
                             ),
@@ -739,6 +2171,22 @@ impl ThecaProfile {
                             self.notes[note_pos].title),
                             tty
                         ));
+                        match self.notes[note_pos].uuid {
+                            Some(ref u) => try!(pretty_line(
+                                "uuid\n----\n",
+                                &format!("{}\n\n", u),
+                                tty
+                            )),
+                            None => ()
+                        };
+                        match self.notes[note_pos].priority {
+                            Some(ref p) => try!(pretty_line(
+                                "priority\n--------\n",
+                                &format!("{}\n\n", p),
+                                tty
+                            )),
+                            None => ()
+                        };
                         if !self.notes[note_pos].status.is_empty() {
                             try!(pretty_line(
                                 "status\n------\n",
@@ -751,8 +2199,9 @@ impl ThecaProfile {
                             &format!(
                                 "{}\n\n",
                                 try!(
-                                    localize_last_touched_string(
-                                        &*self.notes[note_pos].last_touched
+                                    format_last_touched(
+                                        &*self.notes[note_pos].last_touched,
+                                        relative
                                     )
                                 )
                             ),
@@ -780,84 +2229,604 @@ impl ThecaProfile {
                         }
                     };
                 }
-            },
-            true => println!(
-                "{}",
-                as_pretty_json(&self.notes[note_pos].clone())
-            )
+
+                // schedule
+                match self.notes[note_pos].due {
+                    Some(ref d) => try!(pretty_line(
+                        match condensed {
+                            true => "due: ",
+                            false => "due\n---\n"
+                        },
+                        &match condensed {
+                            true => format!("{}\n", try!(format_last_touched(&*d, relative))),
+                            false => format!("{}\n\n", try!(format_last_touched(&*d, relative)))
+                        },
+                        tty
+                    )),
+                    None => ()
+                };
+                match self.notes[note_pos].every {
+                    Some(ref e) => try!(pretty_line(
+                        match condensed {
+                            true => "every: ",
+                            false => "every\n-----\n"
+                        },
+                        &match condensed {
+                            true => format!("{}\n", e),
+                            false => format!("{}\n\n", e)
+                        },
+                        tty
+                    )),
+                    None => ()
+                };
+
+                // pinned
+                if is_pinned(&self.notes[note_pos]) {
+                    try!(pretty_line(
+                        match condensed {
+                            true => "pinned: ",
+                            false => "pinned\n------\n"
+                        },
+                        &match condensed {
+                            true => "yes\n".to_string(),
+                            false => "yes\n\n".to_string()
+                        },
+                        tty
+                    ));
+                }
+
+                // time tracking
+                if self.notes[note_pos].time_log.is_some() {
+                    let spent = format!(
+                        "{}{}",
+                        format_duration(try!(logged(&self.notes[note_pos], None, &now()))),
+                        match running(&self.notes[note_pos]) {
+                            true => " (running)",
+                            false => ""
+                        }
+                    );
+                    try!(pretty_line(
+                        match condensed {
+                            true => "time: ",
+                            false => "time\n----\n"
+                        },
+                        &match condensed {
+                            true => format!("{}\n", spent),
+                            false => format!("{}\n\n", spent)
+                        },
+                        tty
+                    ));
+                }
+
+                // custom fields
+                match self.notes[note_pos].fields {
+                    Some(ref fields) => match condensed {
+                        true => for f in fields.iter() {
+                            try!(pretty_line(
+                                &*format!("{}: ", f.name),
+                                &format!("{}\n", f.value),
+                                tty
+                            ));
+                        },
+                        false => {
+                            let values: Vec<String> = fields.iter().map(|f| {
+                                format!("{}: {} ({})", f.name, f.value, f.kind)
+                            }).collect();
+                            try!(pretty_line(
+                                "fields\n------\n",
+                                &format!("{}\n\n", values.connect("\n")),
+                                tty
+                            ));
+                        }
+                    },
+                    None => ()
+                };
+
+                // archived
+                match self.notes[note_pos].archived {
+                    Some(ref a) => try!(pretty_line(
+                        match condensed {
+                            true => "archived: ",
+                            false => "archived\n--------\n"
+                        },
+                        &match condensed {
+                            true => format!("{}\n", try!(format_last_touched(&*a, relative))),
+                            false => format!("{}\n\n", try!(format_last_touched(&*a, relative)))
+                        },
+                        tty
+                    )),
+                    None => ()
+                };
+
+                // checklist
+                match progress(&*self.notes[note_pos].body) {
+                    Some((checked, total)) => try!(pretty_line(
+                        match condensed {
+                            true => "checklist: ",
+                            false => "checklist\n---------\n"
+                        },
+                        &match condensed {
+                            true => format!("{}/{}\n", checked, total),
+                            false => format!("{}/{}\n\n", checked, total)
+                        },
+                        tty
+                    )),
+                    None => ()
+                };
+
+                // parent and subtasks
+                match self.notes[note_pos].parent {
+                    Some(p) => try!(pretty_line(
+                        match condensed {
+                            true => "parent: ",
+                            false => "parent\n------\n"
+                        },
+                        &match condensed {
+                            true => format!("{}\n", p),
+                            false => format!("{}\n\n", p)
+                        },
+                        tty
+                    )),
+                    None => ()
+                };
+                let subtasks: Vec<String> = children(&self.notes, id)
+                    .iter()
+                    .filter_map(|c| self.notes.iter().find(|n| &n.id == c))
+                    .map(|n| format!("{}: {}", n.id, n.title))
+                    .collect();
+                if !subtasks.is_empty() {
+                    match condensed {
+                        true => {
+                            try!(pretty_line("subtasks: ", &format!(
+                                "{}\n",
+                                subtasks.connect(", ")),
+                                tty
+                            ));
+                        },
+                        false => {
+                            try!(pretty_line("subtasks\n--------\n", &format!(
+                                "{}\n\n",
+                                subtasks.connect("\n")),
+                                tty
+                            ));
+                        }
+                    };
+                }
+
+                // dependencies
+                let blocked_by: Vec<String> = blockers(&self.notes[note_pos])
+                    .iter()
+                    .map(|b| match self.notes.iter().find(|n| &n.id == b) {
+                        Some(n) if n.status == DONE => format!("{} (done)", b),
+                        Some(_) => format!("{} (open)", b),
+                        None => format!("{} (deleted)", b)
+                    })
+                    .collect();
+                if !blocked_by.is_empty() {
+                    match condensed {
+                        true => {
+                            try!(pretty_line("blocked by: ", &format!(
+                                "{}\n",
+                                blocked_by.connect(", ")),
+                                tty
+                            ));
+                        },
+                        false => {
+                            try!(pretty_line("blocked by\n----------\n", &format!(
+                                "{}\n\n",
+                                blocked_by.connect("\n")),
+                                tty
+                            ));
+                        }
+                    };
+                }
+
+                // attachments
+                match self.notes[note_pos].attachments {
+                    Some(ref a) if !a.is_empty() => {
+                        let names: Vec<String> = a.iter()
+                                                  .map(|a| a.name.clone())
+                                                  .collect();
+                        match condensed {
+                            true => try!(pretty_line("attachments: ", &format!(
+                                "{}\n",
+                                names.connect(", ")),
+                                tty
+                            )),
+                            false => try!(pretty_line(
+                                "attachments\n-----------\n",
+                                &format!("{}\n\n", names.connect("\n")),
+                                tty
+                            ))
+                        };
+                    },
+                    _ => ()
+                };
+
+                // links to and from other notes
+                let index = LinkIndex::new(&self.notes);
+                let describe = |ids: Vec<usize>| -> Vec<String> {
+                    ids.iter().map(|l| match self.notes.iter().find(|n| &n.id == l) {
+                        Some(n) => format!("{}: {}", n.id, n.title),
+                        None => format!("{}: [deleted]", l)
+                    }).collect()
+                };
+                let outgoing = describe(index.outgoing(id));
+                let backlinks = describe(index.backlinks(id));
+                for &(name, ref lines) in [
+                    ("links", &outgoing),
+                    ("backlinks", &backlinks)
+                ].iter() {
+                    if lines.is_empty() {continue;}
+                    match condensed {
+                        true => {
+                            try!(pretty_line(&*format!("{}: ", name), &format!(
+                                "{}\n",
+                                lines.connect(", ")),
+                                tty
+                            ));
+                        },
+                        false => {
+                            let underline: String = repeat('-').take(name.len())
+                                                               .collect();
+                            try!(pretty_line(
+                                &*format!("{}\n{}\n", name, underline),
+                                &format!("{}\n\n", lines.connect("\n")),
+                                tty
+                            ));
+                        }
+                    };
+                }
+            },
+            true => println!(
+                "{}",
+                as_pretty_json(&self.notes[note_pos].clone())
+            )
+        }
+        Ok(())
+    }
+
+    /// search the notes using the full text index, best matches first with
+    /// a snippet of the body around the first match
+    pub fn ranked_search(
+        &mut self,
+        pattern: &String,
+        limit: usize,
+        json: bool,
+        archived: bool,
+        all: bool,
+        where_clause: &String,
+        no_status: bool,
+        started_status: bool,
+        urgent_status: bool,
+        priority: &String,
+        profile_folder: &String,
+        profile_name: &String,
+        key: &String
+    ) -> Result<(), ThecaError> {
+        let index_file = try!(index_path(profile_folder, profile_name));
+        let index_key = match self.encrypted {
+            true => Some(password_to_key(&*key)),
+            false => None
+        };
+        let mut index = Index::load(&index_file, index_key.as_ref().map(|k| &**k));
+        // the index is written when the profile is saved, catch up with
+        // anything that changed since (or build it the first time)
+        index.update(&self.notes);
+        let mut candidates = try!(filter_where(
+            visible(&self.notes, archived, all),
+            &*where_clause
+        ));
+        // the same notes plain search would look through
+        try!(filter_status_priority(
+            &mut candidates,
+            no_status,
+            started_status,
+            urgent_status,
+            priority
+        ));
+        let terms = tokenize(&*pattern);
+        let mut ranked: Vec<(Ranked, Vec<(usize, usize)>)> = vec![];
+        for (id, score) in index.score(&*pattern).into_iter() {
+            match candidates.iter().find(|n| n.id == id) {
+                Some(n) => {
+                    let (text, spans) = snippet(&*n.body, &terms, 72);
+                    ranked.push((Ranked {
+                        id: n.id,
+                        title: n.title.clone(),
+                        score: score,
+                        snippet: text
+                    }, spans));
+                },
+                None => ()
+            };
+        }
+        if limit > 0 {ranked.truncate(limit);}
+        match json {
+            true => {
+                let results: Vec<&Ranked> = ranked.iter().map(|r| &r.0).collect();
+                println!("{}", as_pretty_json(&results));
+            },
+            false => {
+                if ranked.is_empty() {
+                    println!("nothing found");
+                    return Ok(())
+                }
+                let tty = istty(STDOUT_FILENO);
+                for &(ref r, ref spans) in ranked.iter() {
+                    try!(pretty_line(
+                        &format!("{}: ", r.id),
+                        &format!("{} ({:.2})\n", r.title, r.score),
+                        tty
+                    ));
+                    if !r.snippet.is_empty() {
+                        let indented: Vec<(usize, usize)> = spans.iter()
+                                                                  .map(|&(s, e)| (s + 4, e + 4))
+                                                                  .collect();
+                        try!(print_highlighted(&*format!("    {}", r.snippet), &indented, tty));
+                    }
+                }
+            }
+        };
+        Ok(())
+    }
+
+    /// print all notes in the profile
+    pub fn list_notes(
+        &mut self,
+        limit: usize,
+        condensed: bool,
+        json: bool,
+        datesort: bool,
+        manual: bool,
+        reverse: bool,
+        search_body: bool,
+        no_status: bool,
+        started_status: bool,
+        urgent_status: bool,
+        relative: bool,
+        collapse: bool,
+        archived: bool,
+        all: bool,
+        where_clause: &String,
+        columns: &String,
+        priority: &String,
+        prioritysort: bool,
+        sort: &String
+    ) -> Result<(), ThecaError> {
+        let notes = try!(filter_where(
+            visible(&self.notes, archived, all),
+            &*where_clause
+        ));
+        let order = manual_order(&self.notes, &self.order);
+        if notes.len() > 0 {
+            try!(sorted_print(
+                &mut notes.clone(),
+                limit,
+                condensed,
+                json,
+                datesort,
+                reverse,
+                search_body,
+                no_status,
+                started_status,
+                urgent_status,
+                relative,
+                true,
+                collapse,
+                match manual {
+                    true => Some(&order),
+                    false => None
+                },
+                &parse_columns(&*columns),
+                priority,
+                prioritysort,
+                sort,
+                None
+            ));
+        } else {
+            match json {
+                true => println!("[]"),
+                false => match archived {
+                    true => println!("there are no archived notes"),
+                    false => println!("this profile is empty")
+                }
+            }
         }
         Ok(())
     }
 
-    /// print all notes in the profile
-    pub fn list_notes(
+    /// print the notes that aren't done and aren't blocked by open notes
+    pub fn ready_notes(
         &mut self,
         limit: usize,
         condensed: bool,
         json: bool,
         datesort: bool,
+        manual: bool,
         reverse: bool,
-        search_body: bool,
-        no_status: bool,
-        started_status: bool,
-        urgent_status:bool
+        relative: bool,
+        columns: &String,
+        priority: &String,
+        prioritysort: bool,
+        sort: &String
     ) -> Result<(), ThecaError> {
-        if self.notes.len() > 0 {
+        let notes: Vec<ThecaItem> = ready(&self.notes).into_iter()
+                                                       .filter(|n| !is_archived(n))
+                                                       .collect();
+        let order = manual_order(&self.notes, &self.order);
+        if notes.len() > 0 {
             try!(sorted_print(
-                &mut self.notes.clone(),
+                &mut notes.clone(),
                 limit,
                 condensed,
                 json,
                 datesort,
                 reverse,
-                search_body,no_status,
-                started_status,
-                urgent_status
+                false,
+                false,
+                false,
+                false,
+                relative,
+                false,
+                false,
+                match manual {
+                    true => Some(&order),
+                    false => None
+                },
+                &parse_columns(&*columns),
+                priority,
+                prioritysort,
+                sort,
+                None
             ));
         } else {
             match json {
                 true => println!("[]"),
-                false => println!("this profile is empty")
+                false => println!("nothing is ready")
+            }
+        }
+        Ok(())
+    }
+
+    /// print the recurring notes that will add another note when done
+    pub fn recurring_notes(&self, json: bool, relative: bool) -> Result<(), ThecaError> {
+        let notes: Vec<ThecaItem> = self.notes.iter()
+                                              .filter(|n| n.every.is_some())
+                                              .filter(|n| !is_archived(n))
+                                              .map(|n| n.clone())
+                                              .collect();
+        match json {
+            true => println!("{}", as_pretty_json(&notes)),
+            false => {
+                if notes.is_empty() {
+                    println!("there are no recurring notes");
+                }
+                for n in notes.iter() {
+                    let due = match n.due {
+                        Some(ref d) => try!(format_last_touched(&*d, relative)),
+                        None => "".to_string()
+                    };
+                    println!(
+                        "{}: {} [every {}, due {}]",
+                        n.id,
+                        n.title,
+                        n.every.clone().unwrap(),
+                        due
+                    );
+                }
             }
+        };
+        Ok(())
+    }
+
+    /// print the dependencies between notes as a DOT graph
+    pub fn graph(&self) -> Result<(), ThecaError> {
+        println!("{}", to_dot(&self.notes));
+        Ok(())
+    }
+
+    /// print the links between notes in the profile
+    pub fn list_links(&self, broken: bool) -> Result<(), ThecaError> {
+        let index = LinkIndex::new(&self.notes);
+        let links = match broken {
+            true => index.broken(&self.notes),
+            false => index.links.clone()
+        };
+        if links.is_empty() {
+            match broken {
+                true => println!("no broken links"),
+                false => println!("no links")
+            };
+            return Ok(())
+        }
+        for &(from, to) in links.iter() {
+            match self.notes.iter().any(|n| n.id == to) {
+                true => println!("note {} -> note {}", from, to),
+                false => println!("note {} -> note {} [deleted]", from, to)
+            };
         }
         Ok(())
     }
 
+    /// the notes matching a search pattern, in the order they were found,
+    /// without a matcher the pattern is a query
+    pub fn matching_notes(
+        &self,
+        pattern: &String,
+        matcher: Option<&Matcher>,
+        search_body: bool,
+        archived: bool,
+        all: bool,
+        where_clause: &String
+    ) -> Result<Vec<ThecaItem>, ThecaError> {
+        let candidates = visible(&self.notes, archived, all);
+        let notes: Vec<ThecaItem> = match matcher {
+            None => try!(filter_notes(candidates, &*pattern, search_body)),
+            // fuzzy matches are ranked, best first
+            Some(&Matcher {pattern: Pattern::Fuzzy(_), ..}) => {
+                rank_notes(&candidates, &*pattern).iter().filter_map(|&(id, _)| {
+                    candidates.iter().find(|n| n.id == id).map(|n| n.clone())
+                }).collect()
+            },
+            Some(m) => candidates.into_iter().filter(|n| !m.matches(n).is_empty()).collect()
+        };
+        filter_where(notes, &*where_clause)
+    }
+
     /// print notes search for in the profile
     pub fn search_notes(
         &mut self,
         pattern: &String,
         regex: bool,
+        query: bool,
+        fuzzy: bool,
         limit: usize,
         condensed: bool,
         json: bool,
         datesort: bool,
+        manual: bool,
         reverse: bool,
         search_body: bool,
         no_status: bool,
         started_status: bool,
-        urgent_status:bool
+        urgent_status: bool,
+        relative: bool,
+        archived: bool,
+        all: bool,
+        where_clause: &String,
+        columns: &String,
+        priority: &String,
+        prioritysort: bool,
+        sort: &String,
+        context: usize,
+        everywhere: bool,
+        ignore_case: bool,
+        smart_case: bool,
+        whole_word: bool
     ) -> Result<(), ThecaError> {
-        let notes: Vec<ThecaItem> = match regex {
-            true => {
-                let re = match Regex::new(&pattern[..]) {
-                    Ok(r) => r,
-                    Err(e) => specific_fail!(
-                        format!("regex error: {}.", e.msg)
-                    )
-                };
-                self.notes.iter().filter(|n| match search_body {
-                    true => re.is_match(&*n.body),
-                    false => re.is_match(&*n.title)
-                }).map(|n| n.clone()).collect()
-            },
-            false => {
-                self.notes.iter().filter(|n| match search_body {
-                    true => n.body.contains(&pattern[..]),
-                    false => n.title.contains(&pattern[..])
-                }).map(|n| n.clone()).collect()
-            }
-        };
+        let highlight = try!(Matcher::new(
+            &*pattern,
+            regex,
+            query,
+            fuzzy,
+            search_body,
+            everywhere,
+            ignore_case,
+            smart_case,
+            whole_word
+        )).map(|m| Highlight {matcher: m, context: context});
+        let notes = try!(self.matching_notes(
+            pattern,
+            highlight.as_ref().map(|h| &h.matcher),
+            search_body,
+            archived,
+            all,
+            where_clause
+        ));
+        // matches in bodies are printed under their note
+        let search_body = search_body || everywhere;
+        let order = manual_order(&self.notes, &self.order);
         if notes.len() > 0 {
             try!(sorted_print(
                 &mut notes.clone(),
@@ -869,7 +2838,19 @@ impl ThecaProfile {
                 search_body,
                 no_status,
                 started_status,
-                urgent_status
+                urgent_status,
+                relative,
+                false,
+                false,
+                match manual {
+                    true => Some(&order),
+                    false => None
+                },
+                &parse_columns(&*columns),
+                priority,
+                prioritysort,
+                sort,
+                highlight.as_ref()
             ));
         } else {
             match json {
@@ -900,6 +2881,41 @@ pub fn setup_args(args: &mut Args) -> Result<(), ThecaError> {
         Err(_) => ()
     };
 
+    match var("THECA_RELATIVE_DATES") {
+        Ok(val) => {
+            if !args.flag_relative && !val.is_empty() && val != "0" &&
+               val != "false" {
+                args.flag_relative = true;
+            }
+        },
+        Err(_) => ()
+    };
+
+    match var("THECA_AUTO_DONE") {
+        Ok(val) => {
+            if !args.flag_auto_done && !val.is_empty() && val != "0" &&
+               val != "false" {
+                args.flag_auto_done = true;
+            }
+        },
+        Err(_) => ()
+    };
+
+    match var("THECA_AUTO_ARCHIVE_DAYS") {
+        Ok(val) => {
+            if args.flag_auto_archive == 0 && !val.is_empty() {
+                args.flag_auto_archive = match val.parse() {
+                    Ok(d) => d,
+                    Err(_) => specific_fail!(format!(
+                        "THECA_AUTO_ARCHIVE_DAYS should be a number of days, not '{}'",
+                        val
+                    ))
+                };
+            }
+        },
+        Err(_) => ()
+    };
+
     // if key is provided but --encrypted not set, it prob should be
     if !args.flag_key.is_empty() && !args.flag_encrypted {
         args.flag_encrypted = true;
@@ -915,6 +2931,24 @@ pub fn setup_args(args: &mut Args) -> Result<(), ThecaError> {
         args.flag_profile = "default".to_string();
     }
 
+    // the journal commands always use the journal profile, `today` creates
+    // it the first time it is used
+    if args.cmd_today || args.cmd_journal {
+        if args.flag_journal.is_empty() {
+            args.flag_journal = match var("THECA_JOURNAL_PROFILE") {
+                Ok(val) if !val.is_empty() => val,
+                _ => "journal".to_string()
+            };
+        }
+        args.flag_profile = args.flag_journal.clone();
+        let mut journal_pathbuf = try!(find_profile_folder(&args.flag_profile_folder));
+        journal_pathbuf.push(&(args.flag_profile.to_string() + ".json"));
+        if args.cmd_today && !journal_pathbuf.exists() {
+            args.cmd_new_profile = true;
+            args.arg_name = vec![args.flag_profile.clone()];
+        }
+    }
+
     Ok(())
 }
 
@@ -923,6 +2957,71 @@ pub fn parse_cmds(
     args: &mut Args,
     profile_fingerprint: &u64
 ) -> Result<(), ThecaError> {
+    // templates, these don't touch the profile (and `templates edit` shouldn't
+    // be mistaken for editing a note)
+    if args.cmd_templates {
+        let templates = try!(templates_path(
+            &args.flag_profile_folder,
+            &args.flag_profile
+        ));
+        if args.cmd_show {
+            print!("{}", try!(read_template(&templates, &args.arg_template)));
+        } else if args.cmd_edit {
+            let path = try!(template_file(&templates, &args.arg_template));
+            let current = match path.is_file() {
+                true => try!(read_template(&templates, &args.arg_template)),
+                false => SKELETON.to_string()
+            };
+            let edited = try!(drop_to_editor(&current));
+            // check the header now rather than the next time it is used,
+            // a broken template isn't saved
+            try!(parse_template(&*try!(expand(&*edited, "", "", |_| Ok("".to_string())))));
+            if !templates.exists() {try!(create_dir(&templates));}
+            let mut file = try!(File::create(&path));
+            try!(file.write_all(edited.as_bytes()));
+            println!("template {} saved", args.arg_template);
+        } else {
+            let names = try!(template_names(&templates));
+            match names.is_empty() {
+                true => println!("no templates for '{}'", args.flag_profile),
+                false => for n in names.iter() {println!("{}", n);}
+            };
+        }
+        return Ok(())
+    }
+
+    // listing views (saving and removing them changes the profile, and
+    // using one is handled before it gets here)
+    if args.cmd_view && !args.cmd_save && !args.cmd_remove {
+        try!(profile.list_views(args.flag_json));
+        return Ok(())
+    }
+
+    // id specs (`3-9,12,!5`) and --where pick the notes bulk commands work on
+    if [
+        args.cmd_del,
+        args.cmd_edit,
+        args.cmd_transfer,
+        args.cmd_archive,
+        args.cmd_unarchive
+    ].iter().any(|c| c == &true) && (!args.arg_ids.is_empty() || !args.flag_where.is_empty()) {
+        let action = match (args.cmd_del, args.cmd_edit, args.cmd_transfer, args.cmd_archive) {
+            (true, _, _, _) => "delete",
+            (_, true, _, _) => "edit",
+            (_, _, true, _) => "transfer",
+            (_, _, _, true) => "archive",
+            _ => "unarchive"
+        };
+        args.arg_id = try!(profile.select_notes(
+            &args.arg_ids,
+            &args.flag_where,
+            args.flag_archived,
+            args.flag_all,
+            action,
+            args.flag_yes
+        ));
+    }
+
     match [
         args.cmd_add,
         args.cmd_edit,
@@ -931,40 +3030,164 @@ pub fn parse_cmds(
         args.cmd_decrypt_profile,
         args.cmd_transfer,
         args.cmd_clear,
-        args.cmd_new_profile
+        args.cmd_new_profile,
+        args.cmd_attach,
+        args.cmd_check,
+        args.cmd_uncheck,
+        args.cmd_archive,
+        args.cmd_unarchive,
+        args.cmd_pin,
+        args.cmd_unpin,
+        args.cmd_move,
+        args.cmd_start,
+        args.cmd_stop,
+        args.cmd_today,
+        args.cmd_save,
+        args.cmd_remove,
+        args.cmd_replace,
+        args.cmd_undo
     ].iter().any(|c| c == &true) {
         true => {
+            // the profile is going to be saved, so older notes can get
+            // their uuids now (series and transfers use them)
+            profile.assign_uuids();
+
             // add
             if args.cmd_add {
+                // fill in the template first so the editor starts with it
+                let template = match args.flag_template.is_empty() {
+                    true => None,
+                    false => {
+                        let templates = try!(templates_path(
+                            &args.flag_profile_folder,
+                            &args.flag_profile
+                        ));
+                        let text = try!(read_template(&templates, &args.flag_template));
+                        let date = try!(strftime("%Y-%m-%d", &now()));
+                        let expanded = try!(expand(&*text, &*date, &*args.flag_profile, ask));
+                        Some(try!(parse_template(&*expanded)))
+                    }
+                };
+                let mut body = args.flag_body.clone();
+                let mut use_editor = args.flag_editor;
+                let mut started = args.flag_started;
+                let mut urgent = args.flag_urgent;
+                match template {
+                    Some(ref t) => {
+                        if body.is_empty() && !args.cmd__ {
+                            let initial = initial_body(t);
+                            body = vec![match use_editor && istty(STDOUT_FILENO)
+                                              && istty(STDIN_FILENO) {
+                                true => try!(drop_to_editor(&initial)),
+                                false => initial
+                            }];
+                            use_editor = false;
+                        }
+                        if !started && !urgent {
+                            started = t.status == STARTED;
+                            urgent = t.status == URGENT;
+                        }
+                    },
+                    None => ()
+                };
                 try!(profile.add_note(
                     &args.arg_title,
-                    &args.flag_body,
-                    args.flag_started,
-                    args.flag_urgent,
+                    &body,
+                    started,
+                    urgent,
                     args.cmd__,
-                    args.flag_editor,
-                    true
+                    use_editor,
+                    false
                 ));
+                let new_id = match profile.notes.last() {
+                    Some(n) => n.id,
+                    None => 0
+                };
+                // flags given on the command line win over the template
+                match template {
+                    Some(ref t) => {
+                        if !t.fields.is_empty() {
+                            try!(profile.set_fields(new_id, &t.fields));
+                        }
+                        if !t.priority.is_empty() {
+                            try!(profile.set_priority(new_id, &t.priority));
+                        }
+                    },
+                    None => ()
+                };
+                if args.flag_parent != 0 {
+                    try!(profile.set_parent(new_id, args.flag_parent));
+                }
+                if args.flag_blocked_by != 0 {
+                    try!(profile.add_blocker(new_id, args.flag_blocked_by));
+                }
+                if !args.flag_every.is_empty() || !args.flag_due.is_empty() {
+                    try!(profile.set_schedule(new_id, &args.flag_every, &args.flag_due));
+                }
+                if !args.flag_set.is_empty() {
+                    try!(profile.set_fields(new_id, &args.flag_set));
+                }
+                if !args.flag_priority.is_empty() {
+                    try!(profile.set_priority(new_id, &args.flag_priority));
+                }
+                // only once none of the flags above failed (and left the
+                // profile unsaved)
+                println!("note {} added", new_id);
             }
 
-            // edit    
+            // edit, several notes can have their status, priority, fields...
+            // changed at once but only one can have its title or body edited
             if args.cmd_edit {
-                try!(profile.edit_note(
-                    args.arg_id[0],
-                    &args.arg_title,
-                    &args.flag_body,
-                    args.flag_started,
-                    args.flag_urgent,
-                    args.flag_none,
-                    args.cmd__,
-                    args.flag_editor,
-                    args.flag_encrypted,
-                    args.flag_yes
-                ));
+                if args.arg_id.len() > 1 && (!args.arg_title.is_empty() ||
+                   !args.flag_body.is_empty() || args.flag_editor || args.cmd__) {
+                    specific_fail_str!("the title or body can only be edited one note at a time.");
+                }
+                for &id in args.arg_id.clone().iter() {
+                    try!(profile.edit_note(
+                        id,
+                        &args.arg_title,
+                        &args.flag_body,
+                        args.flag_started,
+                        args.flag_urgent,
+                        args.flag_none,
+                        args.cmd__,
+                        args.flag_editor,
+                        args.flag_encrypted,
+                        args.flag_yes
+                    ));
+                    if args.flag_parent != 0 {
+                        try!(profile.set_parent(id, args.flag_parent));
+                    }
+                    if args.flag_blocked_by != 0 {
+                        try!(profile.add_blocker(id, args.flag_blocked_by));
+                    }
+                    if args.flag_unblock != 0 {
+                        try!(profile.remove_blocker(id, args.flag_unblock));
+                    }
+                    if !args.flag_every.is_empty() || !args.flag_due.is_empty() {
+                        try!(profile.set_schedule(
+                            id,
+                            &args.flag_every,
+                            &args.flag_due
+                        ));
+                    }
+                    if !args.flag_set.is_empty() {
+                        try!(profile.set_fields(id, &args.flag_set));
+                    }
+                    if !args.flag_priority.is_empty() {
+                        try!(profile.set_priority(id, &args.flag_priority));
+                    }
+                    if args.flag_done {
+                        try!(profile.mark_done(id));
+                    }
+                }
             }
             
             // delete    
-            if args.cmd_del { profile.delete_note(&args.arg_id); }
+            if args.cmd_del {
+                let ids = try!(profile.delete_ids(&args.arg_id, args.flag_yes));
+                profile.delete_note(&ids);
+            }
 
             // transfer
             if args.cmd_transfer {
@@ -972,13 +3195,72 @@ pub fn parse_cmds(
                 try!(profile.transfer_note(args));
             }
 
+            // attach
+            if args.cmd_attach {
+                try!(profile.attach(
+                    args.arg_id[0],
+                    &args.arg_file,
+                    &args.flag_profile_folder,
+                    &args.flag_profile,
+                    &args.flag_key
+                ));
+            }
+
+            // checklists
+            if args.cmd_check || args.cmd_uncheck {
+                try!(profile.check_item(
+                    args.arg_id[0],
+                    args.arg_item,
+                    args.cmd_check,
+                    args.flag_auto_done
+                ));
+            }
+
+            // archive
+            if args.cmd_archive || args.cmd_unarchive {
+                try!(profile.archive_notes(&args.arg_id, args.cmd_archive));
+            }
+
+            // pinning and manual order
+            if args.cmd_pin || args.cmd_unpin {
+                try!(profile.pin_notes(&args.arg_id, args.cmd_pin));
+            }
+
+            if args.cmd_move {
+                try!(profile.move_note(args.arg_id[0], args.flag_before));
+            }
+
+            // time tracking
+            if args.cmd_start {
+                try!(profile.start_timer(args.arg_id[0], args.flag_concurrent));
+            }
+
+            if args.cmd_stop {
+                try!(profile.stop_timer(&args.arg_id));
+            }
+
             // clear
-            if args.cmd_clear { try!(profile.clear(args.flag_yes)); }
+            if args.cmd_clear {
+                // the attachment blobs are kept so `undo` can bring the
+                // notes back whole, `verify` removes them
+                try!(profile.clear(args.flag_yes));
+            }
 
             // decrypt profile
             // FIXME: should test how this interacts with save_to_file when the profile has
             //        changed during execution
             if args.cmd_decrypt_profile {
+                // attachments are decrypted along with the profile
+                let old_key = match profile.encrypted {
+                    true => Some(args.flag_key.clone()),
+                    false => None
+                };
+                try!(profile.rekey_attachments(
+                    &args.flag_profile_folder,
+                    &args.flag_profile,
+                    old_key.as_ref(),
+                    None
+                ));
                 profile.encrypted = false; // is it that easy? i think it is
                 println!("decrypting '{}'", args.flag_profile);
             }
@@ -992,6 +3274,18 @@ pub fn parse_cmds(
                     args.flag_new_key = try!(get_password());
                 }
 
+                // attachments are (re)encrypted with the new key as well
+                let old_key = match profile.encrypted {
+                    true => Some(args.flag_key.clone()),
+                    false => None
+                };
+                try!(profile.rekey_attachments(
+                    &args.flag_profile_folder,
+                    &args.flag_profile,
+                    old_key.as_ref(),
+                    Some(&args.flag_new_key)
+                ));
+
                 // set args.key and args.encrypted
                 args.flag_encrypted = true;
                 args.flag_key = args.flag_new_key.clone();
@@ -1009,40 +3303,228 @@ pub fn parse_cmds(
                 println!("creating profile '{}'", args.arg_name[0]);
             }
 
+            // journal
+            if args.cmd_today {
+                try!(profile.today(
+                    &args.flag_body,
+                    args.cmd__,
+                    args.flag_editor,
+                    args.flag_encrypted,
+                    args.flag_yes,
+                    args.flag_json,
+                    args.flag_condensed,
+                    args.flag_relative
+                ));
+            }
+
+            // views
+            if args.cmd_save {
+                try!(profile.save_view(&args.arg_view, &args.arg_args));
+            }
+
+            if args.cmd_remove {
+                try!(profile.remove_view(&args.arg_view));
+            }
+
+            // find and replace, a dry run isn't saved
+            if args.cmd_replace {
+                if !try!(profile.replace(
+                    &args.arg_pattern,
+                    &args.arg_replacement,
+                    args.flag_regex,
+                    &args.flag_in,
+                    args.flag_archived,
+                    args.flag_all,
+                    &args.flag_where,
+                    args.flag_dry_run,
+                    args.flag_yes
+                )) {
+                    return Ok(())
+                }
+            }
+
+            // undo (saving keeps what was undone, so undoing again redoes it)
+            if args.cmd_undo {
+                args.flag_key = try!(profile.undo(
+                    &args.flag_profile_folder,
+                    &args.flag_profile,
+                    &args.flag_key
+                ));
+                args.flag_encrypted = profile.encrypted;
+            }
+
+            // archive notes that have been done for a while
+            if args.flag_auto_archive != 0 {
+                try!(profile.auto_archive(args.flag_auto_archive));
+            }
+
             try!(profile.save_to_file(args, profile_fingerprint));
         },
         false => {
+            // attachments
+            if args.cmd_attachments {
+                try!(profile.list_attachments(args.arg_id[0], args.flag_json));
+                return Ok(())
+            }
+
+            if args.cmd_extract {
+                try!(profile.extract(
+                    args.arg_id[0],
+                    &args.arg_attachment,
+                    &args.flag_profile_folder,
+                    &args.flag_profile,
+                    &args.flag_key,
+                    args.flag_yes
+                ));
+                return Ok(())
+            }
+
+            if args.cmd_verify {
+                try!(profile.verify(
+                    &args.flag_profile_folder,
+                    &args.flag_profile,
+                    args.flag_yes
+                ));
+                return Ok(())
+            }
+
             // view
             if !args.arg_id.is_empty() {
                 try!(profile.view_note(
                     args.arg_id[0],
                     args.flag_json,
-                    args.flag_condensed
+                    args.flag_condensed,
+                    args.flag_relative
                 ));
                 return Ok(())
             }
 
+            // pick, only the id goes to stdout so it can be used as
+            // `theca edit $(theca pick)`
+            if args.cmd_pick {
+                match pick(
+                    visible(&profile.notes, args.flag_archived, args.flag_all),
+                    &*args.arg_pattern
+                ) {
+                    Ok(id) => println!("{}", id),
+                    // nothing goes to stdout unless a note was picked, so
+                    // `theca edit $(theca pick)` doesn't get the message
+                    Err(e) => {
+                        try!(writeln!(stderr(), "{}", e.desc));
+                        set_exit_status(1);
+                    }
+                };
+                return Ok(())
+            }
+
             // search
+            if args.cmd_search && args.flag_rank {
+                try!(profile.ranked_search(
+                    &args.arg_pattern,
+                    args.flag_limit,
+                    args.flag_json,
+                    args.flag_archived,
+                    args.flag_all,
+                    &args.flag_where,
+                    args.flag_none,
+                    args.flag_started,
+                    args.flag_urgent,
+                    &args.flag_priority,
+                    &args.flag_profile_folder,
+                    &args.flag_profile,
+                    &args.flag_key
+                ));
+                return Ok(())
+            }
+
             if args.cmd_search {
                 try!(profile.search_notes(
                     &args.arg_pattern,
                     args.flag_regex,
+                    args.flag_query,
+                    args.flag_fuzzy,
                     args.flag_limit,
                     args.flag_condensed,
                     args.flag_json,
                     args.flag_datesort,
+                    args.flag_manual,
                     args.flag_reverse,
                     args.flag_search_body,
                     args.flag_none,
                     args.flag_started,
-                    args.flag_urgent
+                    args.flag_urgent,
+                    args.flag_relative,
+                    args.flag_archived,
+                    args.flag_all,
+                    &args.flag_where,
+                    &args.flag_columns,
+                    &args.flag_priority,
+                    args.flag_prioritysort,
+                    &args.flag_sort,
+                    args.flag_context,
+                    args.flag_everywhere,
+                    args.flag_ignore_case,
+                    args.flag_smart_case,
+                    args.flag_word
+                ));
+                return Ok(())
+            }
+
+            // ready
+            if args.cmd_ready {
+                try!(profile.ready_notes(
+                    args.flag_limit,
+                    args.flag_condensed,
+                    args.flag_json,
+                    args.flag_datesort,
+                    args.flag_manual,
+                    args.flag_reverse,
+                    args.flag_relative,
+                    &args.flag_columns,
+                    &args.flag_priority,
+                    args.flag_prioritysort,
+                    &args.flag_sort
                 ));
                 return Ok(())
             }
 
+            // recurring notes
+            if args.cmd_recurring {
+                try!(profile.recurring_notes(args.flag_json, args.flag_relative));
+                return Ok(())
+            }
+
+            // journal
+            if args.cmd_journal {
+                try!(profile.print_journal(&args.flag_from, &args.flag_to, args.flag_json));
+                return Ok(())
+            }
+
+            // timesheet
+            if args.cmd_timesheet {
+                try!(profile.print_timesheet(args.flag_week, args.flag_json, args.flag_csv));
+                return Ok(())
+            }
+
+            // dependency graph
+            if args.cmd_graph {
+                try!(profile.graph());
+                return Ok(())
+            }
+
+            // links
+            if args.cmd_links {
+                try!(profile.list_links(args.flag_broken));
+                return Ok(())
+            }
+
             // stats
             if args.cmd_info {
-                try!(profile.stats(&args.flag_profile));
+                try!(profile.stats(
+                    &args.flag_profile,
+                    args.flag_archived,
+                    args.flag_all
+                ));
                 return Ok(())
             }
 
@@ -1081,11 +3563,21 @@ pub fn parse_cmds(
                     args.flag_condensed,
                     args.flag_json,
                     args.flag_datesort,
+                    args.flag_manual,
                     args.flag_reverse,
                     args.flag_search_body,
                     args.flag_none,
                     args.flag_started,
-                    args.flag_urgent
+                    args.flag_urgent,
+                    args.flag_relative,
+                    args.flag_collapse,
+                    args.flag_archived,
+                    args.flag_all,
+                    &args.flag_where,
+                    &args.flag_columns,
+                    &args.flag_priority,
+                    args.flag_prioritysort,
+                    &args.flag_sort
                 ));
                 return Ok(())
             }
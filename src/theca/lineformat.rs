@@ -11,16 +11,27 @@
 //   tries to construct a line format that won't overflow the console
 //   width.
 
+use std::cmp::{max};
+
 use errors::{ThecaError};
 use ::{ThecaItem};
-use utils::{termsize};
+use utils::{termsize, relative_last_touched_string};
+use checklist::{progress_string};
+use fields::{get_field};
+
+/// widest a custom field column gets, longer values are truncated like
+/// the title
+pub static FIELD_WIDTH: usize = 24;
 
-#[derive(Copy)]
+#[derive(Clone)]
 pub struct LineFormat {
     pub colsep: usize,
     pub id_width: usize,
+    pub priority_width: usize,
     pub title_width: usize,
+    pub progress_width: usize,
     pub status_width: usize,
+    pub field_widths: Vec<(String, usize)>,
     pub touched_width: usize
 }
 
@@ -28,7 +39,9 @@ impl LineFormat {
     pub fn new(
         items: &Vec<ThecaItem>,
         condensed: bool,
-        search: bool
+        search: bool,
+        relative: bool,
+        columns: &Vec<String>
     ) -> Result<LineFormat, ThecaError> {
         // get termsize :>
         let console_width = termsize();
@@ -42,8 +55,11 @@ impl LineFormat {
         let mut line_format = LineFormat {
             colsep: colsep,
             id_width:0,
+            priority_width:0,
             title_width:0,
+            progress_width:0,
             status_width:0,
+            field_widths: vec![],
             touched_width:0
         };
 
@@ -57,6 +73,22 @@ impl LineFormat {
         // then set id_width to 2 so "id" isn't truncated
         if line_format.id_width < 2 && !condensed {line_format.id_width = 2;}
 
+        // priorities are short (A or 2), if no items have one the column
+        // is truncated
+        for n in items.iter() {
+            match n.priority {
+                Some(ref p) if p.len() > line_format.priority_width => {
+                    line_format.priority_width = p.len();
+                },
+                _ => ()
+            };
+        }
+        // if using extended make sure "pri" won't be truncated
+        if line_format.priority_width > 0 && line_format.priority_width < 3 &&
+           !condensed {
+            line_format.priority_width = 3;
+        }
+
         // get length of longest title string
         line_format.title_width = match items.iter()
                                              .max_by(|n| match n.body.len() > 0 {
@@ -75,6 +107,20 @@ impl LineFormat {
             line_format.title_width = 5;
         }
 
+        // checklist progress (e.g. 3/7), if no items have checklists
+        // the column is truncated
+        for n in items.iter() {
+            let width = progress_string(&*n.body).len();
+            if width > line_format.progress_width {
+                line_format.progress_width = width;
+            }
+        }
+        // if using extended make sure "[x]" won't be truncated
+        if line_format.progress_width > 0 && line_format.progress_width < 3 &&
+           !condensed {
+            line_format.progress_width = 3;
+        }
+
         // status length stuff
         line_format.status_width = match items.iter()
                                               .any(|n| n.status.len() > 0) {
@@ -99,16 +145,54 @@ impl LineFormat {
             }
         };
 
-        // last_touched has fixed string length so no need for silly iter stuff
-        line_format.touched_width = match condensed {
-            true => 10, // condensed
-            false => 19 // expanded
+        // custom field columns are as wide as their widest value (up to
+        // FIELD_WIDTH), in extended mode they are at least as wide as the
+        // field name
+        for c in columns.iter() {
+            let mut width = match condensed {
+                true => 1,
+                false => c.len()
+            };
+            for n in items.iter() {
+                match get_field(n, &*c) {
+                    Some(f) if f.value.len() > width => width = f.value.len(),
+                    _ => ()
+                };
+            }
+            let widest = match condensed {
+                true => FIELD_WIDTH,
+                false => max(FIELD_WIDTH, c.len())
+            };
+            if width > widest {width = widest;}
+            line_format.field_widths.push((c.clone(), width));
+        }
+
+        // absolute last_touched has fixed string length so no need for silly
+        // iter stuff, relative dates ("3h ago", "yesterday") need measuring
+        line_format.touched_width = match relative {
+            true => {
+                let mut widest = 0;
+                for n in items.iter() {
+                    let width = try!(
+                        relative_last_touched_string(&*n.last_touched)
+                    ).len();
+                    if width > widest {widest = width;}
+                }
+                // if using extended set touched_width to at least 12 so
+                // "last touched" won't be truncated
+                if widest < 12 && !condensed {widest = 12;}
+                widest
+            },
+            false => match condensed {
+                true => 10, // condensed
+                false => 19 // expanded
+            }
         };
 
         // check to make sure our new line format isn't bigger than the console
         let line_width = line_format.line_width();
         if console_width > 0 && line_width > console_width &&
-           (line_format.title_width-(line_width-console_width)) > 0 {
+           line_format.title_width > line_width - console_width {
             // if it is trim text from the title width since it is always the biggest...
             // if there isn't any statuses, also give the title the colsep char space
             line_format.title_width -= line_width-console_width;
@@ -118,10 +202,16 @@ impl LineFormat {
     }
 
     pub fn line_width(&self) -> usize {
-        let columns = match self.status_width == 0 {
+        let mut columns = match self.status_width == 0 {
             true => 2*self.colsep,
             false => 3*self.colsep
         };
-        self.id_width+self.title_width+self.status_width+self.touched_width+columns
+        if self.progress_width != 0 {columns += self.colsep;}
+        if self.priority_width != 0 {columns += self.colsep;}
+        for &(_, width) in self.field_widths.iter() {
+            columns += width+self.colsep;
+        }
+        self.id_width+self.priority_width+self.title_width+self.progress_width+
+        self.status_width+self.touched_width+columns
     }
 }
@@ -0,0 +1,71 @@
+//  _   _                    
+// | |_| |__   ___  ___ __ _ 
+// | __| '_ \ / _ \/ __/ _` |
+// | |_| | | |  __/ (_| (_| |
+//  \__|_| |_|\___|\___\__,_|
+//
+// licensed under the MIT license <http://opensource.org/licenses/MIT>
+//
+// links.rs
+//   parsing `[[id]]` and `#id` references to other notes out of note
+//   bodies and building an index of them.
+
+use regex::{Regex};
+
+use ::{ThecaItem};
+
+/// ids referenced in a note body as `[[id]]` or `#id`, in order of
+/// appearance and without duplicates
+pub fn parse_links(body: &str) -> Vec<usize> {
+    // `#id` has to start a word so things like `&#39;` or `abc#1` are skipped
+    let re = Regex::new(r"\[\[(\d+)\]\]|(?:^|[^\w&])#(\d+)\b").unwrap();
+    let mut links: Vec<usize> = vec![];
+    for caps in re.captures_iter(body) {
+        let id = match caps.at(1) {
+            Some(i) => i,
+            None => caps.at(2).unwrap_or("")
+        };
+        match id.parse::<usize>() {
+            Ok(i) => if !links.contains(&i) {links.push(i);},
+            Err(_) => ()
+        };
+    }
+    links
+}
+
+/// all the links between the notes of a profile
+pub struct LinkIndex {
+    /// (from, to) pairs
+    pub links: Vec<(usize, usize)>
+}
+
+impl LinkIndex {
+    pub fn new(notes: &[ThecaItem]) -> LinkIndex {
+        let mut links: Vec<(usize, usize)> = vec![];
+        for n in notes.iter() {
+            for to in parse_links(&*n.body).iter() {
+                // a note mentioning itself isn't much of a link
+                if *to != n.id {links.push((n.id, *to));}
+            }
+        }
+        LinkIndex {links: links}
+    }
+
+    /// ids the note `id` links to
+    pub fn outgoing(&self, id: usize) -> Vec<usize> {
+        self.links.iter().filter(|l| l.0 == id).map(|l| l.1).collect()
+    }
+
+    /// ids of the notes that link to the note `id`
+    pub fn backlinks(&self, id: usize) -> Vec<usize> {
+        self.links.iter().filter(|l| l.1 == id).map(|l| l.0).collect()
+    }
+
+    /// links pointing at notes that don't exist (anymore)
+    pub fn broken(&self, notes: &[ThecaItem]) -> Vec<(usize, usize)> {
+        self.links.iter()
+                  .filter(|l| !notes.iter().any(|n| n.id == l.1))
+                  .map(|l| *l)
+                  .collect()
+    }
+}
@@ -0,0 +1,70 @@
+//  _   _                    
+// | |_| |__   ___  ___ __ _ 
+// | __| '_ \ / _ \/ __/ _` |
+// | |_| | | |  __/ (_| (_| |
+//  \__|_| |_|\___|\___\__,_|
+//
+// licensed under the MIT license <http://opensource.org/licenses/MIT>
+//
+// order.rs
+//   helpers for pinned notes and the manual order of notes in a profile
+//   (set with `theca move`).
+
+use ::{ThecaItem};
+
+/// is the note pinned to the top of lists?
+pub fn is_pinned(note: &ThecaItem) -> bool {
+    note.pinned == Some(true)
+}
+
+/// the full manual order of the notes, notes that have never been moved
+/// (or were added since) follow the ordered ones by id
+pub fn manual_order(notes: &[ThecaItem], order: &Option<Vec<usize>>) -> Vec<usize> {
+    let mut ordered: Vec<usize> = match *order {
+        Some(ref o) => o.iter()
+                        .filter(|i| notes.iter().any(|n| &n.id == *i))
+                        .map(|i| *i)
+                        .collect(),
+        None => vec![]
+    };
+    let mut rest: Vec<usize> = notes.iter()
+                                    .map(|n| n.id)
+                                    .filter(|i| !ordered.contains(i))
+                                    .collect();
+    rest.sort();
+    ordered.push_all(&rest);
+    ordered
+}
+
+/// move `id` so it comes directly before `before` in `order`
+pub fn move_before(order: &Vec<usize>, id: usize, before: usize) -> Vec<usize> {
+    let mut moved: Vec<usize> = order.iter().filter(|i| **i != id).map(|i| *i).collect();
+    let pos = match moved.iter().position(|i| *i == before) {
+        Some(p) => p,
+        None => moved.len()
+    };
+    moved.insert(pos, id);
+    moved
+}
+
+/// sort notes by their position in `order`, anything missing goes last
+pub fn sort_manual(notes: &mut Vec<ThecaItem>, order: &Vec<usize>) {
+    notes.sort_by(|a, b| {
+        let pos = |id: usize| match order.iter().position(|i| *i == id) {
+            Some(p) => p,
+            None => order.len()
+        };
+        pos(a.id).cmp(&pos(b.id))
+    });
+}
+
+/// move pinned notes to the top, keeping the order within the pinned and
+/// unpinned notes
+pub fn pinned_first(notes: &Vec<ThecaItem>) -> Vec<ThecaItem> {
+    let mut sorted: Vec<ThecaItem> = notes.iter()
+                                          .filter(|n| is_pinned(n))
+                                          .map(|n| n.clone())
+                                          .collect();
+    sorted.extend(notes.iter().filter(|n| !is_pinned(n)).map(|n| n.clone()));
+    sorted
+}
@@ -0,0 +1,257 @@
+//  _   _                    
+// | |_| |__   ___  ___ __ _ 
+// | __| '_ \ / _ \/ __/ _` |
+// | |_| | | |  __/ (_| (_| |
+//  \__|_| |_|\___|\___\__,_|
+//
+// licensed under the MIT license <http://opensource.org/licenses/MIT>
+//
+// pick.rs
+//   a small built-in fuzzy finder for `theca pick`, the picker is drawn on
+//   stderr so the chosen id (printed on stdout) can be captured by a shell.
+
+use std::io::{Write, stderr};
+use std::old_io::stdio::{stdin_raw};
+
+use ::{ThecaItem};
+use errors::{ThecaError, GenericError};
+use fuzzy::{rank_notes};
+use utils::{set_term_raw, termsize};
+use utils::c::{istty, pending};
+use libc::{STDIN_FILENO};
+
+/// number of matches shown under the prompt
+static ROWS: usize = 10;
+
+/// keys the picker understands
+#[derive(PartialEq, Debug)]
+pub enum Key {
+    Char(char),
+    Backspace,
+    Up,
+    Down,
+    Enter,
+    Cancel,
+    Ignored
+}
+
+/// what to do after a key
+#[derive(PartialEq, Debug)]
+pub enum Action {
+    Continue,
+    Pick(usize),
+    Cancel
+}
+
+/// the picker state, everything but the terminal handling
+pub struct Picker {
+    pub notes: Vec<ThecaItem>,
+    pub query: String,
+    pub matches: Vec<usize>,
+    pub selected: usize
+}
+
+impl Picker {
+    pub fn new(notes: Vec<ThecaItem>, query: &str) -> Picker {
+        let mut picker = Picker {
+            notes: notes,
+            query: query.to_string(),
+            matches: vec![],
+            selected: 0
+        };
+        picker.refresh();
+        picker
+    }
+
+    /// rerun the match after the query changed, an empty query lists every
+    /// note in order
+    fn refresh(&mut self) {
+        self.matches = match self.query.trim().is_empty() {
+            true => self.notes.iter().map(|n| n.id).collect(),
+            false => rank_notes(&self.notes, &*self.query).iter().map(|m| m.0).collect()
+        };
+        self.selected = 0;
+    }
+
+    pub fn key(&mut self, key: Key) -> Action {
+        match key {
+            Key::Char(c) => {
+                self.query.push(c);
+                self.refresh();
+            },
+            Key::Backspace => {
+                if self.query.pop().is_some() {self.refresh();}
+            },
+            Key::Up => {
+                if self.selected > 0 {self.selected -= 1;}
+            },
+            Key::Down => {
+                if self.selected + 1 < self.matches.len() {self.selected += 1;}
+            },
+            Key::Enter => {
+                return match self.matches.get(self.selected) {
+                    Some(id) => Action::Pick(*id),
+                    None => Action::Continue
+                }
+            },
+            Key::Cancel => return Action::Cancel,
+            Key::Ignored => ()
+        };
+        Action::Continue
+    }
+
+    /// the first match shown, scrolled so the selection is always visible
+    fn first(&self) -> usize {
+        match self.selected < ROWS {
+            true => 0,
+            false => self.selected + 1 - ROWS
+        }
+    }
+
+    /// the line of `lines` the selection is on
+    pub fn selected_row(&self) -> usize {
+        self.selected - self.first() + 1
+    }
+
+    /// the lines to draw, the prompt then the visible matches
+    pub fn lines(&self, width: usize) -> Vec<String> {
+        let mut lines = vec![format!(
+            "> {}  ({}/{})",
+            self.query,
+            self.matches.len(),
+            self.notes.len()
+        )];
+        for (i, id) in self.matches.iter().enumerate().skip(self.first()).take(ROWS) {
+            let title = match self.notes.iter().find(|n| n.id == *id) {
+                Some(n) => n.title.clone(),
+                None => continue
+            };
+            let line = format!("{} {}: {}", match i == self.selected {
+                true => ">",
+                false => " "
+            }, id, title);
+            lines.push(match width > 0 {
+                true => line.chars().take(width - 1).collect(),
+                false => line
+            });
+        }
+        lines
+    }
+}
+
+/// turn the bytes of a keypress into a key, `next` returns the next byte
+/// from the terminal
+pub fn read_key<F: FnMut() -> Option<u8>>(mut next: F) -> Key {
+    let first = match next() {
+        Some(b) => b,
+        None => return Key::Cancel
+    };
+    match first {
+        13 | 10 => Key::Enter,
+        127 | 8 => Key::Backspace,
+        // ctrl-c, ctrl-d, ctrl-g
+        3 | 4 | 7 => Key::Cancel,
+        // ctrl-p, ctrl-n
+        16 => Key::Up,
+        14 => Key::Down,
+        27 => match next() {
+            Some(91) | Some(79) => match next() {
+                Some(65) => Key::Up,
+                Some(66) => Key::Down,
+                _ => Key::Ignored
+            },
+            _ => Key::Cancel
+        },
+        b if b < 32 => Key::Ignored,
+        b => {
+            // the rest of a multibyte character
+            let extra = match b {
+                b if b >= 0xf0 => 3,
+                b if b >= 0xe0 => 2,
+                b if b >= 0xc0 => 1,
+                _ => 0
+            };
+            let mut bytes = vec![b];
+            for _ in 0..extra {
+                match next() {
+                    Some(c) => bytes.push(c),
+                    None => return Key::Cancel
+                };
+            }
+            match String::from_utf8(bytes) {
+                Ok(s) => match s.chars().next() {
+                    Some(c) => Key::Char(c),
+                    None => Key::Ignored
+                },
+                Err(_) => Key::Ignored
+            }
+        }
+    }
+}
+
+/// draw the picker over the lines drawn last time, returns the number of
+/// lines now on screen
+fn draw(picker: &Picker, drawn: usize) -> Result<usize, ThecaError> {
+    let mut err = stderr();
+    if drawn > 1 {try!(write!(err, "\x1b[{}A", drawn - 1));}
+    try!(write!(err, "\r\x1b[J"));
+    let lines = picker.lines(termsize());
+    let selected = picker.selected_row();
+    for (i, line) in lines.iter().enumerate() {
+        match i == selected {
+            // reverse video for the selection
+            true => try!(write!(err, "\x1b[7m{}\x1b[0m", line)),
+            false => try!(write!(err, "{}", line))
+        };
+        if i + 1 < lines.len() {try!(write!(err, "\n"));}
+    }
+    try!(err.flush());
+    Ok(lines.len())
+}
+
+/// run the picker on the terminal and return the chosen id
+pub fn pick(notes: Vec<ThecaItem>, query: &str) -> Result<usize, ThecaError> {
+    if !istty(STDIN_FILENO) {
+        specific_fail_str!("pick needs a terminal to read keys from.")
+    }
+    let mut picker = Picker::new(notes, query);
+    let mut input = stdin_raw();
+    try!(set_term_raw(true));
+    let mut drawn = 0;
+    let mut chosen: Result<Option<usize>, ThecaError> = Ok(None);
+    loop {
+        drawn = match draw(&picker, drawn) {
+            Ok(d) => d,
+            Err(e) => {
+                chosen = Err(e);
+                break;
+            }
+        };
+        // the rest of a key arrives with its first byte, so a lone escape
+        // cancels without waiting for the next keypress
+        let mut first = true;
+        let key = read_key(|| {
+            if !first && !pending(STDIN_FILENO, 50) {return None}
+            first = false;
+            input.read_byte().ok()
+        });
+        match picker.key(key) {
+            Action::Pick(id) => {
+                chosen = Ok(Some(id));
+                break;
+            },
+            Action::Cancel => break,
+            Action::Continue => ()
+        };
+    }
+    // always put the terminal back and clear the picker away
+    try!(set_term_raw(false));
+    let mut err = stderr();
+    if drawn > 1 {try!(write!(err, "\x1b[{}A", drawn - 1));}
+    try!(write!(err, "\r\x1b[J"));
+    try!(err.flush());
+    match try!(chosen) {
+        Some(id) => Ok(id),
+        None => specific_fail_str!("nothing picked.")
+    }
+}
@@ -0,0 +1,78 @@
+//  _   _                    
+// | |_| |__   ___  ___ __ _ 
+// | __| '_ \ / _ \/ __/ _` |
+// | |_| | | |  __/ (_| (_| |
+//  \__|_| |_|\___|\___\__,_|
+//
+// licensed under the MIT license <http://opensource.org/licenses/MIT>
+//
+// priority.rs
+//   note priorities, either a letter A-Z (as in todo.txt) or a number, in
+//   both cases lower is more important.
+
+use std::cmp::{Ordering};
+
+use ::{ThecaItem};
+use errors::{ThecaError, GenericError};
+
+/// parse a priority given on the command line, letters are upper cased
+/// and "none" clears the priority
+pub fn parse_priority(priority: &str) -> Result<Option<String>, ThecaError> {
+    let priority = priority.trim();
+    if priority == "none" {
+        return Ok(None)
+    }
+    if priority.len() == 1 &&
+       priority.chars().all(|c| (c >= 'a' && c <= 'z') || (c >= 'A' && c <= 'Z')) {
+        return Ok(Some(priority.to_uppercase()))
+    }
+    match priority.parse::<u32>() {
+        Ok(p) => Ok(Some(p.to_string())),
+        Err(_) => specific_fail!(format!(
+            "invalid priority: {} (use a letter A-Z or a number)",
+            priority
+        ))
+    }
+}
+
+/// where a priority ranks, letters come before numbers and notes without
+/// a priority come last
+pub fn priority_rank(priority: &Option<String>) -> (u8, u32) {
+    match *priority {
+        Some(ref p) => match p.parse::<u32>() {
+            Ok(n) => (1, n),
+            Err(_) => (0, p.chars().next().map(|c| c as u32).unwrap_or(0))
+        },
+        None => (2, 0)
+    }
+}
+
+/// compare the priorities of two notes, more important first
+pub fn cmp_priority(a: &ThecaItem, b: &ThecaItem) -> Ordering {
+    priority_rank(&a.priority).cmp(&priority_rank(&b.priority))
+}
+
+/// parse a priority filter, a single priority ("A") or an inclusive range
+/// ("A-C" or "1-3")
+pub fn parse_priority_filter(filter: &str) -> Result<((u8, u32), (u8, u32)), ThecaError> {
+    let parts: Vec<&str> = filter.splitn(2, '-').collect();
+    let low = priority_rank(&try!(parse_priority(parts[0])));
+    let high = match parts.len() {
+        2 => priority_rank(&try!(parse_priority(parts[1]))),
+        _ => low
+    };
+    match low <= high {
+        true => Ok((low, high)),
+        false => Ok((high, low))
+    }
+}
+
+/// does the note have a priority within the filter? a filter of just
+/// "none" matches the notes without one
+pub fn priority_matches(note: &ThecaItem, filter: &((u8, u32), (u8, u32))) -> bool {
+    let rank = priority_rank(&note.priority);
+    match note.priority {
+        Some(_) => rank >= filter.0 && rank <= filter.1,
+        None => filter.0 == rank && filter.1 == rank
+    }
+}
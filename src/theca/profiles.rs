@@ -0,0 +1,231 @@
+//  _   _                    
+// | |_| |__   ___  ___ __ _ 
+// | __| '_ \ / _ \/ __/ _` |
+// | |_| | | |  __/ (_| (_| |
+//  \__|_| |_|\___|\___\__,_|
+//
+// licensed under the MIT license <http://opensource.org/licenses/MIT>
+//
+// profiles.rs
+//   working across profiles, searching every profile in the profile folder
+//   and `profile:id` references to notes in other profiles.
+
+use std::io::{Write, stderr};
+
+use regex::{Regex};
+use rustc_serialize::json::{as_pretty_json};
+
+use ::{Args, ThecaItem, ThecaProfile};
+use errors::{ThecaError, GenericError};
+use fields::{parse_columns};
+use highlight::{Highlight, Matcher, Match};
+use order::{manual_order};
+use utils::{find_profile_folder, profile_names, get_password, sort_notes, print_notes};
+
+/// a search result from `search --all-profiles`
+#[derive(RustcEncodable, Clone)]
+pub struct ProfileNote {
+    pub profile: String,
+    pub note: ThecaItem,
+    pub matches: Vec<Match>
+}
+
+/// split a `profile:id` reference
+pub fn parse_note_ref(arg: &str) -> Option<(String, usize)> {
+    let re = Regex::new(r"^([\w-]+):(\d+)$").unwrap();
+    match re.captures(arg) {
+        Some(caps) => match caps.at(2).unwrap().parse() {
+            Ok(id) => Some((caps.at(1).unwrap().to_string(), id)),
+            Err(_) => None
+        },
+        None => None
+    }
+}
+
+/// rewrite a `profile:id` reference given as the note to view (`theca
+/// work:3`) or edit (`theca edit work:3`) into the id and `--profile`, the
+/// options taking a value are read from `usage` so their values are never
+/// mistaken for the note
+pub fn expand_note_refs(argv: Vec<String>, usage: &str) -> Result<Vec<String>, ThecaError> {
+    let option_re = Regex::new(r"(-{1,2}[\w-]+)[ =][A-Z][A-Z_]*\b").unwrap();
+    let takes_value: Vec<&str> = option_re.captures_iter(usage)
+                                          .map(|c| c.at(1).unwrap())
+                                          .collect();
+    let mut expanded: Vec<String> = vec![];
+    let mut positionals: Vec<String> = vec![];
+    let mut profile: Option<String> = None;
+    let mut option_value = false;
+    for (i, arg) in argv.into_iter().enumerate() {
+        if i == 0 || option_value {
+            option_value = false;
+            expanded.push(arg);
+            continue;
+        }
+        if arg.starts_with("-") && arg != "-" {
+            option_value = !arg.contains("=") && takes_value.contains(&&*arg);
+            if &*arg == "-p" || &*arg == "--profile" || arg.starts_with("--profile=") {
+                match profile {
+                    Some(_) => specific_fail_str!(
+                        "a note in another profile can't be used with --profile."
+                    ),
+                    None => ()
+                };
+            }
+            expanded.push(arg);
+            continue;
+        }
+        let note_position = positionals.is_empty() ||
+                            (positionals.len() == 1 && &*positionals[0] == "edit");
+        positionals.push(arg.clone());
+        match (note_position, parse_note_ref(&*arg)) {
+            (true, Some((name, id))) => {
+                if expanded.iter().any(|a| &**a == "-p" || &**a == "--profile" ||
+                                           a.starts_with("--profile=")) {
+                    specific_fail_str!(
+                        "a note in another profile can't be used with --profile."
+                    );
+                }
+                profile = Some(name);
+                expanded.push(id.to_string());
+            },
+            _ => expanded.push(arg)
+        };
+    }
+    match profile {
+        Some(name) => {
+            expanded.push("--profile".to_string());
+            expanded.push(name);
+        },
+        None => ()
+    };
+    Ok(expanded)
+}
+
+/// search every profile in the profile folder, encrypted profiles are
+/// either skipped or opened with the last key that worked, the key of a
+/// profile it doesn't open is asked for (and the profile left out if that
+/// doesn't open it either)
+pub fn search_profiles(args: &Args) -> Result<(), ThecaError> {
+    if args.flag_rank {
+        specific_fail_str!("--rank can't be used with --all-profiles.");
+    }
+    let folder = try!(find_profile_folder(&args.flag_profile_folder));
+    let columns = parse_columns(&*args.flag_columns);
+    let highlight = try!(Matcher::new(
+        &*args.arg_pattern,
+        args.flag_regex,
+        args.flag_query,
+        args.flag_fuzzy,
+        args.flag_search_body,
+        args.flag_everywhere,
+        args.flag_ignore_case,
+        args.flag_smart_case,
+        args.flag_word
+    )).map(|m| Highlight {matcher: m, context: args.flag_context});
+    let search_body = args.flag_search_body || args.flag_everywhere;
+    let mut key = args.flag_key.clone();
+    let mut found: Vec<ProfileNote> = vec![];
+    let mut printed = false;
+    for (name, encrypted) in try!(profile_names(&folder)).into_iter() {
+        if encrypted && args.flag_skip_encrypted {continue;}
+        let opened = match encrypted && key.is_empty() {
+            true => None,
+            false => match ThecaProfile::new(
+                &name,
+                &args.flag_profile_folder,
+                &key,
+                false,
+                encrypted,
+                args.flag_yes
+            ) {
+                Ok((p, _)) => Some(p),
+                Err(_) if encrypted => None,
+                Err(e) => return Err(e)
+            }
+        };
+        let profile = match opened {
+            Some(p) => p,
+            None => {
+                println!("{} is encrypted", name);
+                let profile_key = try!(get_password());
+                match ThecaProfile::new(
+                    &name,
+                    &args.flag_profile_folder,
+                    &profile_key,
+                    false,
+                    encrypted,
+                    args.flag_yes
+                ) {
+                    Ok((p, _)) => {
+                        key = profile_key;
+                        p
+                    },
+                    Err(_) => {
+                        try!(writeln!(
+                            stderr(),
+                            "warning: couldn't decrypt {} with that key, leaving it out",
+                            name
+                        ));
+                        continue;
+                    }
+                }
+            }
+        };
+        let mut notes = try!(profile.matching_notes(
+            &args.arg_pattern,
+            highlight.as_ref().map(|h| &h.matcher),
+            args.flag_search_body,
+            args.flag_archived,
+            args.flag_all,
+            &args.flag_where
+        ));
+        let order = manual_order(&profile.notes, &profile.order);
+        try!(sort_notes(
+            &mut notes,
+            args.flag_limit,
+            args.flag_datesort,
+            args.flag_reverse,
+            args.flag_none,
+            args.flag_started,
+            args.flag_urgent,
+            false,
+            false,
+            match args.flag_manual {
+                true => Some(&order),
+                false => None
+            },
+            &args.flag_priority,
+            args.flag_prioritysort,
+            &args.flag_sort
+        ));
+        if notes.is_empty() {continue;}
+        match args.flag_json {
+            true => for n in notes.into_iter() {
+                let matches = match highlight {
+                    Some(ref h) => h.matcher.matches(&n),
+                    None => vec![]
+                };
+                found.push(ProfileNote {profile: name.clone(), note: n, matches: matches});
+            },
+            false => {
+                if printed {println!("");}
+                println!("# {}", name);
+                try!(print_notes(
+                    &notes,
+                    args.flag_condensed,
+                    false,
+                    search_body,
+                    args.flag_relative,
+                    &columns,
+                    highlight.as_ref()
+                ));
+                printed = true;
+            }
+        };
+    }
+    match args.flag_json {
+        true => println!("{}", as_pretty_json(&found)),
+        false => if !printed {println!("nothing found");}
+    };
+    Ok(())
+}
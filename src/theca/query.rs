@@ -0,0 +1,549 @@
+//  _   _                    
+// | |_| |__   ___  ___ __ _ 
+// | __| '_ \ / _ \/ __/ _` |
+// | |_| | | |  __/ (_| (_| |
+//  \__|_| |_|\___|\___\__,_|
+//
+// licensed under the MIT license <http://opensource.org/licenses/MIT>
+//
+// query.rs
+//   the small query language accepted by `search -q` and `--where`, e.g.
+//   `status:started AND (title:/deploy/ OR body:rollback) AND touched:>7d`.
+
+use std::cmp::{Ordering};
+use std::iter::{repeat};
+
+use regex::{Regex};
+use time::{Tm, now};
+
+use ::{ThecaItem};
+use errors::{ThecaError, GenericError};
+use fields::{get_field, compare};
+use journal::{is_day};
+use priority::{parse_priority, priority_rank, parse_priority_filter, priority_matches};
+use schedule::{parse_duration};
+use utils::{parse_last_touched};
+
+/// the keys that match the built in parts of a note, any other key is the
+/// name of a custom field (`field.due` reaches a field named like a key)
+pub static KEYS: [&'static str; 8] = [
+    "title", "body", "status", "touched", "due", "id", "priority", "has"
+];
+
+/// comparisons that can follow a key, longest first so `>=` isn't read as `>`
+static OPS: [&'static str; 6] = [">=", "<=", "!=", "=", ">", "<"];
+
+/// What a term is matched against, text or a `/regex/`
+#[derive(Clone)]
+pub enum Value {
+    Text(String),
+    Pattern(Regex)
+}
+
+/// A single `key:value`, `key>value` or bare word (an empty key)
+#[derive(Clone)]
+pub struct Term {
+    pub key: String,
+    pub op: String,
+    pub value: Value
+}
+
+/// A parsed query
+pub enum Query {
+    And(Box<Query>, Box<Query>),
+    Or(Box<Query>, Box<Query>),
+    Not(Box<Query>),
+    Term(Term)
+}
+
+enum Token {
+    Open,
+    Close,
+    And,
+    Or,
+    Not,
+    Term(Term)
+}
+
+/// an error pointing at the character `at` of the query
+fn query_error(query: &str, at: usize, msg: &str) -> ThecaError {
+    let marker: String = repeat(' ').take(at).collect();
+    ThecaError {
+        kind: GenericError,
+        desc: format!("query error: {}\n    {}\n    {}^", msg, query, marker),
+        detail: None
+    }
+}
+
+/// the comparison starting at `i`, if there is one
+fn op_at(chars: &[char], i: usize) -> Option<&'static str> {
+    OPS.iter().map(|o| *o).find(|o| {
+        let op: Vec<char> = o.chars().collect();
+        chars.len() >= i + op.len() && &chars[i..i+op.len()] == &*op
+    })
+}
+
+fn is_delim(c: char) -> bool {
+    c.is_whitespace() || c == '(' || c == ')' || c == ','
+}
+
+/// read a `"quoted"` or `/regex/` value starting at `i`, `\` escapes the
+/// closing character
+fn read_quoted(
+    query: &str,
+    chars: &[char],
+    i: &mut usize,
+    close: char
+) -> Result<String, ThecaError> {
+    let start = *i;
+    let mut value = String::new();
+    *i += 1;
+    loop {
+        if *i >= chars.len() {
+            let what = match close {
+                '/' => "this regex is never closed",
+                _ => "this quote is never closed"
+            };
+            return Err(query_error(query, start, what))
+        }
+        match chars[*i] {
+            '\\' if *i + 1 < chars.len() && chars[*i+1] == close => {
+                value.push(close);
+                *i += 2;
+            },
+            c if c == close => {
+                *i += 1;
+                return Ok(value)
+            },
+            c => {
+                value.push(c);
+                *i += 1;
+            }
+        };
+    }
+}
+
+/// check a term makes sense for its key so mistakes are reported with
+/// where they are rather than when the query is run
+fn check_term(
+    query: &str,
+    key: &str,
+    op: &str,
+    value: &Value,
+    at: usize
+) -> Result<(), ThecaError> {
+    let text = match *value {
+        Value::Text(ref t) => t.clone(),
+        Value::Pattern(_) => "".to_string()
+    };
+    let is_pattern = match *value {
+        Value::Pattern(_) => true,
+        Value::Text(_) => false
+    };
+    let equality = op.is_empty() || op == "=" || op == "!=";
+    let problem = match &*key.to_lowercase() {
+        "" | "title" | "body" if !equality => {
+            Some("text can only be matched with :, = or !=".to_string())
+        },
+        "status" if is_pattern || !equality ||
+                    !["started", "urgent", "done", "none"].contains(&&*text.to_lowercase()) => {
+            Some("status is one of started, urgent, done or none".to_string())
+        },
+        "touched" | "due" if is_pattern || (!is_day(&*text) && parse_duration(&*text).is_err()) => {
+            Some(format!("{} takes a date (YYYY-MM-DD) or a duration (7d)", key))
+        },
+        "id" if is_pattern || text.parse::<usize>().is_err() => {
+            Some("id takes a number".to_string())
+        },
+        "priority" if is_pattern || match equality {
+            true => text != "none" && parse_priority_filter(&*text).is_err(),
+            false => parse_priority(&*text).is_err()
+        } => {
+            Some("priority takes a letter A-Z, a number, a range (A-C) or none".to_string())
+        },
+        "has" if is_pattern || !op.is_empty() => {
+            Some("has takes the name of a field".to_string())
+        },
+        _ if is_pattern && !equality => {
+            Some("a regex can only be matched with :, = or !=".to_string())
+        },
+        _ => None
+    };
+    match problem {
+        Some(p) => Err(query_error(query, at, &*p)),
+        None => Ok(())
+    }
+}
+
+/// split a query into tokens, each with the character it starts at, `bare`
+/// is the key given to bare words
+fn tokenize(query: &str, bare: &str) -> Result<Vec<(Token, usize)>, ThecaError> {
+    let chars: Vec<char> = query.chars().collect();
+    let mut tokens: Vec<(Token, usize)> = vec![];
+    let mut i = 0;
+    while i < chars.len() {
+        let start = i;
+        match chars[i] {
+            c if c.is_whitespace() => {i += 1; continue;},
+            '(' => {tokens.push((Token::Open, start)); i += 1; continue;},
+            ')' => {tokens.push((Token::Close, start)); i += 1; continue;},
+            // the old `--where a=1,b=2` form, a comma is the same as AND
+            ',' => {tokens.push((Token::And, start)); i += 1; continue;},
+            _ => ()
+        };
+
+        // a key is a name directly followed by a colon or a comparison
+        let mut j = i;
+        while j < chars.len() &&
+              (chars[j].is_alphanumeric() || chars[j] == '_' || chars[j] == '-' ||
+               chars[j] == '.') {
+            j += 1;
+        }
+        let mut key = String::new();
+        let mut op = String::new();
+        if j > i && chars[i].is_alphabetic() && j < chars.len() &&
+           (chars[j] == ':' || op_at(&chars, j).is_some()) {
+            key = chars[i..j].iter().map(|c| *c).collect();
+            i = j;
+            if chars[i] == ':' {i += 1;}
+            match op_at(&chars, i) {
+                Some(o) => {
+                    op = o.to_string();
+                    i += o.len();
+                },
+                None => ()
+            };
+            if i >= chars.len() || is_delim(chars[i]) {
+                return Err(query_error(query, i, &*format!("{} needs a value", key)))
+            }
+        } else if !bare.is_empty() && op_at(&chars, i).is_some() {
+            return Err(query_error(query, i, "a comparison needs a field before it"))
+        }
+
+        let value_start = i;
+        let value = match chars[i] {
+            '"' => Value::Text(try!(read_quoted(query, &chars, &mut i, '"'))),
+            '/' => {
+                let pattern = try!(read_quoted(query, &chars, &mut i, '/'));
+                match Regex::new(&*pattern) {
+                    Ok(r) => Value::Pattern(r),
+                    Err(e) => return Err(query_error(
+                        query,
+                        value_start,
+                        &*format!("regex error: {}", e.msg)
+                    ))
+                }
+            },
+            _ => {
+                let mut word = String::new();
+                while i < chars.len() && !is_delim(chars[i]) {
+                    word.push(chars[i]);
+                    i += 1;
+                }
+                if key.is_empty() {
+                    match &*word {
+                        "AND" => {tokens.push((Token::And, start)); continue;},
+                        "OR" => {tokens.push((Token::Or, start)); continue;},
+                        "NOT" => {tokens.push((Token::Not, start)); continue;},
+                        _ => ()
+                    };
+                }
+                Value::Text(word)
+            }
+        };
+        if key.is_empty() {key = bare.to_string();}
+        try!(check_term(query, &*key, &*op, &value, value_start));
+        tokens.push((Token::Term(Term {key: key, op: op, value: value}), start));
+    }
+    Ok(tokens)
+}
+
+/// recursive descent over the tokens, OR binds loosest then AND (which can
+/// be left out between terms) then NOT
+struct Parser<'a> {
+    query: &'a str,
+    tokens: Vec<(Token, usize)>,
+    pos: usize
+}
+
+impl<'a> Parser<'a> {
+    /// where the next token starts (or the end of the query)
+    fn at(&self) -> usize {
+        match self.tokens.get(self.pos) {
+            Some(&(_, at)) => at,
+            None => self.query.chars().count()
+        }
+    }
+
+    fn error(&self, msg: &str) -> ThecaError {
+        query_error(self.query, self.at(), msg)
+    }
+
+    fn next_is(&self, kind: &str) -> bool {
+        match (self.tokens.get(self.pos), kind) {
+            (Some(&(Token::Open, _)), "(") => true,
+            (Some(&(Token::Close, _)), ")") => true,
+            (Some(&(Token::And, _)), "AND") => true,
+            (Some(&(Token::Or, _)), "OR") => true,
+            (Some(&(Token::Not, _)), "NOT") => true,
+            (Some(&(Token::Term(_), _)), "term") => true,
+            _ => false
+        }
+    }
+
+    fn parse_or(&mut self) -> Result<Query, ThecaError> {
+        let mut left = try!(self.parse_and());
+        while self.next_is("OR") {
+            self.pos += 1;
+            let right = try!(self.parse_and());
+            left = Query::Or(Box::new(left), Box::new(right));
+        }
+        Ok(left)
+    }
+
+    fn parse_and(&mut self) -> Result<Query, ThecaError> {
+        let mut left = try!(self.parse_not());
+        loop {
+            if self.next_is("AND") {
+                self.pos += 1;
+            } else if !(self.next_is("term") || self.next_is("(") || self.next_is("NOT")) {
+                break;
+            }
+            let right = try!(self.parse_not());
+            left = Query::And(Box::new(left), Box::new(right));
+        }
+        Ok(left)
+    }
+
+    fn parse_not(&mut self) -> Result<Query, ThecaError> {
+        match self.next_is("NOT") {
+            true => {
+                self.pos += 1;
+                Ok(Query::Not(Box::new(try!(self.parse_not()))))
+            },
+            false => self.parse_primary()
+        }
+    }
+
+    fn parse_primary(&mut self) -> Result<Query, ThecaError> {
+        if self.next_is("(") {
+            let open = self.at();
+            self.pos += 1;
+            let inner = try!(self.parse_or());
+            if !self.next_is(")") {
+                return Err(match self.pos < self.tokens.len() {
+                    true => self.error("expected )"),
+                    false => query_error(self.query, open, "this ( is never closed")
+                })
+            }
+            self.pos += 1;
+            return Ok(inner)
+        }
+        if self.next_is("term") {
+            let term = match self.tokens[self.pos].0 {
+                Token::Term(ref t) => t.clone(),
+                _ => unreachable!()
+            };
+            self.pos += 1;
+            return Ok(Query::Term(term))
+        }
+        Err(match self.pos < self.tokens.len() {
+            true => match self.next_is(")") {
+                true => self.error("unexpected )"),
+                false => self.error("expected a term before this")
+            },
+            false => self.error("the query ends too early, expected a term")
+        })
+    }
+}
+
+/// parse a query, an empty query is None (everything matches)
+pub fn parse_query(query: &str) -> Result<Option<Query>, ThecaError> {
+    parse(query, "")
+}
+
+/// parse a `--where` query, where a bare word is the name of a custom field
+/// the note has (`estimate,customer=acme`) as it always was
+pub fn parse_where(query: &str) -> Result<Option<Query>, ThecaError> {
+    parse(query, "has")
+}
+
+fn parse(query: &str, bare: &str) -> Result<Option<Query>, ThecaError> {
+    let tokens = try!(tokenize(query, bare));
+    if tokens.is_empty() {
+        return Ok(None)
+    }
+    let mut parser = Parser {query: query, tokens: tokens, pos: 0};
+    let parsed = try!(parser.parse_or());
+    match parser.pos < parser.tokens.len() {
+        true => Err(match parser.next_is(")") {
+            true => parser.error("unexpected )"),
+            false => parser.error("expected AND or OR")
+        }),
+        false => Ok(Some(parsed))
+    }
+}
+
+fn ord_matches(op: &str, ord: Ordering) -> bool {
+    match op {
+        "" | "=" => ord == Ordering::Equal,
+        "!=" => ord != Ordering::Equal,
+        ">" => ord == Ordering::Greater,
+        "<" => ord == Ordering::Less,
+        ">=" => ord != Ordering::Less,
+        "<=" => ord != Ordering::Greater,
+        _ => false
+    }
+}
+
+fn text_matches(term: &Term, text: &str) -> bool {
+    match term.value {
+        Value::Pattern(ref re) => re.is_match(text) != (term.op == "!="),
+        Value::Text(ref t) => match &*term.op {
+            "=" => text == &**t,
+            "!=" => text != &**t,
+            _ => text.contains(&**t)
+        }
+    }
+}
+
+/// dates compare by day, durations by how long ago (`touched`) or how long
+/// until (`due`) the stamp is, a bare duration means at most that long
+fn time_matches(
+    term: &Term,
+    text: &str,
+    stamp: &str,
+    now: &Tm,
+    since: bool
+) -> Result<bool, ThecaError> {
+    if is_day(text) {
+        let day = match stamp.len() >= 10 {
+            true => &stamp[..10],
+            false => stamp
+        };
+        return Ok(ord_matches(&*term.op, day.cmp(text)))
+    }
+    let limit = try!(parse_duration(text));
+    let then = try!(parse_last_touched(stamp)).to_timespec();
+    let distance = match since {
+        true => (now.to_timespec() - then).num_seconds(),
+        false => (then - now.to_timespec()).num_seconds()
+    };
+    let op = match term.op.is_empty() {
+        true => "<=",
+        false => &*term.op
+    };
+    Ok(ord_matches(op, distance.cmp(&limit)))
+}
+
+fn term_matches(
+    term: &Term,
+    note: &ThecaItem,
+    search_body: bool,
+    now: &Tm
+) -> Result<bool, ThecaError> {
+    let text = match term.value {
+        Value::Text(ref t) => t.clone(),
+        Value::Pattern(_) => "".to_string()
+    };
+    Ok(match &*term.key.to_lowercase() {
+        "" => match search_body {
+            true => text_matches(term, &*note.body),
+            false => text_matches(term, &*note.title)
+        },
+        "title" => text_matches(term, &*note.title),
+        "body" => text_matches(term, &*note.body),
+        "status" => {
+            let want = match &*text.to_lowercase() {
+                "none" => "".to_string(),
+                s => s.to_string()
+            };
+            (note.status.to_lowercase() == want) != (term.op == "!=")
+        },
+        "touched" => try!(time_matches(term, &*text, &*note.last_touched, now, true)),
+        "due" => match note.due {
+            Some(ref d) => try!(time_matches(term, &*text, &*d, now, false)),
+            None => false
+        },
+        "id" => ord_matches(&*term.op, note.id.cmp(&text.parse::<usize>().unwrap_or(0))),
+        "priority" => match (&*term.op, &*text) {
+            ("", "none") | ("=", "none") => note.priority.is_none(),
+            ("!=", "none") => note.priority.is_some(),
+            ("", _) | ("=", _) => priority_matches(note, &try!(parse_priority_filter(&*text))),
+            ("!=", _) => !priority_matches(note, &try!(parse_priority_filter(&*text))),
+            (op, _) => note.priority.is_some() && ord_matches(
+                op,
+                priority_rank(&note.priority).cmp(&priority_rank(&try!(parse_priority(&*text))))
+            )
+        },
+        "has" => get_field(note, &*text).is_some(),
+        k => match get_field(note, match k.starts_with("field.") {
+            true => &term.key[6..],
+            false => &*term.key
+        }) {
+            Some(f) => match term.value {
+                Value::Pattern(_) => text_matches(term, &*f.value),
+                Value::Text(ref t) => ord_matches(&*term.op, try!(compare(f, &**t)))
+            },
+            None => false
+        }
+    })
+}
+
+/// does the note match the query?
+pub fn matches(
+    query: &Query,
+    note: &ThecaItem,
+    search_body: bool,
+    now: &Tm
+) -> Result<bool, ThecaError> {
+    Ok(match *query {
+        Query::And(ref a, ref b) => {
+            try!(matches(&**a, note, search_body, now)) &&
+            try!(matches(&**b, note, search_body, now))
+        },
+        Query::Or(ref a, ref b) => {
+            try!(matches(&**a, note, search_body, now)) ||
+            try!(matches(&**b, note, search_body, now))
+        },
+        Query::Not(ref q) => !try!(matches(&**q, note, search_body, now)),
+        Query::Term(ref t) => try!(term_matches(t, note, search_body, now))
+    })
+}
+
+/// the notes matching a query, bare words match the title (or the body
+/// if `search_body` is set)
+pub fn filter_notes(
+    notes: Vec<ThecaItem>,
+    query: &str,
+    search_body: bool
+) -> Result<Vec<ThecaItem>, ThecaError> {
+    let parsed = try!(parse_query(query));
+    filter(notes, parsed, search_body)
+}
+
+/// the notes matching a `--where` query
+pub fn filter_where(
+    notes: Vec<ThecaItem>,
+    where_clause: &str
+) -> Result<Vec<ThecaItem>, ThecaError> {
+    let parsed = try!(parse_where(where_clause));
+    filter(notes, parsed, false)
+}
+
+fn filter(
+    notes: Vec<ThecaItem>,
+    parsed: Option<Query>,
+    search_body: bool
+) -> Result<Vec<ThecaItem>, ThecaError> {
+    let parsed = match parsed {
+        Some(q) => q,
+        None => return Ok(notes)
+    };
+    let now = now();
+    let mut kept: Vec<ThecaItem> = vec![];
+    for n in notes.into_iter() {
+        if try!(matches(&parsed, &n, search_body, &now)) {kept.push(n);}
+    }
+    Ok(kept)
+}
@@ -0,0 +1,163 @@
+//  _   _                    
+// | |_| |__   ___  ___ __ _ 
+// | __| '_ \ / _ \/ __/ _` |
+// | |_| | | |  __/ (_| (_| |
+//  \__|_| |_|\___|\___\__,_|
+//
+// licensed under the MIT license <http://opensource.org/licenses/MIT>
+//
+// replace.rs
+//   `theca replace`, find and replace across the titles and/or bodies of
+//   every matching note, with a line diff of what is going to change.
+
+use std::io::{Write};
+use std::iter::{repeat};
+
+use regex::{Regex};
+use term::{stdout};
+use term::color::{RED, GREEN};
+
+use ::{ThecaItem};
+use errors::{ThecaError, GenericError};
+
+/// which parts of a note a replacement is made in
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub enum Target {
+    Title,
+    Body,
+    Both
+}
+
+impl Target {
+    pub fn title(&self) -> bool {
+        *self != Target::Body
+    }
+
+    pub fn body(&self) -> bool {
+        *self != Target::Title
+    }
+}
+
+/// parse the value of `--in`
+pub fn parse_target(target: &str) -> Result<Target, ThecaError> {
+    match target {
+        "title" => Ok(Target::Title),
+        "body" => Ok(Target::Body),
+        "both" => Ok(Target::Both),
+        _ => specific_fail!(format!(
+            "--in should be one of title, body or both, not '{}'",
+            target
+        ))
+    }
+}
+
+/// what a note will look like once the replacement has been made
+#[derive(Clone, PartialEq, Debug)]
+pub struct Change {
+    pub id: usize,
+    pub title: String,
+    pub body: String
+}
+
+/// replace every occurrence of `pattern` (or every match of `re`, where
+/// `$1`/`$name` in the replacement refer to its groups) in `text`
+pub fn replace_text(
+    text: &str,
+    pattern: &str,
+    re: Option<&Regex>,
+    replacement: &str
+) -> String {
+    match re {
+        Some(r) => r.replace_all(text, replacement),
+        None => text.replace(pattern, replacement)
+    }
+}
+
+/// the change the replacement makes to `note`, if it makes one at all
+pub fn note_change(
+    note: &ThecaItem,
+    pattern: &str,
+    re: Option<&Regex>,
+    replacement: &str,
+    target: Target
+) -> Option<Change> {
+    let title = match target.title() {
+        true => replace_text(&*note.title, pattern, re, replacement),
+        false => note.title.clone()
+    };
+    let body = match target.body() {
+        true => replace_text(&*note.body, pattern, re, replacement),
+        false => note.body.clone()
+    };
+    match title != note.title || body != note.body {
+        true => Some(Change {id: note.id, title: title, body: body}),
+        false => None
+    }
+}
+
+/// the lines removed from `old` (prefixed with `-`) and added in `new`
+/// (prefixed with `+`), in order, lines both share aren't included
+pub fn diff_lines(old: &str, new: &str) -> Vec<String> {
+    let a: Vec<&str> = old.lines().collect();
+    let b: Vec<&str> = new.lines().collect();
+    // lengths of the longest common subsequences of the tails of a and b
+    let mut lcs: Vec<Vec<usize>> = repeat(repeat(0).take(b.len() + 1).collect())
+                                       .take(a.len() + 1).collect();
+    for i in (0..a.len()).rev() {
+        for j in (0..b.len()).rev() {
+            lcs[i][j] = match a[i] == b[j] {
+                true => lcs[i + 1][j + 1] + 1,
+                false => match lcs[i + 1][j] >= lcs[i][j + 1] {
+                    true => lcs[i + 1][j],
+                    false => lcs[i][j + 1]
+                }
+            };
+        }
+    }
+    let mut lines = vec![];
+    let (mut i, mut j) = (0, 0);
+    while i < a.len() || j < b.len() {
+        if i < a.len() && j < b.len() && a[i] == b[j] {
+            i += 1;
+            j += 1;
+        } else if j == b.len() || (i < a.len() && lcs[i + 1][j] >= lcs[i][j + 1]) {
+            lines.push(format!("-{}", a[i]));
+            i += 1;
+        } else {
+            lines.push(format!("+{}", b[j]));
+            j += 1;
+        }
+    }
+    lines
+}
+
+/// print the diff between `note` and `change`, a field at a time
+pub fn print_diff(
+    note: &ThecaItem,
+    change: &Change,
+    tty: bool
+) -> Result<(), ThecaError> {
+    let mut t = match stdout() {
+        Some(t) => t,
+        None => specific_fail_str!("could not retrieve standard output.")
+    };
+    for &(field, old, new) in [
+        ("title", &note.title, &change.title),
+        ("body", &note.body, &change.body)
+    ].iter() {
+        if old == new {continue;}
+        try!(write!(t, "note {} {}:\n", note.id, field));
+        for line in diff_lines(&**old, &**new).iter() {
+            if tty {
+                try!(t.fg(match line.starts_with("-") {
+                    true => RED,
+                    false => GREEN
+                }));
+            }
+            try!(write!(t, "\t{}", line));
+            if tty {try!(t.reset());}
+            try!(write!(t, "\n"));
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,232 @@
+//  _   _                    
+// | |_| |__   ___  ___ __ _ 
+// | __| '_ \ / _ \/ __/ _` |
+// | |_| | | |  __/ (_| (_| |
+//  \__|_| |_|\___|\___\__,_|
+//
+// licensed under the MIT license <http://opensource.org/licenses/MIT>
+//
+// schedule.rs
+//   parsing of durations ("30m", "2h", "1w") and of the schedules used by
+//   recurring notes, either a fixed interval or a cron style
+//   "minute hour day-of-month month day-of-week" line.
+
+use time::{Tm, Duration, at, now, strftime};
+
+use ::{DATEFMT};
+use errors::{ThecaError, GenericError};
+use utils::{parse_last_touched};
+
+/// length of a duration like "30m", "2h", "3d", "1w" in seconds
+pub fn parse_duration(spec: &str) -> Result<i64, ThecaError> {
+    let spec = spec.trim();
+    let split = match spec.find(|c: char| !c.is_digit(10)) {
+        Some(i) => i,
+        None => spec.len()
+    };
+    let amount = match spec[..split].parse::<i64>() {
+        Ok(a) => a,
+        Err(_) => specific_fail!(format!("invalid duration: {}", spec))
+    };
+    let unit = match &spec[split..] {
+        "" | "s" => 1,
+        "m" => 60,
+        "h" => 60*60,
+        "d" => 60*60*24,
+        "w" => 60*60*24*7,
+        _ => specific_fail!(format!(
+            "invalid duration: {} (units are s, m, h, d, w)",
+            spec
+        ))
+    };
+    Ok(amount * unit)
+}
+
+/// a date given on the command line, either YYYY-MM-DD (midnight local
+/// time) or a duration from now like "3d", formatted with DATEFMT
+pub fn parse_date(date: &str) -> Result<String, ThecaError> {
+    let date = date.trim();
+    match parse_duration(date) {
+        Ok(secs) => Ok(try!(strftime(
+            DATEFMT,
+            &at(now().to_timespec() + Duration::seconds(secs))
+        ))),
+        Err(_) => {
+            let full = format!("{} 00:00:00 {}", date, try!(strftime("%z", &now())));
+            match parse_last_touched(&*full) {
+                Ok(t) => Ok(try!(strftime(DATEFMT, &t))),
+                Err(_) => specific_fail!(format!(
+                    "invalid date: {} (use YYYY-MM-DD or something like 3d)",
+                    date
+                ))
+            }
+        }
+    }
+}
+
+/// the values a single cron field matches
+fn parse_cron_field(
+    field: &str,
+    min: i32,
+    max: i32
+) -> Result<Vec<i32>, ThecaError> {
+    let mut values: Vec<i32> = vec![];
+    for part in field.split(',') {
+        let (range, step) = match part.find('/') {
+            Some(i) => match part[i+1..].parse::<i32>() {
+                Ok(s) if s > 0 => (&part[..i], s),
+                _ => specific_fail!(format!("invalid cron field: {}", field))
+            },
+            None => (part, 1)
+        };
+        let (start, end) = match range {
+            "*" => (min, max),
+            _ => match range.find('-') {
+                Some(i) => match (range[..i].parse::<i32>(), range[i+1..].parse::<i32>()) {
+                    (Ok(s), Ok(e)) => (s, e),
+                    _ => specific_fail!(format!("invalid cron field: {}", field))
+                },
+                None => match range.parse::<i32>() {
+                    Ok(v) => (v, v),
+                    Err(_) => specific_fail!(format!("invalid cron field: {}", field))
+                }
+            }
+        };
+        if start < min || end > max || start > end {
+            specific_fail!(format!("cron field out of range: {}", field));
+        }
+        let mut v = start;
+        while v <= end {
+            if !values.contains(&v) {values.push(v);}
+            v += step;
+        }
+    }
+    Ok(values)
+}
+
+pub struct Cron {
+    pub minutes: Vec<i32>,
+    pub hours: Vec<i32>,
+    pub days: Vec<i32>,
+    pub months: Vec<i32>,
+    pub weekdays: Vec<i32>,
+    /// whether the day of the month or week fields start with `*`
+    pub any_day: bool,
+    pub any_weekday: bool
+}
+
+impl Cron {
+    fn matches_day(&self, t: &Tm) -> bool {
+        let day = self.days.contains(&t.tm_mday);
+        let weekday = self.weekdays.contains(&t.tm_wday);
+        // like cron, when both the day of the month and the day of the week
+        // are restricted a day matching either is enough
+        let matched = match self.any_day || self.any_weekday {
+            true => day && weekday,
+            false => day || weekday
+        };
+        matched && self.months.contains(&(t.tm_mon+1))
+    }
+
+    fn matches(&self, t: &Tm) -> bool {
+        self.matches_day(t) && self.hours.contains(&t.tm_hour) &&
+        self.minutes.contains(&t.tm_min)
+    }
+}
+
+pub enum Schedule {
+    /// every n seconds
+    Fixed(i64),
+    /// every n months
+    Months(i32),
+    Cron(Cron)
+}
+
+impl Schedule {
+    /// parse "1w", "3mo" or "1y" style intervals or a five field cron line
+    /// like "0 9 * * 1"
+    pub fn parse(spec: &str) -> Result<Schedule, ThecaError> {
+        let fields: Vec<&str> = spec.split(' ').filter(|f| !f.is_empty())
+                                               .collect();
+        match fields.len() {
+            1 => {
+                let spec = fields[0];
+                if spec.ends_with("mo") || spec.ends_with("y") {
+                    let (amount, months) = match spec.ends_with("mo") {
+                        true => (&spec[..spec.len()-2], 1),
+                        false => (&spec[..spec.len()-1], 12)
+                    };
+                    return match amount.parse::<i32>() {
+                        Ok(a) if a > 0 => Ok(Schedule::Months(a * months)),
+                        _ => specific_fail!(format!("invalid schedule: {}", spec))
+                    }
+                }
+                match try!(parse_duration(spec)) {
+                    s if s > 0 => Ok(Schedule::Fixed(s)),
+                    _ => specific_fail!(format!("invalid schedule: {}", spec))
+                }
+            },
+            5 => Ok(Schedule::Cron(Cron {
+                minutes: try!(parse_cron_field(fields[0], 0, 59)),
+                hours: try!(parse_cron_field(fields[1], 0, 23)),
+                days: try!(parse_cron_field(fields[2], 1, 31)),
+                months: try!(parse_cron_field(fields[3], 1, 12)),
+                weekdays: try!(parse_cron_field(fields[4], 0, 6)),
+                any_day: fields[2].starts_with("*"),
+                any_weekday: fields[4].starts_with("*")
+            })),
+            _ => specific_fail!(format!(
+                "invalid schedule: {} (use something like 1w, 3mo or a cron line like \"0 9 * * 1\")",
+                spec
+            ))
+        }
+    }
+
+    /// the first time strictly after `t` the schedule fires
+    pub fn next_after(&self, t: &Tm) -> Result<Tm, ThecaError> {
+        match *self {
+            Schedule::Fixed(secs) => Ok(at(t.to_timespec() + Duration::seconds(secs))),
+            Schedule::Months(months) => {
+                let mut next = t.clone();
+                let total = next.tm_mon + months;
+                next.tm_year += total / 12;
+                next.tm_mon = total % 12;
+                // mktime sorts out days that don't exist in the new month
+                Ok(at(next.to_timespec()))
+            },
+            Schedule::Cron(ref cron) => {
+                // start at the next whole minute
+                let mut next = at(
+                    t.to_timespec() + Duration::seconds(60 - t.tm_sec as i64)
+                );
+                // give up after a bit more than a leap year of minutes
+                for _ in 0..(367*24*60) {
+                    if cron.matches(&next) {return Ok(next);}
+                    let skip = match cron.matches_day(&next) {
+                        true => 60,
+                        // skip to the start of the next day
+                        false => 60 * (60*24 - next.tm_hour*60 - next.tm_min) as i64
+                    };
+                    next = at(next.to_timespec() + Duration::seconds(skip));
+                }
+                specific_fail_str!("cron schedule never fires")
+            }
+        }
+    }
+
+    /// when the occurrence after one due at `due` should be due, missed
+    /// occurrences are skipped so the result is always after `now`
+    pub fn next_occurrence(&self, due: &Tm, now: &Tm) -> Result<Tm, ThecaError> {
+        let mut next = match *self {
+            // cron schedules don't drift, no need to walk from an old date
+            Schedule::Cron(_) if due.to_timespec() < now.to_timespec() => {
+                try!(self.next_after(now))
+            },
+            _ => try!(self.next_after(due))
+        };
+        while next.to_timespec() <= now.to_timespec() {
+            next = try!(self.next_after(&next));
+        }
+        Ok(next)
+    }
+}
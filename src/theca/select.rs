@@ -0,0 +1,117 @@
+//  _   _                    
+// | |_| |__   ___  ___ __ _ 
+// | __| '_ \ / _ \/ __/ _` |
+// | |_| | | |  __/ (_| (_| |
+//  \__|_| |_|\___|\___\__,_|
+//
+// licensed under the MIT license <http://opensource.org/licenses/MIT>
+//
+// select.rs
+//   picking the notes bulk commands (del, edit, transfer, archive) work on,
+//   with id specs like `3-9,12,!5` and/or a `--where` query.
+
+use errors::{ThecaError, GenericError};
+
+/// a piece of an id spec, an id or a range of ids (both ends included)
+/// that is either selected or, after a `!`, left out
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub struct Term {
+    pub exclude: bool,
+    pub from: usize,
+    pub to: usize
+}
+
+impl Term {
+    pub fn is_range(&self) -> bool {
+        self.from != self.to
+    }
+
+    pub fn contains(&self, id: usize) -> bool {
+        self.from <= id && id <= self.to
+    }
+}
+
+fn parse_id(id: &str, spec: &str) -> Result<usize, ThecaError> {
+    match id.trim().parse::<usize>() {
+        Ok(i) => Ok(i),
+        Err(_) => specific_fail!(format!(
+            "'{}' isn't a note id or a range of ids (like 3-9,12,!5).",
+            spec
+        ))
+    }
+}
+
+/// parse an id spec, a comma separated list of ids (`12`) and ranges
+/// (`3-9`), either of which can be excluded with a leading `!`
+pub fn parse_spec(spec: &str) -> Result<Vec<Term>, ThecaError> {
+    let mut terms = vec![];
+    for part in spec.split(',') {
+        let part = part.trim();
+        let (exclude, part) = match part.starts_with("!") {
+            true => (true, part[1..].trim()),
+            false => (false, part)
+        };
+        let bounds: Vec<&str> = part.splitn(2, '-').collect();
+        let (from, to) = match bounds.len() {
+            2 => (try!(parse_id(bounds[0], spec)), try!(parse_id(bounds[1], spec))),
+            _ => {
+                let id = try!(parse_id(part, spec));
+                (id, id)
+            }
+        };
+        if from > to {
+            specific_fail!(format!("the range {}-{} is backwards.", from, to));
+        }
+        terms.push(Term {exclude: exclude, from: from, to: to});
+    }
+    Ok(terms)
+}
+
+/// whether the specs select more than the ids they list, so ranges or
+/// exclusions, which are confirmed before anything is changed
+pub fn is_bulk(specs: &[String]) -> Result<bool, ThecaError> {
+    for spec in specs.iter() {
+        if try!(parse_spec(&*spec)).iter().any(|t| t.exclude || t.is_range()) {
+            return Ok(true)
+        }
+    }
+    Ok(false)
+}
+
+/// the ids selected by `specs`, in the order given, ranges only select the
+/// notes in `existing` (single ids are kept so a missing note is pointed
+/// out). `matched` are the notes found by a query, which narrow the ids
+/// down, or are the selection when no ids are given.
+pub fn select_ids(
+    specs: &[String],
+    existing: &[usize],
+    matched: Option<&[usize]>
+) -> Result<Vec<usize>, ThecaError> {
+    let mut terms = vec![];
+    for spec in specs.iter() {
+        terms.push_all(&try!(parse_spec(&*spec)));
+    }
+    let mut ids: Vec<usize> = vec![];
+    for t in terms.iter().filter(|t| !t.exclude) {
+        match t.is_range() {
+            true => for id in existing.iter().filter(|i| t.contains(**i)) {
+                if !ids.contains(id) {ids.push(*id);}
+            },
+            false => if !ids.contains(&t.from) {ids.push(t.from);}
+        };
+    }
+    let picked = terms.iter().any(|t| !t.exclude);
+    ids = match (picked, matched) {
+        (true, Some(m)) => ids.into_iter().filter(|i| m.contains(i)).collect(),
+        (true, None) => ids,
+        (false, Some(m)) => m.to_vec(),
+        (false, None) => specific_fail_str!(
+            "there are only ids to leave out, which notes should be selected?"
+        )
+    };
+    ids.retain(|i| !terms.iter().any(|t| t.exclude && t.contains(*i)));
+    if ids.is_empty() {
+        specific_fail_str!("no notes were selected.");
+    }
+    Ok(ids)
+}
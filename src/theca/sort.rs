@@ -0,0 +1,246 @@
+//  _   _                    
+// | |_| |__   ___  ___ __ _ 
+// | __| '_ \ / _ \/ __/ _` |
+// | |_| | | |  __/ (_| (_| |
+//  \__|_| |_|\___|\___\__,_|
+//
+// licensed under the MIT license <http://opensource.org/licenses/MIT>
+//
+// sort.rs
+//   `--sort status,-due,title`, sorting notes by a list of keys (any field
+//   of a note, or a custom field) with text compared the way people read it.
+
+use std::cmp::{Ordering};
+use std::cmp::Ordering::{Less, Equal, Greater};
+
+use regex::{Regex};
+use time::{Tm, now};
+
+use ::{ThecaItem, NOSTATUS, STARTED, URGENT, DONE};
+use errors::{ThecaError, GenericError};
+use deps::{blockers};
+use fields::{Field, get_field};
+use order::{is_pinned};
+use priority::{priority_rank};
+use schedule::{parse_duration};
+use timetrack::{logged};
+use utils::{parse_last_touched};
+
+/// letters folded onto the letter they are accented versions of, so
+/// `éclair` sorts next to `eclair` rather than after `zebra`
+static ACCENTED: &'static str = "àáâãäåāăąçćčďđèéêëēėęěğìíîïīįıłñńňòóôõöøōőœŕřśşšťùúûüūůűųýÿźżž";
+static BASE: &'static str =     "aaaaaaaaacccddeeeeeeeegiiiiiiilnnnooooooooorrssstuuuuuuuuyyzzz";
+
+/// a key to sort by, `-key` sorts it in descending order
+#[derive(Clone, PartialEq, Debug)]
+pub struct SortKey {
+    pub key: String,
+    pub descending: bool
+}
+
+/// parse a comma separated list of sort keys, the fields of a note are id,
+/// title, status, body, touched, uuid, parent, blocked_by, attachments,
+/// due, every, series, archived, pinned, time and priority, any other key
+/// is a custom field
+pub fn parse_sort(sort: &str) -> Result<Vec<SortKey>, ThecaError> {
+    let name_re = Regex::new(r"^[A-Za-z_][\w-]*$").unwrap();
+    let mut keys = vec![];
+    for part in sort.split(',').map(|p| p.trim()).filter(|p| !p.is_empty()) {
+        let (descending, name) = match part.starts_with("-") {
+            true => (true, part[1..].trim()),
+            false => (false, part)
+        };
+        if !name_re.is_match(name) {
+            specific_fail!(format!("'{}' isn't a field to sort by.", part));
+        }
+        let key = match name {
+            "last_touched" => "touched",
+            "blocked" => "blocked_by",
+            "time_log" => "time",
+            k => k
+        };
+        keys.push(SortKey {key: key.to_string(), descending: descending});
+    }
+    Ok(keys)
+}
+
+/// part of a piece of text as it's sorted, runs of digits are compared as
+/// numbers (by length once leading zeros are gone, then digit by digit) and
+/// come before letters
+#[derive(PartialEq, Eq, PartialOrd, Ord, Debug)]
+enum Piece {
+    Number(usize, String),
+    Letter(char)
+}
+
+fn base(c: char) -> char {
+    let lower = c.to_string().to_lowercase().chars().next().unwrap_or(c);
+    match ACCENTED.chars().position(|a| a == lower) {
+        Some(i) => BASE.chars().nth(i).unwrap_or(lower),
+        None => lower
+    }
+}
+
+fn pieces(text: &str) -> Vec<Piece> {
+    let mut pieces = vec![];
+    let mut digits = String::new();
+    for c in text.chars() {
+        if c.is_digit(10) {
+            digits.push(c);
+            continue;
+        }
+        if !digits.is_empty() {
+            let number = digits.trim_left_matches('0').to_string();
+            pieces.push(Piece::Number(number.len(), number));
+            digits = String::new();
+        }
+        match c {
+            'ß' => {
+                pieces.push(Piece::Letter('s'));
+                pieces.push(Piece::Letter('s'));
+            },
+            _ => pieces.push(Piece::Letter(base(c)))
+        };
+    }
+    if !digits.is_empty() {
+        let number = digits.trim_left_matches('0').to_string();
+        pieces.push(Piece::Number(number.len(), number));
+    }
+    pieces
+}
+
+/// compare text ignoring case and accents, with numbers in order (`note 2`
+/// before `note 10`), falling back on case and accents and then the bytes
+/// so different text never compares equal
+pub fn collate(a: &str, b: &str) -> Ordering {
+    match pieces(a).cmp(&pieces(b)) {
+        Equal => match a.to_lowercase().cmp(&b.to_lowercase()) {
+            Equal => a.cmp(b),
+            o => o
+        },
+        o => o
+    }
+}
+
+/// the value of a key on a note, numbers come before text
+#[derive(Clone, PartialEq, Debug)]
+enum Value {
+    Number(i64),
+    Text(String)
+}
+
+fn cmp_values(a: &Value, b: &Value) -> Ordering {
+    match (a, b) {
+        (&Value::Number(ref x), &Value::Number(ref y)) => x.cmp(y),
+        (&Value::Text(ref x), &Value::Text(ref y)) => collate(&**x, &**y),
+        (&Value::Number(_), &Value::Text(_)) => Less,
+        (&Value::Text(_), &Value::Number(_)) => Greater
+    }
+}
+
+/// statuses from the most to the least pressing
+fn status_rank(status: &str) -> i64 {
+    if status == URGENT {
+        0
+    } else if status == STARTED {
+        1
+    } else if status == NOSTATUS {
+        2
+    } else if status == DONE {
+        3
+    } else {
+        4
+    }
+}
+
+fn date_value(date: &str) -> Option<Value> {
+    match parse_last_touched(date) {
+        Ok(t) => Some(Value::Number(t.to_timespec().sec)),
+        Err(_) => Some(Value::Text(date.to_string()))
+    }
+}
+
+fn field_value(field: &Field) -> Option<Value> {
+    match &*field.kind {
+        "int" => field.value.parse::<i64>().ok().map(|i| Value::Number(i)),
+        "duration" => parse_duration(&*field.value).ok().map(|d| Value::Number(d)),
+        "bool" => Some(Value::Number(match &*field.value {
+            "true" => 1,
+            _ => 0
+        })),
+        _ => Some(Value::Text(field.value.clone()))
+    }
+}
+
+/// the value of `key` on a note, None if the note doesn't have one
+fn value(note: &ThecaItem, key: &str, now: &Tm) -> Option<Value> {
+    match key {
+        "id" => Some(Value::Number(note.id as i64)),
+        "title" => Some(Value::Text(note.title.clone())),
+        "status" => Some(Value::Number(status_rank(&*note.status))),
+        "body" => Some(Value::Text(note.body.clone())),
+        "touched" => date_value(&*note.last_touched),
+        "uuid" => note.uuid.clone().map(|u| Value::Text(u)),
+        "parent" => note.parent.map(|p| Value::Number(p as i64)),
+        "blocked_by" => Some(Value::Number(blockers(note).len() as i64)),
+        "attachments" => Some(Value::Number(match note.attachments {
+            Some(ref a) => a.len() as i64,
+            None => 0
+        })),
+        "due" => match note.due {
+            Some(ref d) => date_value(&**d),
+            None => None
+        },
+        "every" => note.every.clone().map(|e| Value::Text(e)),
+        "series" => note.series.clone().map(|s| Value::Text(s)),
+        "archived" => match note.archived {
+            Some(ref a) => date_value(&**a),
+            None => None
+        },
+        // pinned notes first
+        "pinned" => Some(Value::Number(match is_pinned(note) {
+            true => 0,
+            false => 1
+        })),
+        "time" => logged(note, None, now).ok().map(|s| Value::Number(s)),
+        // more important first, letters before numbers
+        "priority" => match priority_rank(&note.priority) {
+            (2, _) => None,
+            (kind, rank) => Some(Value::Number(((kind as i64) << 32) + rank as i64))
+        },
+        name => match get_field(note, name) {
+            Some(f) => field_value(f),
+            None => None
+        }
+    }
+}
+
+/// compare the values of two notes key by key, notes without a value for
+/// a key go after the notes with one whichever way the key is sorted
+fn cmp_keyed(a: &Vec<Option<Value>>, b: &Vec<Option<Value>>, keys: &[SortKey]) -> Ordering {
+    for (i, k) in keys.iter().enumerate() {
+        let order = match (&a[i], &b[i]) {
+            (&Some(ref x), &Some(ref y)) => match k.descending {
+                true => cmp_values(x, y).reverse(),
+                false => cmp_values(x, y)
+            },
+            (&Some(_), &None) => Less,
+            (&None, &Some(_)) => Greater,
+            (&None, &None) => Equal
+        };
+        if order != Equal {return order}
+    }
+    Equal
+}
+
+/// sort notes by each key in turn, notes that are equal on every key keep
+/// the order they were in
+pub fn sort_by_keys(notes: &mut Vec<ThecaItem>, keys: &[SortKey]) {
+    let now = now();
+    // the values are worked out once rather than on every comparison
+    let mut keyed: Vec<(Vec<Option<Value>>, ThecaItem)> = notes.iter().map(|n| {
+        (keys.iter().map(|k| value(n, &*k.key, &now)).collect(), n.clone())
+    }).collect();
+    keyed.sort_by(|a, b| cmp_keyed(&a.0, &b.0, keys));
+    *notes = keyed.into_iter().map(|(_, n)| n).collect();
+}
@@ -0,0 +1,204 @@
+//  _   _                    
+// | |_| |__   ___  ___ __ _ 
+// | __| '_ \ / _ \/ __/ _` |
+// | |_| | | |  __/ (_| (_| |
+//  \__|_| |_|\___|\___\__,_|
+//
+// licensed under the MIT license <http://opensource.org/licenses/MIT>
+//
+// templates.rs
+//   named note templates kept in `<profile folder>/<profile>.templates/`,
+//   `theca add --template bug` prefills a new note from one of them.
+
+use std::fs::{PathExt, File, read_dir};
+use std::io::{Read};
+use std::path::{Path, PathBuf};
+
+use std::old_io::{stdin};
+
+use regex::{Regex};
+
+use errors::{ThecaError, GenericError};
+use utils::{find_profile_folder};
+
+/// A template after its placeholders have been filled in, the header
+/// lines (between two `---` lines at the top of the file) set everything
+/// but the body
+pub struct Template {
+    pub status: String,
+    pub priority: String,
+    pub fields: Vec<String>,
+    pub tags: Vec<String>,
+    pub body: String
+}
+
+/// what an empty template looks like when it is first edited
+pub static SKELETON: &'static str = "---
+status: 
+priority: 
+set: 
+tags: 
+---
+";
+
+/// folder the templates of a profile are stored in
+pub fn templates_path(
+    profile_folder: &String,
+    profile_name: &String
+) -> Result<PathBuf, ThecaError> {
+    let mut templates = try!(find_profile_folder(profile_folder));
+    templates.push(&(profile_name.to_string() + ".templates"));
+    Ok(templates)
+}
+
+/// the names of every template in the folder, sorted
+pub fn template_names(templates: &Path) -> Result<Vec<String>, ThecaError> {
+    let mut names: Vec<String> = vec![];
+    if templates.is_dir() {
+        for file in try!(read_dir(templates)) {
+            let path = try!(file).path();
+            match (path.extension(), path.file_stem()) {
+                (Some(e), Some(s)) if e.to_str() == Some("md") => {
+                    names.push(s.to_str().unwrap_or("").to_string());
+                },
+                _ => ()
+            };
+        }
+    }
+    names.sort();
+    Ok(names)
+}
+
+/// path of the template `name`, names are kept to a single path component
+pub fn template_file(templates: &Path, name: &String) -> Result<PathBuf, ThecaError> {
+    let re = Regex::new(r"^[\w-]+$").unwrap();
+    if !re.is_match(&*name) {
+        specific_fail!(format!(
+            "invalid template name: {} (use letters, numbers, - and _)",
+            name
+        ));
+    }
+    Ok(templates.join(&format!("{}.md", name)))
+}
+
+/// the unexpanded contents of the template `name`
+pub fn read_template(templates: &Path, name: &String) -> Result<String, ThecaError> {
+    let path = try!(template_file(templates, name));
+    if !path.is_file() {
+        specific_fail!(format!("template {} doesn't exist", name));
+    }
+    let mut file = try!(File::open(&path));
+    let mut contents = String::new();
+    try!(file.read_to_string(&mut contents));
+    Ok(contents)
+}
+
+/// replace `{{date}}`, `{{profile}}` and `{{prompt:Question}}` placeholders,
+/// `prompt` is asked for the answer to each question (only once if the
+/// same question appears more than once)
+pub fn expand<F>(
+    text: &str,
+    date: &str,
+    profile: &str,
+    mut prompt: F
+) -> Result<String, ThecaError> where F: FnMut(&str) -> Result<String, ThecaError> {
+    let re = Regex::new(r"\{\{\s*([^}:]+?)\s*(?::([^}]*))?\}\}").unwrap();
+    let mut answers: Vec<(String, String)> = vec![];
+    let mut expanded = String::new();
+    let mut last = 0;
+    for caps in re.captures_iter(text) {
+        let (start, end) = caps.pos(0).unwrap();
+        expanded.push_str(&text[last..start]);
+        let value = match (caps.at(1).unwrap_or(""), caps.at(2)) {
+            ("date", None) => date.to_string(),
+            ("profile", None) => profile.to_string(),
+            ("prompt", Some(q)) => {
+                let q = q.trim().to_string();
+                match answers.iter().position(|a| a.0 == q) {
+                    Some(i) => answers[i].1.clone(),
+                    None => {
+                        let answer = try!(prompt(&*q));
+                        answers.push((q, answer.clone()));
+                        answer
+                    }
+                }
+            },
+            _ => specific_fail!(format!(
+                "unknown placeholder: {} (use {{{{date}}}}, {{{{profile}}}} or \
+                 {{{{prompt:Question}}}})",
+                &text[start..end]
+            ))
+        };
+        expanded.push_str(&*value);
+        last = end;
+    }
+    expanded.push_str(&text[last..]);
+    Ok(expanded)
+}
+
+/// split an expanded template into its header and body
+pub fn parse_template(text: &str) -> Result<Template, ThecaError> {
+    let mut template = Template {
+        status: "".to_string(),
+        priority: "".to_string(),
+        fields: vec![],
+        tags: vec![],
+        body: text.to_string()
+    };
+    let lines: Vec<&str> = text.lines().collect();
+    if lines.is_empty() || lines[0].trim() != "---" {
+        return Ok(template)
+    }
+    let end = match lines.iter().skip(1).position(|l| l.trim() == "---") {
+        Some(e) => e + 1,
+        None => specific_fail_str!("the template header is never closed with ---")
+    };
+    for l in lines[1..end].iter().filter(|l| !l.trim().is_empty()) {
+        let (key, value) = match l.find(':') {
+            Some(i) => (l[..i].trim(), l[i+1..].trim()),
+            None => specific_fail!(format!("invalid template header line: {}", l))
+        };
+        if value.is_empty() {continue;}
+        match key {
+            "status" => template.status = match &*value.to_lowercase() {
+                "started" => "Started".to_string(),
+                "urgent" => "Urgent".to_string(),
+                "none" => "".to_string(),
+                _ => specific_fail!(format!(
+                    "invalid template status: {} (use started, urgent or none)",
+                    value
+                ))
+            },
+            "priority" => template.priority = value.to_string(),
+            "set" => template.fields.push(value.to_string()),
+            "tags" => for t in value.split(|c: char| c == ',' || c == ' ') {
+                let t = t.trim().trim_left_matches('#');
+                if !t.is_empty() {template.tags.push(t.to_string());}
+            },
+            _ => specific_fail!(format!(
+                "unknown template header: {} (use status, priority, set or tags)",
+                key
+            ))
+        };
+    }
+    template.body = lines[end+1..].connect("\n");
+    Ok(template)
+}
+
+/// the body a note made from the template starts with, tags are added
+/// on their own line as #hashtags
+pub fn initial_body(template: &Template) -> String {
+    let tags: Vec<String> = template.tags.iter().map(|t| format!("#{}", t)).collect();
+    match (template.body.trim().is_empty(), tags.is_empty()) {
+        (_, true) => template.body.clone(),
+        (true, false) => tags.connect(" "),
+        (false, false) => format!("{}\n\n{}", template.body.trim_right(), tags.connect(" "))
+    }
+}
+
+/// ask a template question on the terminal
+pub fn ask(question: &str) -> Result<String, ThecaError> {
+    print!("{}: ", question);
+    let answer = try!(stdin().read_line());
+    Ok(answer.trim().to_string())
+}
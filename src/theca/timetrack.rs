@@ -0,0 +1,149 @@
+//  _   _                    
+// | |_| |__   ___  ___ __ _ 
+// | __| '_ \ / _ \/ __/ _` |
+// | |_| | | |  __/ (_| (_| |
+//  \__|_| |_|\___|\___\__,_|
+//
+// licensed under the MIT license <http://opensource.org/licenses/MIT>
+//
+// timetrack.rs
+//   time tracking for notes, `theca start` and `theca stop` log intervals
+//   on a note which `theca timesheet` adds up per note and per tag.
+
+use regex::{Regex};
+use time::{Tm, Duration, at};
+
+use ::{ThecaItem};
+use errors::{ThecaError};
+use utils::{parse_last_touched};
+
+/// A logged interval of work on a note, `stop` is None while the timer
+/// is still running
+#[derive(RustcDecodable, RustcEncodable, Clone)]
+pub struct TimeEntry {
+    pub start: String,
+    pub stop: Option<String>
+}
+
+/// time spent on a single note
+#[derive(RustcEncodable, Clone)]
+pub struct NoteTime {
+    pub id: usize,
+    pub title: String,
+    pub seconds: i64
+}
+
+/// time spent on notes with a tag
+#[derive(RustcEncodable, Clone)]
+pub struct TagTime {
+    pub tag: String,
+    pub seconds: i64
+}
+
+/// time spent per note and per tag over a period
+#[derive(RustcEncodable, Clone)]
+pub struct Timesheet {
+    pub notes: Vec<NoteTime>,
+    pub tags: Vec<TagTime>,
+    pub total: i64
+}
+
+/// is a timer running on the note?
+pub fn running(note: &ThecaItem) -> bool {
+    match note.time_log {
+        Some(ref l) => l.iter().any(|e| e.stop.is_none()),
+        None => false
+    }
+}
+
+/// ids of the notes with running timers
+pub fn running_notes(notes: &[ThecaItem]) -> Vec<usize> {
+    notes.iter().filter(|n| running(n)).map(|n| n.id).collect()
+}
+
+/// midnight (local time) at the start of the monday of the week `now` is in
+pub fn week_start(now: &Tm) -> Tm {
+    let days_back = ((now.tm_wday + 6) % 7) as i64;
+    let since_midnight = (now.tm_hour*60*60 + now.tm_min*60 + now.tm_sec) as i64;
+    at(now.to_timespec() - Duration::seconds(days_back*60*60*24 + since_midnight))
+}
+
+/// seconds logged on the note between `from` (or the beginning) and `to`,
+/// running timers count up to `to`
+pub fn logged(note: &ThecaItem, from: Option<&Tm>, to: &Tm) -> Result<i64, ThecaError> {
+    let entries = match note.time_log {
+        Some(ref l) => l.clone(),
+        None => return Ok(0)
+    };
+    let to = to.to_timespec();
+    let mut total = 0;
+    for e in entries.iter() {
+        let mut start = try!(parse_last_touched(&*e.start)).to_timespec();
+        let mut stop = match e.stop {
+            Some(ref s) => try!(parse_last_touched(&*s)).to_timespec(),
+            None => to
+        };
+        match from {
+            Some(f) if f.to_timespec() > start => start = f.to_timespec(),
+            _ => ()
+        };
+        if stop > to {stop = to;}
+        if stop > start {total += (stop - start).num_seconds();}
+    }
+    Ok(total)
+}
+
+/// tags on a note, words starting with # in the title or body (#123 is a
+/// link to another note, not a tag)
+pub fn tags(note: &ThecaItem) -> Vec<String> {
+    let re = Regex::new(r"(?:^|\s)#([A-Za-z][\w-]*)").unwrap();
+    let mut found: Vec<String> = vec![];
+    for text in [&note.title, &note.body].iter() {
+        for caps in re.captures_iter(&***text) {
+            let tag = caps.at(1).unwrap_or("").to_lowercase();
+            if !found.contains(&tag) {found.push(tag);}
+        }
+    }
+    found
+}
+
+/// add up the time logged on each note, and each tag, between `from` and `to`
+pub fn timesheet(
+    notes: &[ThecaItem],
+    from: Option<&Tm>,
+    to: &Tm
+) -> Result<Timesheet, ThecaError> {
+    let mut sheet = Timesheet {notes: vec![], tags: vec![], total: 0};
+    for n in notes.iter() {
+        let seconds = try!(logged(n, from, to));
+        if seconds == 0 {continue;}
+        sheet.notes.push(NoteTime {id: n.id, title: n.title.clone(), seconds: seconds});
+        sheet.total += seconds;
+        for t in tags(n).into_iter() {
+            match sheet.tags.iter().position(|s| s.tag == t) {
+                Some(i) => sheet.tags[i].seconds += seconds,
+                None => sheet.tags.push(TagTime {tag: t, seconds: seconds})
+            };
+        }
+    }
+    sheet.notes.sort_by(|a, b| b.seconds.cmp(&a.seconds));
+    sheet.tags.sort_by(|a, b| b.seconds.cmp(&a.seconds));
+    Ok(sheet)
+}
+
+/// format a number of seconds like "1h 05m"
+pub fn format_duration(seconds: i64) -> String {
+    let minutes = seconds / 60;
+    match minutes < 60 {
+        true => format!("{}m", minutes),
+        false => format!("{}h {:02}m", minutes / 60, minutes % 60)
+    }
+}
+
+/// quote a value for a CSV file if it needs it
+pub fn csv_field(value: &str) -> String {
+    match value.contains(',') || value.contains('"') || value.contains('\n') {
+        true => format!("\"{}\"", value.replace("\"", "\"\"")),
+        false => value.to_string()
+    }
+}
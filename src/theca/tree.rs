@@ -0,0 +1,93 @@
+//  _   _                    
+// | |_| |__   ___  ___ __ _ 
+// | __| '_ \ / _ \/ __/ _` |
+// | |_| | | |  __/ (_| (_| |
+//  \__|_| |_|\___|\___\__,_|
+//
+// licensed under the MIT license <http://opensource.org/licenses/MIT>
+//
+// tree.rs
+//   helpers for working with the parent/child relationships between
+//   notes (subtasks).
+
+use std::iter::{repeat};
+
+use ::{ThecaItem};
+
+/// ids of the notes whose parent is `id`
+pub fn children(notes: &[ThecaItem], id: usize) -> Vec<usize> {
+    notes.iter().filter(|n| n.parent == Some(id)).map(|n| n.id).collect()
+}
+
+/// ids of every note below `id`, parents always come before their children
+pub fn descendants(notes: &[ThecaItem], id: usize) -> Vec<usize> {
+    let mut found: Vec<usize> = vec![];
+    let mut queue = children(notes, id);
+    while !queue.is_empty() {
+        let next = queue.remove(0);
+        // a hand edited profile could contain a loop, don't follow it forever
+        if next == id || found.contains(&next) {continue;}
+        found.push(next);
+        queue.push_all(&children(notes, next));
+    }
+    found
+}
+
+/// would making `parent` the parent of `id` create a loop?
+pub fn would_cycle(notes: &[ThecaItem], id: usize, parent: usize) -> bool {
+    id == parent || descendants(notes, id).contains(&parent)
+}
+
+/// reorder notes so children follow their parent (keeping the existing
+/// order between siblings) and indent their titles by depth, if `collapse`
+/// is set only top level notes are kept with a count of what is below them
+pub fn tree_order(notes: &Vec<ThecaItem>, collapse: bool) -> Vec<ThecaItem> {
+    let mut ordered: Vec<ThecaItem> = vec![];
+    let mut seen: Vec<usize> = vec![];
+    // notes without a parent in this list (it may have been filtered out)
+    // are shown at the top level
+    for n in notes.iter().filter(|n| match n.parent {
+        Some(p) => !notes.iter().any(|o| o.id == p),
+        None => true
+    }) {
+        push_subtree(n, notes, 0, collapse, &mut ordered, &mut seen);
+    }
+    // anything left over is part of a loop, just print it flat
+    for n in notes.iter() {
+        if !seen.contains(&n.id) {
+            push_subtree(n, notes, 0, collapse, &mut ordered, &mut seen);
+        }
+    }
+    ordered
+}
+
+fn push_subtree(
+    note: &ThecaItem,
+    notes: &Vec<ThecaItem>,
+    depth: usize,
+    collapse: bool,
+    ordered: &mut Vec<ThecaItem>,
+    seen: &mut Vec<usize>
+) {
+    if seen.contains(&note.id) {return;}
+    seen.push(note.id);
+    let indent: String = repeat(' ').take(depth*2).collect();
+    let mut display = note.clone();
+    display.title = format!("{}{}", indent, note.title);
+    match collapse {
+        true => {
+            let below = descendants(notes, note.id);
+            for d in below.iter() {seen.push(*d);}
+            if below.len() > 0 {
+                display.title = format!("{} [+{}]", display.title, below.len());
+            }
+            ordered.push(display);
+        },
+        false => {
+            ordered.push(display);
+            for c in notes.iter().filter(|c| c.parent == Some(note.id)) {
+                push_subtree(c, notes, depth+1, collapse, ordered, seen);
+            }
+        }
+    }
+}
@@ -0,0 +1,34 @@
+//  _   _                    
+// | |_| |__   ___  ___ __ _ 
+// | __| '_ \ / _ \/ __/ _` |
+// | |_| | | |  __/ (_| (_| |
+//  \__|_| |_|\___|\___\__,_|
+//
+// licensed under the MIT license <http://opensource.org/licenses/MIT>
+//
+// undo.rs
+//   every save keeps the profile as it was in `<profile>.undo`, `theca undo`
+//   puts it back (and keeps the undone state, so a second undo is a redo).
+
+use std::fs::{copy};
+use std::path::{Path, PathBuf};
+
+use errors::{ThecaError};
+use utils::{find_profile_folder};
+
+/// file the previous state of a profile is kept in
+pub fn undo_path(
+    profile_folder: &String,
+    profile_name: &String
+) -> Result<PathBuf, ThecaError> {
+    let mut undo = try!(find_profile_folder(profile_folder));
+    undo.push(&(profile_name.to_string() + ".undo"));
+    Ok(undo)
+}
+
+/// copy the profile at `profile_path` over the last undo state, the bytes
+/// are kept as they are so an encrypted profile stays encrypted
+pub fn keep_for_undo(profile_path: &Path, undo: &Path) -> Result<(), ThecaError> {
+    try!(copy(profile_path, undo));
+    Ok(())
+}
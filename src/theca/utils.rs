@@ -20,19 +20,23 @@ use std::cmp::{Ordering};
 use std::iter::{repeat};
 
 // time imports
-use time::{get_time};
+use time::{get_time, now};
 use time::{strftime, strptime, at, Tm};
 
 // term imports
 use term::{stdout};
 use term::attr::Attr::{Bold};
+use term::color::{RED};
 
 // json imports
-use rustc_serialize::json::{as_pretty_json, decode};
+use rustc_serialize::json::{Json, as_pretty_json, decode};
 
 // tempdir imports
 use tempdir::{TempDir};
 
+// rand imports
+use rand::{thread_rng, Rng};
+
 // old imports
 use std::old_io::stdio::{stdin};
 use std::old_io::{IoError};
@@ -41,6 +45,11 @@ use std::old_io::{IoError};
 use ::{DATEFMT, DATEFMT_SHORT, ThecaItem, ThecaProfile};
 use errors::{ThecaError, GenericError};
 use lineformat::{LineFormat};
+use tree::{tree_order};
+use order::{sort_manual, pinned_first};
+use priority::{cmp_priority, parse_priority_filter, priority_matches};
+use highlight::{Highlight, note_json};
+use sort::{parse_sort, sort_by_keys};
 
 pub use libc::{
     STDIN_FILENO,
@@ -53,6 +62,7 @@ pub mod c {
     extern crate libc;
     pub use self::libc::{
         c_int,
+        c_short,
         c_uint,
         c_ushort,
         c_ulong,
@@ -99,6 +109,14 @@ pub mod c {
     pub const ECHO:c_uint = 8;
     pub const TCSANOW: c_int = 0;
     #[cfg(any(target_os = "linux", target_os = "android"))]
+    pub const ICANON: c_uint = 2;
+    #[cfg(any(target_os = "linux", target_os = "android"))]
+    pub const ISIG: c_uint = 1;
+    #[cfg(any(target_os = "macos", target_os = "ios"))]
+    pub const ICANON: c_uint = 0x100;
+    #[cfg(any(target_os = "macos", target_os = "ios"))]
+    pub const ISIG: c_uint = 0x80;
+    #[cfg(any(target_os = "linux", target_os = "android"))]
     static TIOCGWINSZ: c_ulong = 0x5413;
     #[cfg(any(target_os = "macos", target_os = "ios"))]
     static TIOCGWINSZ: c_ulong = 0x40087468;
@@ -114,6 +132,19 @@ pub mod c {
         let isit = unsafe {isatty(fd as i32)};
         isit != 0
     }
+    #[repr(C)]
+    pub struct Pollfd {
+        pub fd: c_int,
+        pub events: c_short,
+        pub revents: c_short
+    }
+    pub const POLLIN: c_short = 1;
+    // is there input waiting on fd (within timeout milliseconds)?
+    pub fn pending(fd: c_int, timeout: c_int) -> bool {
+        extern {fn poll(fds: *mut Pollfd, nfds: c_ulong, timeout: c_int) -> c_int;}
+        let mut p = Pollfd {fd: fd, events: POLLIN, revents: 0};
+        unsafe {poll(&mut p as *mut Pollfd, 1, timeout) > 0}
+    }
 }
 
 fn set_term_echo(echo: bool) -> Result<(), ThecaError> {
@@ -127,6 +158,19 @@ fn set_term_echo(echo: bool) -> Result<(), ThecaError> {
     Ok(())
 }
 
+// read keys one at a time without echoing them (or turning ctrl-c into
+// a signal, so the terminal can always be put back)
+pub fn set_term_raw(raw: bool) -> Result<(), ThecaError> {
+    let mut t = c::Termios::new();
+    try_errno!(c::tcgetattr(STDIN_FILENO, &mut t));
+    match raw {
+        true => t.c_lflag &= !(c::ECHO | c::ICANON | c::ISIG),
+        false => t.c_lflag |= c::ECHO | c::ICANON | c::ISIG
+    };
+    try_errno!(c::tcsetattr(STDIN_FILENO, c::TCSANOW, &mut t));
+    Ok(())
+}
+
 // unsafety wrapper
 pub fn termsize() -> usize {
     let ws = unsafe {c::dimensions()};
@@ -174,6 +218,24 @@ pub fn drop_to_editor(contents: &String) -> Result<String, ThecaError> {
     }
 }
 
+// random (version 4) uuid, used to identify a note across profiles
+pub fn new_uuid() -> String {
+    let mut bytes = [0u8; 16];
+    thread_rng().fill_bytes(&mut bytes);
+    // set the version (4) and variant (RFC 4122) bits
+    bytes[6] = (bytes[6] & 0x0f) | 0x40;
+    bytes[8] = (bytes[8] & 0x3f) | 0x80;
+    let hex: Vec<String> = bytes.iter().map(|b| format!("{:02x}", b)).collect();
+    format!(
+        "{}-{}-{}-{}-{}",
+        hex[0..4].concat(),
+        hex[4..6].concat(),
+        hex[6..8].concat(),
+        hex[8..10].concat(),
+        hex[10..16].concat()
+    )
+}
+
 pub fn get_password() -> Result<String, ThecaError> {
     // should really turn off terminal echo...
     print!("Key: ");
@@ -233,6 +295,43 @@ pub fn pretty_line(
     Ok(())
 }
 
+/// print text with the byte ranges in `spans` in bold red (on a tty)
+pub fn print_spans(
+    text: &str,
+    spans: &Vec<(usize, usize)>,
+    tty: bool
+) -> Result<(), ThecaError> {
+    let mut t = match stdout() {
+        Some(t) => t,
+        None => specific_fail_str!("could not retrieve standard output.")
+    };
+    let mut last = 0;
+    for &(start, end) in spans.iter() {
+        if start < last || end > text.len() {continue;}
+        try!(write!(t, "{}", &text[last..start]));
+        if tty {
+            try!(t.attr(Bold));
+            try!(t.fg(RED));
+        }
+        try!(write!(t, "{}", &text[start..end]));
+        if tty {try!(t.reset());}
+        last = end;
+    }
+    try!(write!(t, "{}", &text[last..]));
+    Ok(())
+}
+
+/// print a line with the byte ranges in `spans` highlighted
+pub fn print_highlighted(
+    text: &str,
+    spans: &Vec<(usize, usize)>,
+    tty: bool
+) -> Result<(), ThecaError> {
+    try!(print_spans(text, spans, tty));
+    print!("\n");
+    Ok(())
+}
+
 pub fn format_field(value: &String, width: usize, truncate: bool) -> String {
     if value.len() > width && width > 3 && truncate {
         format!("{: <1$.1$}...", value, width-3)
@@ -259,10 +358,30 @@ fn print_header(line_format: &LineFormat) -> Result<(), ThecaError> {
             false
         )+&*column_seperator
     };
+    let priority = match line_format.priority_width == 0 {
+        true => "".to_string(),
+        false => format_field(
+            &"pri".to_string(),
+            line_format.priority_width,
+            false
+        )+&*column_seperator
+    };
+    let mut fields = String::new();
+    for &(ref name, width) in line_format.field_widths.iter() {
+        fields.push_str(&*(format_field(name, width, false)+&*column_seperator));
+    }
+    let progress = match line_format.progress_width == 0 {
+        true => "".to_string(),
+        false => format_field(
+            &"[x]".to_string(),
+            line_format.progress_width,
+            false
+        )+&*column_seperator
+    };
     if tty {try!(t.attr(Bold));}
     try!(write!(
                 t, 
-                "{1}{0}{2}{0}{3}{4}\n{5}\n",
+                "{1}{0}{8}{2}{0}{6}{3}{7}{4}\n{5}\n",
                 column_seperator,
                 format_field(&"id".to_string(), line_format.id_width, false),
                 format_field(
@@ -276,7 +395,10 @@ fn print_header(line_format: &LineFormat) -> Result<(), ThecaError> {
                     line_format.touched_width,
                     false
                 ),
-                header_seperator
+                header_seperator,
+                progress,
+                fields,
+                priority
             ));
     if tty {try!(t.reset());}
     Ok(())
@@ -292,7 +414,45 @@ pub fn sorted_print(
     search_body: bool,
     no_status: bool,
     started_status: bool,
-    urgent_status: bool
+    urgent_status: bool,
+    relative: bool,
+    tree: bool,
+    collapse: bool,
+    manual_order: Option<&Vec<usize>>,
+    columns: &Vec<String>,
+    priority: &String,
+    prioritysort: bool,
+    sort: &String,
+    highlight: Option<&Highlight>
+) -> Result<(), ThecaError> {
+    try!(sort_notes(
+        notes,
+        limit,
+        datesort,
+        reverse,
+        no_status,
+        started_status,
+        urgent_status,
+        tree && !json,
+        collapse,
+        manual_order,
+        priority,
+        prioritysort,
+        sort
+    ));
+    print_notes(notes, condensed, json, search_body, relative, columns, highlight)
+}
+
+/// filter, sort and limit notes the way they are listed, without printing
+/// them
+/// keep only the notes with the status (-n, -s or -u) and priority (-P)
+/// asked for
+pub fn filter_status_priority(
+    notes: &mut Vec<ThecaItem>,
+    no_status: bool,
+    started_status: bool,
+    urgent_status: bool,
+    priority: &String
 ) -> Result<(), ThecaError> {
     if no_status {
         notes.retain(|n| n.status == "");
@@ -301,37 +461,98 @@ pub fn sorted_print(
     } else if urgent_status {
         notes.retain(|n| n.status == "Urgent");
     }
-    let limit = match limit != 0 && notes.len() >= limit {
-        true => limit,
-        false => notes.len()
+    if !priority.is_empty() {
+        let filter = try!(parse_priority_filter(&*priority));
+        notes.retain(|n| priority_matches(n, &filter));
+    }
+    Ok(())
+}
+
+pub fn sort_notes(
+    notes: &mut Vec<ThecaItem>,
+    limit: usize,
+    datesort: bool,
+    reverse: bool,
+    no_status: bool,
+    started_status: bool,
+    urgent_status: bool,
+    tree: bool,
+    collapse: bool,
+    manual_order: Option<&Vec<usize>>,
+    priority: &String,
+    prioritysort: bool,
+    sort: &String
+) -> Result<(), ThecaError> {
+    let keys = try!(parse_sort(&*sort));
+    try!(filter_status_priority(notes, no_status, started_status, urgent_status, priority));
+    match manual_order {
+        Some(order) => sort_manual(notes, order),
+        None => if !keys.is_empty() {
+            sort_by_keys(notes, &keys);
+        } else if prioritysort {
+            notes.sort_by(|a, b| cmp_priority(a, b));
+        } else if datesort {
+            notes.sort_by(|a, b| match cmp_last_touched(
+                &*a.last_touched,
+                &*b.last_touched
+            ) {
+                Ok(o) => o,
+                Err(_) => a.last_touched.cmp(&b.last_touched)
+            });
+        }
     };
-    if datesort {
-        notes.sort_by(|a, b| match cmp_last_touched(
-            &*a.last_touched,
-            &*b.last_touched
-        ) {
-            Ok(o) => o,
-            Err(_) => a.last_touched.cmp(&b.last_touched)
-        });
+    if reverse {notes.reverse();}
+    // pinned notes stay at the top whichever way the list is sorted
+    *notes = pinned_first(notes);
+    // subtasks are listed (indented) under their parent
+    if tree {
+        *notes = tree_order(notes, collapse);
+    }
+    if limit != 0 && notes.len() > limit {
+        notes.truncate(limit);
     }
+    Ok(())
+}
 
+/// print notes as they are, as a table (or condensed) or as JSON
+pub fn print_notes(
+    notes: &Vec<ThecaItem>,
+    condensed: bool,
+    json: bool,
+    search_body: bool,
+    relative: bool,
+    columns: &Vec<String>,
+    highlight: Option<&Highlight>
+) -> Result<(), ThecaError> {
     match json {
         false => {
-            if reverse {notes.reverse();}
-            let line_format = try!(LineFormat::new(&notes[0..limit].to_vec(), condensed, search_body));
-            if !condensed && !json {
+            let line_format = try!(LineFormat::new(
+                notes,
+                condensed,
+                search_body,
+                relative,
+                columns
+            ));
+            if !condensed {
                 try!(print_header(&line_format));
             }
-            for n in notes[0..limit].iter() {
-                try!(n.print(&line_format, search_body));
+            for n in notes.iter() {
+                try!(n.print(&line_format, search_body, relative, highlight));
             }
         },
-        true => {
-            if reverse { notes.reverse(); }
-            println!("{}", as_pretty_json(&notes[0..limit].to_vec()))
+        true => match highlight {
+            // search results carry the offsets of their matches
+            Some(h) => {
+                let mut results = vec![];
+                for n in notes.iter() {
+                    results.push(try!(note_json(n, &h.matcher.matches(n))));
+                }
+                println!("{}", as_pretty_json(&Json::Array(results)));
+            },
+            None => println!("{}", as_pretty_json(notes))
         }
     };
-    
+
     Ok(())
 }
 
@@ -357,6 +578,49 @@ pub fn localize_last_touched_string(lt: &str) -> Result<String, ThecaError> {
     Ok(try!(strftime(DATEFMT_SHORT, &t)))
 }
 
+pub fn relative_last_touched_string(lt: &str) -> Result<String, ThecaError> {
+    let t = try!(parse_last_touched(lt));
+    let offset = (now().to_timespec() - t.to_timespec()).num_seconds();
+    // dates in the future (due dates, or clock skew from a synced profile)
+    // are described the other way around
+    let past = offset >= 0;
+    let seconds = offset.abs();
+    let minutes = seconds / 60;
+    let hours = minutes / 60;
+    let days = hours / 24;
+    let amount = if seconds < 60 {
+        return Ok("just now".to_string())
+    } else if hours < 1 {
+        format!("{}m", minutes)
+    } else if days < 1 {
+        format!("{}h", hours)
+    } else if days < 2 {
+        return Ok(match past {
+            true => "yesterday".to_string(),
+            false => "tomorrow".to_string()
+        })
+    } else if days < 7 {
+        format!("{}d", days)
+    } else if days < 30 {
+        format!("{}w", days / 7)
+    } else if days < 365 {
+        format!("{}mo", days / 30)
+    } else {
+        format!("{}y", days / 365)
+    };
+    Ok(match past {
+        true => format!("{} ago", amount),
+        false => format!("in {}", amount)
+    })
+}
+
+pub fn format_last_touched(lt: &str, relative: bool) -> Result<String, ThecaError> {
+    match relative {
+        true => relative_last_touched_string(lt),
+        false => localize_last_touched_string(lt)
+    }
+}
+
 pub fn cmp_last_touched(a: &str, b: &str) -> Result<Ordering, ThecaError> {
     let a_tm = try!(parse_last_touched(a));
     let b_tm = try!(parse_last_touched(b));
@@ -403,20 +667,31 @@ pub fn path_to_profile_name(profile_path: &PathBuf) -> Result<String, ThecaError
     Ok(just_f.to_str().unwrap().to_string())
 }
 
-pub fn profiles_in_folder(folder: &Path) -> Result<(), ThecaError> {
+/// names of the profiles in a folder (sorted) and whether they are encrypted
+pub fn profile_names(folder: &Path) -> Result<Vec<(String, bool)>, ThecaError> {
+    let mut names = vec![];
     if folder.is_dir() {
-        println!("# profiles in {}", folder.display());
         for file in try!(read_dir(folder)) {
             let file = try!(file);
             let is_prof = validate_profile_from_path(&file.path());
             if is_prof.0 {
-                let mut msg = try!(path_to_profile_name(&file.path()));
-                if is_prof.1 {
-                    msg = format!("{} [encrypted]", msg);
-                }
-                println!("    {}", msg);
+                names.push((try!(path_to_profile_name(&file.path())), is_prof.1));
             }
         }
     }
+    names.sort();
+    Ok(names)
+}
+
+pub fn profiles_in_folder(folder: &Path) -> Result<(), ThecaError> {
+    if folder.is_dir() {
+        println!("# profiles in {}", folder.display());
+        for (name, encrypted) in try!(profile_names(folder)).into_iter() {
+            match encrypted {
+                true => println!("    {} [encrypted]", name),
+                false => println!("    {}", name)
+            };
+        }
+    }
     Ok(())
 }
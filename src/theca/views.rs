@@ -0,0 +1,134 @@
+//  _   _                    
+// | |_| |__   ___  ___ __ _ 
+// | __| '_ \ / _ \/ __/ _` |
+// | |_| | | |  __/ (_| (_| |
+//  \__|_| |_|\___|\___\__,_|
+//
+// licensed under the MIT license <http://opensource.org/licenses/MIT>
+//
+// views.rs
+//   named views, a list or search saved (in the profile) as the arguments
+//   it was made from and run again with `theca view <name>`.
+
+use regex::{Regex};
+
+use ::{Args};
+use errors::{ThecaError, GenericError};
+
+/// a saved view
+#[derive(RustcDecodable, RustcEncodable, Clone, PartialEq, Debug)]
+pub struct View {
+    pub name: String,
+    pub args: Vec<String>
+}
+
+/// view names end up in shell completion, so keep them simple
+pub fn check_name(name: &str) -> Result<(), ThecaError> {
+    let re = Regex::new(r"^[\w-]+$").unwrap();
+    if !re.is_match(name) || name == "save" || name == "remove" {
+        specific_fail!(format!(
+            "'{}' can't be used as a view name, use letters, numbers, - and _ \
+             (but not save or remove).",
+            name
+        ));
+    }
+    Ok(())
+}
+
+/// add a view, replacing any with the same name, returns whether one was
+/// replaced
+pub fn set_view(views: &mut Vec<View>, name: &str, args: &Vec<String>) -> bool {
+    let view = View {name: name.to_string(), args: args.clone()};
+    match views.iter().position(|v| &*v.name == name) {
+        Some(i) => {
+            views[i] = view;
+            true
+        },
+        None => {
+            views.push(view);
+            views.sort_by(|a, b| a.name.cmp(&b.name));
+            false
+        }
+    }
+}
+
+/// the saved arguments as a full command line to parse again
+pub fn view_argv(args: &Vec<String>) -> Vec<String> {
+    let mut argv = vec!["theca".to_string()];
+    argv.push_all(&args[..]);
+    argv
+}
+
+/// `theca view save <name> -d -l 10` has to be read as
+/// `theca view save <name> -- -d -l 10` so the flags are saved rather than
+/// used straight away
+pub fn quote_view_args(mut argv: Vec<String>) -> Vec<String> {
+    let at = argv.windows(2).position(|w| &*w[0] == "view" && &*w[1] == "save");
+    match at {
+        Some(i) if argv.len() > i + 3 && &*argv[i+3] != "--" => {
+            argv.insert(i + 3, "--".to_string());
+        },
+        _ => ()
+    };
+    argv
+}
+
+/// views can only list or search notes, in the profile they belong to
+pub fn check_view(view: &Args) -> Result<(), ThecaError> {
+    if [
+        view.cmd_add,
+        view.cmd_archive,
+        view.cmd_attach,
+        view.cmd_attachments,
+        view.cmd_check,
+        view.cmd_clear,
+        view.cmd_del,
+        view.cmd_decrypt_profile,
+        view.cmd_edit,
+        view.cmd_encrypt_profile,
+        view.cmd_extract,
+        view.cmd_graph,
+        view.cmd_import,
+        view.cmd_info,
+        view.cmd_journal,
+        view.cmd_links,
+        view.cmd_list_profiles,
+        view.cmd_move,
+        view.cmd_new_profile,
+        view.cmd_pick,
+        view.cmd_pin,
+        view.cmd_recurring,
+        view.cmd_replace,
+        view.cmd_start,
+        view.cmd_stop,
+        view.cmd_templates,
+        view.cmd_timesheet,
+        view.cmd_today,
+        view.cmd_transfer,
+        view.cmd_unarchive,
+        view.cmd_uncheck,
+        view.cmd_undo,
+        view.cmd_unpin,
+        view.cmd_verify,
+        view.cmd_view,
+        view.cmd__,
+        !view.arg_id.is_empty()
+    ].iter().any(|c| c == &true) {
+        specific_fail_str!("a view can only list or search notes.");
+    }
+    if !view.flag_profile.is_empty() || !view.flag_profile_folder.is_empty() ||
+       !view.flag_key.is_empty() || view.flag_encrypted || view.flag_all_profiles {
+        specific_fail_str!("views belong to a profile, they can't choose one.");
+    }
+    Ok(())
+}
+
+/// the arguments of a view, run in the profile it was loaded from
+pub fn view_args(mut view: Args, current: &Args) -> Args {
+    view.flag_profile = current.flag_profile.clone();
+    view.flag_profile_folder = current.flag_profile_folder.clone();
+    view.flag_key = current.flag_key.clone();
+    view.flag_encrypted = current.flag_encrypted;
+    view.flag_yes = current.flag_yes;
+    view
+}
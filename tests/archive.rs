@@ -0,0 +1,47 @@
+extern crate theca;
+extern crate time;
+
+mod common;
+
+use theca::{ThecaItem};
+use theca::archive::{shown, visible, stale_done};
+use theca::utils::{parse_last_touched};
+
+fn note(id: usize, status: &str, archived: bool) -> ThecaItem {
+    ThecaItem {
+        status: status.to_string(),
+        archived: match archived {
+            true => Some("2015-01-23 19:43:24 -0800".to_string()),
+            false => None
+        },
+        ..common::note(id, &format!("note {}", id), "")
+    }
+}
+
+#[test]
+fn test_shown() {
+    let (active, archived) = (note(1, "", false), note(2, "", true));
+    assert!(shown(&active, false, false));
+    assert!(!shown(&archived, false, false));
+    assert!(!shown(&active, true, false));
+    assert!(shown(&archived, true, false));
+    assert!(shown(&active, false, true));
+    assert!(shown(&archived, false, true));
+}
+
+#[test]
+fn test_visible() {
+    let notes = vec![note(1, "", false), note(2, "Done", true), note(3, "", false)];
+    let ids = |v: Vec<ThecaItem>| v.iter().map(|n| n.id).collect::<Vec<usize>>();
+    assert_eq!(ids(visible(&notes, false, false)), vec![1, 3]);
+    assert_eq!(ids(visible(&notes, true, false)), vec![2]);
+    assert_eq!(ids(visible(&notes, false, true)), vec![1, 2, 3]);
+}
+
+#[test]
+fn test_stale_done() {
+    let notes = vec![note(1, "Done", false), note(2, "Done", true), note(3, "Started", false)];
+    let later = parse_last_touched("2015-02-01 19:43:24 -0800").ok().unwrap();
+    assert_eq!(stale_done(&notes, 7, &later).ok().unwrap(), vec![1]);
+    assert!(stale_done(&notes, 30, &later).ok().unwrap().is_empty());
+}
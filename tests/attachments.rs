@@ -0,0 +1,71 @@
+extern crate theca;
+extern crate tempdir;
+
+mod common;
+
+use tempdir::{TempDir};
+use theca::{ThecaItem};
+use theca::attachments::{Attachment, store_blob, read_blob, orphaned_blobs,
+                         missing_blobs, restore_blobs};
+use theca::crypt::{password_to_key};
+
+fn note(id: usize, attachments: Option<Vec<Attachment>>) -> ThecaItem {
+    ThecaItem {
+        attachments: attachments,
+        ..common::note(id, &format!("note {}", id), "")
+    }
+}
+
+#[test]
+fn test_store_and_read_blob() {
+    let tmpdir = TempDir::new("theca").ok().unwrap();
+    let store = tmpdir.path().join("default.attachments");
+    let contents = b"some file contents";
+
+    let hash = store_blob(&store, contents, None).ok().unwrap();
+    assert_eq!(store_blob(&store, contents, None).ok().unwrap(), hash);
+    assert_eq!(read_blob(&store, &hash, None).ok().unwrap(), contents.to_vec());
+
+    let key = password_to_key("a key");
+    let enc_hash = store_blob(&store, contents, Some(&*key)).ok().unwrap();
+    assert!(enc_hash != hash);
+    assert_eq!(read_blob(&store, &enc_hash, Some(&*key)).ok().unwrap(), contents.to_vec());
+}
+
+#[test]
+fn test_orphaned_and_missing_blobs() {
+    let tmpdir = TempDir::new("theca").ok().unwrap();
+    let store = tmpdir.path().join("default.attachments");
+    let used = store_blob(&store, b"used", None).ok().unwrap();
+    let unused = store_blob(&store, b"unused", None).ok().unwrap();
+    let notes = vec![note(1, Some(vec![
+        Attachment {name: "used.txt".to_string(), hash: used, size: 4},
+        Attachment {name: "gone.txt".to_string(), hash: "abc".to_string(), size: 4}
+    ]))];
+
+    let orphans = orphaned_blobs(&store, &notes).ok().unwrap();
+    assert_eq!(orphans.len(), 1);
+    assert_eq!(orphans[0], store.join(&unused));
+    assert_eq!(missing_blobs(&store, &notes), vec![(1, "gone.txt".to_string())]);
+}
+
+#[test]
+fn test_restore_blobs() {
+    let tmpdir = TempDir::new("theca").ok().unwrap();
+    let store = tmpdir.path().join("default.attachments");
+    let plain = store_blob(&store, b"contents", None).ok().unwrap();
+    let mut notes = vec![note(1, Some(vec![
+        Attachment {name: "a.txt".to_string(), hash: plain.clone(), size: 8}
+    ]))];
+
+    let key = password_to_key("a key");
+    let replaced = restore_blobs(&store, &mut notes, None, Some(&*key)).ok().unwrap();
+    assert_eq!(replaced, vec![plain.clone()]);
+    let enc_hash = notes[0].attachments.as_ref().unwrap()[0].hash.clone();
+    assert!(enc_hash != plain);
+    assert_eq!(read_blob(&store, &enc_hash, Some(&*key)).ok().unwrap(), b"contents".to_vec());
+
+    let replaced = restore_blobs(&store, &mut notes, Some(&*key), None).ok().unwrap();
+    assert_eq!(replaced, vec![enc_hash]);
+    assert_eq!(notes[0].attachments.as_ref().unwrap()[0].hash, plain);
+}
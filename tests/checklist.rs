@@ -0,0 +1,32 @@
+extern crate theca;
+
+use theca::checklist::{items, progress, progress_string, set_item};
+
+static BODY: &'static str = "todo\n- [ ] one\n- [x] two\n  * [X] three\nnot [ ] an item\n";
+
+#[test]
+fn test_items() {
+    assert_eq!(items(BODY), vec![
+        (false, "one".to_string()),
+        (true, "two".to_string()),
+        (true, "three".to_string())
+    ]);
+    assert_eq!(progress(BODY), Some((2, 3)));
+    assert_eq!(progress("no checklist here"), None);
+    assert_eq!(progress_string(BODY), "2/3".to_string());
+    assert_eq!(progress_string(""), "".to_string());
+}
+
+#[test]
+fn test_set_item() {
+    assert_eq!(
+        set_item(BODY, 1, true).ok().unwrap(),
+        "todo\n- [x] one\n- [x] two\n  * [X] three\nnot [ ] an item\n".to_string()
+    );
+    assert_eq!(
+        set_item(BODY, 3, false).ok().unwrap(),
+        "todo\n- [ ] one\n- [x] two\n  * [ ] three\nnot [ ] an item\n".to_string()
+    );
+    assert!(set_item(BODY, 4, true).is_err());
+    assert!(set_item(BODY, 0, true).is_err());
+}
@@ -0,0 +1,13 @@
+use theca::{ThecaItem};
+
+/// a plain note, tests set anything else they need with struct update
+/// syntax (`ThecaItem {parent: Some(1), ..note(2, "a title", "")}`)
+pub fn note(id: usize, title: &str, body: &str) -> ThecaItem {
+    ThecaItem {
+        id: id,
+        title: title.to_string(),
+        body: body.to_string(),
+        last_touched: "2015-01-22 19:43:24 -0800".to_string(),
+        ..Default::default()
+    }
+}
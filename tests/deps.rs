@@ -0,0 +1,45 @@
+extern crate theca;
+
+mod common;
+
+use theca::{ThecaItem};
+use theca::deps::{open_blockers, would_block_cycle, ready, to_dot};
+
+fn note(id: usize, status: &str, blocked_by: Option<Vec<usize>>) -> ThecaItem {
+    ThecaItem {
+        status: status.to_string(),
+        blocked_by: blocked_by,
+        ..common::note(id, &format!("note {}", id), "")
+    }
+}
+
+#[test]
+fn test_open_blockers() {
+    let notes = vec![note(1, "Done", None), note(2, "", None), note(3, "", Some(vec![1, 2, 9]))];
+    assert_eq!(open_blockers(&notes, 3), vec![2]);
+    assert!(open_blockers(&notes, 2).is_empty());
+}
+
+#[test]
+fn test_would_block_cycle() {
+    let notes = vec![note(1, "", None), note(2, "", Some(vec![1])), note(3, "", Some(vec![2]))];
+    assert!(would_block_cycle(&notes, 1, 1));
+    assert!(would_block_cycle(&notes, 1, 3));
+    assert!(!would_block_cycle(&notes, 3, 1));
+}
+
+#[test]
+fn test_ready() {
+    let notes = vec![note(1, "Done", None), note(2, "", Some(vec![1])), note(3, "", Some(vec![2]))];
+    let ids: Vec<usize> = ready(&notes).iter().map(|n| n.id).collect();
+    assert_eq!(ids, vec![2]);
+}
+
+#[test]
+fn test_to_dot() {
+    let notes = vec![note(1, "", None), note(2, "", Some(vec![1])), note(3, "", None)];
+    assert_eq!(
+        to_dot(&notes),
+        "digraph theca {\n    1 [label=\"1: note 1\"];\n    2 [label=\"2: note 2\"];\n    1 -> 2;\n}".to_string()
+    );
+}
@@ -0,0 +1,97 @@
+extern crate theca;
+
+mod common;
+
+use std::cmp::{Ordering};
+
+use theca::{ThecaItem};
+use theca::fields::{Field, infer_kind, normalize, parse_assignment, compare,
+                    parse_columns};
+use theca::query::{filter_where, parse_where};
+
+fn note(id: usize, fields: Vec<(&str, &str, &str)>) -> ThecaItem {
+    ThecaItem {
+        fields: Some(fields.iter().map(|&(n, k, v)| Field {
+            name: n.to_string(),
+            kind: k.to_string(),
+            value: v.to_string()
+        }).collect()),
+        ..common::note(id, &format!("note {}", id), "")
+    }
+}
+
+fn filtered(notes: &Vec<ThecaItem>, conditions: &str) -> Vec<usize> {
+    filter_where(notes.clone(), conditions).ok().unwrap().iter().map(|n| n.id).collect()
+}
+
+#[test]
+fn test_infer_kind() {
+    assert_eq!(infer_kind("true"), "bool");
+    assert_eq!(infer_kind("42"), "int");
+    assert_eq!(infer_kind("2015-03-01"), "date");
+    assert_eq!(infer_kind("2h"), "duration");
+    assert_eq!(infer_kind("acme"), "string");
+}
+
+#[test]
+fn test_normalize() {
+    assert_eq!(normalize("bool", "yes").ok().unwrap(), "true".to_string());
+    assert_eq!(normalize("int", "007").ok().unwrap(), "7".to_string());
+    assert!(normalize("int", "seven").is_err());
+    assert!(normalize("date", "2015-13-45").is_err());
+    assert!(normalize("colour", "red").is_err());
+}
+
+#[test]
+fn test_parse_assignment() {
+    let (name, field) = parse_assignment("estimate=2h").ok().unwrap();
+    assert_eq!(name, "estimate".to_string());
+    let field = field.unwrap();
+    assert_eq!(field.kind, "duration".to_string());
+    assert_eq!(field.value, "2h".to_string());
+
+    let (_, field) = parse_assignment("ticket:string=1234").ok().unwrap();
+    assert_eq!(field.unwrap().kind, "string".to_string());
+
+    let (name, field) = parse_assignment("estimate=").ok().unwrap();
+    assert_eq!(name, "estimate".to_string());
+    assert!(field.is_none());
+
+    assert!(parse_assignment("no value").is_err());
+    assert!(parse_assignment("count:int=lots").is_err());
+}
+
+#[test]
+fn test_filter_notes() {
+    let notes = vec![
+        note(1, vec![("estimate", "duration", "2h"), ("customer", "string", "acme")]),
+        note(2, vec![("estimate", "duration", "30m")]),
+        note(3, vec![("points", "int", "8"), ("due", "date", "2015-03-01")])
+    ];
+    assert_eq!(filtered(&notes, "estimate>1h"), vec![1]);
+    assert_eq!(filtered(&notes, "estimate<=90m"), vec![2]);
+    assert_eq!(filtered(&notes, "estimate"), vec![1, 2]);
+    assert_eq!(filtered(&notes, "estimate,customer=acme"), vec![1]);
+    assert_eq!(filtered(&notes, "points>=10"), Vec::<usize>::new());
+    assert_eq!(filtered(&notes, "points!=10"), vec![3]);
+    // due is the due date of the note, field.due the custom field
+    assert_eq!(filtered(&notes, "field.due<2015-04-01"), vec![3]);
+    assert_eq!(filtered(&notes, "due"), vec![3]);
+    assert!(filter_where(notes.clone(), "points>many").is_err());
+    assert!(parse_where("=1h").is_err());
+}
+
+#[test]
+fn test_compare() {
+    let n = note(1, vec![("estimate", "duration", "2h"), ("points", "int", "8")]);
+    let fields = n.fields.unwrap();
+    assert_eq!(compare(&fields[0], "90m").ok().unwrap(), Ordering::Greater);
+    assert_eq!(compare(&fields[1], "10").ok().unwrap(), Ordering::Less);
+    assert!(compare(&fields[1], "many").is_err());
+}
+
+#[test]
+fn test_parse_columns() {
+    assert_eq!(parse_columns("estimate, customer,"), vec!["estimate".to_string(), "customer".to_string()]);
+    assert!(parse_columns("").is_empty());
+}
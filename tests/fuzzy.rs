@@ -0,0 +1,140 @@
+extern crate theca;
+
+mod common;
+
+use theca::{ThecaItem};
+use theca::fuzzy::{levenshtein, fuzzy_match, rank_notes};
+use theca::pick::{Picker, Key, Action, read_key};
+use common::{note};
+
+fn chars(s: &str) -> Vec<char> {
+    s.chars().collect()
+}
+
+#[test]
+fn test_levenshtein() {
+    assert_eq!(levenshtein(&*chars("deploy"), &*chars("deploy")), 0);
+    assert_eq!(levenshtein(&*chars("deplox"), &*chars("deploy")), 1);
+    assert_eq!(levenshtein(&*chars("deply"), &*chars("deploy")), 1);
+    assert_eq!(levenshtein(&*chars("kitten"), &*chars("sitting")), 3);
+    assert_eq!(levenshtein(&*chars(""), &*chars("abc")), 3);
+}
+
+#[test]
+fn test_fuzzy_match() {
+    let (_, spans) = fuzzy_match("dplyrb", "deploy rollback").unwrap();
+    assert_eq!(spans, vec![(0, 1), (2, 4), (5, 6), (7, 8), (11, 12)]);
+    // case and unicode
+    assert!(fuzzy_match("API", "deploy the api").is_some());
+    assert_eq!(fuzzy_match("cafe", "Über Café").map(|m| m.1), Some(vec![(6, 11)]));
+    assert_eq!(fuzzy_match("über", "Über Café").map(|m| m.1), Some(vec![(0, 5)]));
+    // every word has to match
+    assert!(fuzzy_match("deploy api", "deploy the api").is_some());
+    assert!(fuzzy_match("deploy web", "deploy the api").is_none());
+    assert!(fuzzy_match("xyz", "deploy the api").is_none());
+}
+
+#[test]
+fn test_fuzzy_typos() {
+    // no subsequence, but one typo away from a word
+    assert_eq!(fuzzy_match("deplox", "the deploy").map(|m| m.1), Some(vec![(4, 10)]));
+    // short words have to match exactly
+    assert!(fuzzy_match("apx", "the api").is_none());
+    // too many typos
+    assert!(fuzzy_match("dexlox", "the deploy").is_none());
+}
+
+#[test]
+fn test_fuzzy_scores() {
+    // consecutive characters and word starts score higher
+    let tight = fuzzy_match("roll", "rollback").unwrap().0;
+    let loose = fuzzy_match("roll", "rxoxlxl").unwrap().0;
+    assert!(tight > loose);
+    let start = fuzzy_match("back", "back rollback").unwrap().0;
+    let middle = fuzzy_match("back", "rollback").unwrap().0;
+    assert!(start > middle);
+}
+
+#[test]
+fn test_rank_notes() {
+    let notes = vec![
+        note(1, "groceries", "deploy on friday"),
+        note(2, "deploy the api", ""),
+        note(3, "dinner", ""),
+        note(4, "d e p l o y", "")
+    ];
+    let ids: Vec<usize> = rank_notes(&notes, "deploy").iter().map(|m| m.0).collect();
+    assert_eq!(ids, vec![2, 4, 1]);
+    assert!(rank_notes(&notes, "nothing").is_empty());
+}
+
+#[test]
+fn test_picker() {
+    let notes = vec![
+        note(1, "deploy the api", ""),
+        note(2, "groceries", ""),
+        note(3, "dinner", "")
+    ];
+    let mut picker = Picker::new(notes, "");
+    assert_eq!(picker.matches, vec![1, 2, 3]);
+    assert_eq!(picker.key(Key::Up), Action::Continue);
+    assert_eq!(picker.selected, 0);
+    assert_eq!(picker.key(Key::Down), Action::Continue);
+    assert_eq!(picker.key(Key::Down), Action::Continue);
+    assert_eq!(picker.key(Key::Down), Action::Continue);
+    assert_eq!(picker.selected, 2);
+    assert_eq!(picker.key(Key::Enter), Action::Pick(3));
+    // typing narrows the matches down and resets the selection
+    picker.key(Key::Char('g'));
+    assert_eq!(picker.matches, vec![2]);
+    assert_eq!(picker.selected, 0);
+    picker.key(Key::Char('x'));
+    assert!(picker.matches.is_empty());
+    assert_eq!(picker.key(Key::Enter), Action::Continue);
+    picker.key(Key::Backspace);
+    picker.key(Key::Backspace);
+    assert_eq!(picker.query, "".to_string());
+    assert_eq!(picker.matches, vec![1, 2, 3]);
+    assert_eq!(picker.key(Key::Cancel), Action::Cancel);
+}
+
+#[test]
+fn test_picker_lines() {
+    let picker = Picker::new(vec![note(1, "deploy the api", ""), note(2, "groceries", "")], "dep");
+    assert_eq!(picker.lines(0), vec![
+        "> dep  (1/2)".to_string(),
+        "> 1: deploy the api".to_string()
+    ]);
+    assert_eq!(picker.lines(10)[1], "> 1: depl".to_string());
+    assert_eq!(picker.selected_row(), 1);
+}
+
+#[test]
+fn test_picker_scrolls() {
+    let notes: Vec<ThecaItem> = (1..16).map(|i| note(i, "a note", "")).collect();
+    let mut picker = Picker::new(notes, "");
+    for _ in 0..12 {picker.key(Key::Down);}
+    assert_eq!(picker.selected, 12);
+    let lines = picker.lines(0);
+    assert_eq!(lines.len(), 11);
+    assert_eq!(picker.selected_row(), 10);
+    assert_eq!(lines[picker.selected_row()], "> 13: a note".to_string());
+}
+
+#[test]
+fn test_read_key() {
+    fn key(bytes: Vec<u8>) -> Key {
+        let mut bytes = bytes.into_iter();
+        read_key(|| bytes.next())
+    }
+    assert_eq!(key(vec![b'a']), Key::Char('a'));
+    assert_eq!(key(vec![0xc3, 0xa9]), Key::Char('é'));
+    assert_eq!(key(vec![13]), Key::Enter);
+    assert_eq!(key(vec![127]), Key::Backspace);
+    assert_eq!(key(vec![27, 91, 65]), Key::Up);
+    assert_eq!(key(vec![27, 91, 66]), Key::Down);
+    assert_eq!(key(vec![14]), Key::Down);
+    assert_eq!(key(vec![27]), Key::Cancel);
+    assert_eq!(key(vec![3]), Key::Cancel);
+    assert_eq!(key(vec![1]), Key::Ignored);
+}
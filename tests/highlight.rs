@@ -0,0 +1,99 @@
+extern crate theca;
+
+mod common;
+
+use theca::highlight::{Matcher, Match, ContextLine, context_lines, visible_spans, note_json,
+                       is_word};
+use common::{note};
+
+fn found(field: &str, line: usize, start: usize, end: usize) -> Match {
+    Match {field: field.to_string(), line: line, start: start, end: end}
+}
+
+fn matcher(pattern: &str, regex: bool, fuzzy: bool, search_body: bool) -> Matcher {
+    Matcher::new(pattern, regex, false, fuzzy, search_body, false, false, false, false).unwrap().unwrap()
+}
+
+#[test]
+fn test_matcher_spans() {
+    let keyword = matcher("deploy", false, false, false);
+    assert_eq!(keyword.spans("deploy the api, deploy again"), vec![(0, 6), (16, 22)]);
+    assert_eq!(keyword.spans("Deploy"), vec![]);
+    assert_eq!(keyword.spans("nothing"), vec![]);
+    let regex = matcher(r"de\w+", true, false, false);
+    assert_eq!(regex.spans("deploy deny"), vec![(0, 6), (7, 11)]);
+    let fuzzy = matcher("dpl", false, true, false);
+    assert_eq!(fuzzy.spans("deploy"), vec![(0, 1), (2, 4)]);
+    // queries aren't highlighted
+    assert!(Matcher::new("status:urgent", false, true, false, false, false, false, false, false).unwrap().is_none());
+    assert!(Matcher::new("(", true, false, false, false, false, false, false, false).is_err());
+}
+
+#[test]
+fn test_matcher_case() {
+    let ignore = Matcher::new("café", false, false, false, false, false, true, false, false).unwrap().unwrap();
+    assert_eq!(ignore.spans("CAFÉ au lait, Café"), vec![(0, 5), (15, 20)]);
+    let regex = Matcher::new("ü+", true, false, false, false, false, true, false, false).unwrap().unwrap();
+    assert_eq!(regex.spans("ÜÜber"), vec![(0, 4)]);
+    // smart case ignores case unless there is an upper case letter
+    let smart = Matcher::new("über", false, false, false, false, false, false, true, false).unwrap().unwrap();
+    assert_eq!(smart.spans("Über über"), vec![(0, 5), (6, 11)]);
+    let smart = Matcher::new("Über", false, false, false, false, false, false, true, false).unwrap().unwrap();
+    assert_eq!(smart.spans("Über über"), vec![(0, 5)]);
+}
+
+#[test]
+fn test_matcher_words() {
+    let word = Matcher::new("api", false, false, false, false, false, false, false, true).unwrap().unwrap();
+    assert_eq!(word.spans("api rapid api_key (api) apié"), vec![(0, 3), (19, 22)]);
+    let regex = Matcher::new("de.", true, false, false, false, false, true, false, true).unwrap().unwrap();
+    assert_eq!(regex.spans("deploy Den dev"), vec![(7, 10), (11, 14)]);
+    assert!(is_word("a b", 2, 3));
+    assert!(!is_word("ab", 1, 2));
+}
+
+#[test]
+fn test_matches() {
+    let n = note(1, "deploy", "one\nrun deploy\nthree");
+    assert_eq!(matcher("deploy", false, false, false).matches(&n), vec![found("title", 1, 0, 6)]);
+    assert_eq!(matcher("deploy", false, false, true).matches(&n), vec![found("body", 2, 8, 14)]);
+    assert_eq!(
+        matcher("deploy", false, true, false).matches(&n),
+        vec![found("title", 1, 0, 6), found("body", 2, 8, 14)]
+    );
+    // --everywhere looks at both
+    let everywhere = Matcher::new("deploy", false, false, false, false, true, false, false, false).unwrap().unwrap();
+    assert_eq!(everywhere.matches(&n), vec![found("title", 1, 0, 6), found("body", 2, 8, 14)]);
+}
+
+#[test]
+fn test_context_lines() {
+    let body = "one\ntwo\nthree\nfour\nfive\nsix\nseven";
+    let spans = vec![(4, 7), (24, 27)];
+    let numbers = |groups: Vec<Vec<ContextLine>>| -> Vec<Vec<usize>> {
+        groups.iter().map(|g| g.iter().map(|l| l.number).collect()).collect()
+    };
+    assert_eq!(numbers(context_lines(body, &spans, 0)), vec![vec![2], vec![6]]);
+    assert_eq!(numbers(context_lines(body, &spans, 1)), vec![vec![1, 2, 3], vec![5, 6, 7]]);
+    assert_eq!(numbers(context_lines(body, &spans, 2)), vec![vec![1, 2, 3, 4, 5, 6, 7]]);
+    let groups = context_lines(body, &spans, 0);
+    assert_eq!(groups[0][0], ContextLine {number: 2, text: "two".to_string(), spans: vec![(0, 3)]});
+    assert!(context_lines(body, &vec![], 3).is_empty());
+}
+
+#[test]
+fn test_visible_spans() {
+    assert_eq!(visible_spans("deploy    ", "deploy", &vec![(0, 6)]), vec![(0, 6)]);
+    assert_eq!(visible_spans("depl...", "deploy the api", &vec![(0, 6), (11, 14)]), vec![(0, 4)]);
+    assert_eq!(visible_spans("ab...", "ab...cdef", &vec![(2, 5)]), vec![]);
+}
+
+#[test]
+fn test_note_json() {
+    let n = note(1, "deploy", "");
+    let json = note_json(&n, &vec![found("title", 1, 0, 6)]).unwrap();
+    assert_eq!(json.find("title").unwrap().as_string(), Some("deploy"));
+    let matches = json.find("matches").unwrap().as_array().unwrap();
+    assert_eq!(matches.len(), 1);
+    assert_eq!(matches[0].find("end").unwrap().as_u64(), Some(6));
+}
@@ -0,0 +1,94 @@
+extern crate theca;
+
+mod common;
+
+use theca::index::{Index, word_spans, stem, tokenize, snippet};
+use common::{note};
+
+#[test]
+fn test_word_spans() {
+    assert_eq!(word_spans("roll back, then deploy!"), vec![(0, 4), (5, 9), (11, 15), (16, 22)]);
+    assert_eq!(word_spans("über-café"), vec![(0, 5), (6, 11)]);
+    assert_eq!(word_spans(" -- "), vec![]);
+}
+
+#[test]
+fn test_stem() {
+    assert_eq!(stem("deploys"), "deploy".to_string());
+    assert_eq!(stem("deployed"), "deploy".to_string());
+    assert_eq!(stem("deploying"), "deploy".to_string());
+    assert_eq!(stem("queries"), "query".to_string());
+    assert_eq!(stem("classes"), "class".to_string());
+    assert_eq!(stem("class"), "class".to_string());
+    assert_eq!(stem("status"), "status".to_string());
+    assert_eq!(stem("is"), "is".to_string());
+    assert_eq!(stem("bed"), "bed".to_string());
+}
+
+#[test]
+fn test_tokenize() {
+    assert_eq!(
+        tokenize("Deployed the API, deploying again"),
+        vec!["deploy".to_string(), "the".to_string(), "api".to_string(),
+             "deploy".to_string(), "again".to_string()]
+    );
+}
+
+#[test]
+fn test_index_update() {
+    let mut notes = vec![
+        note(1, "deploy the api", ""),
+        note(2, "groceries", "milk and eggs")
+    ];
+    let mut index = Index::new();
+    assert!(index.update(&notes));
+    assert_eq!(index.documents.len(), 2);
+    // nothing has changed
+    assert!(!index.update(&notes));
+
+    notes[1].body = "bread".to_string();
+    notes.remove(0);
+    assert!(index.update(&notes));
+    assert_eq!(index.documents.len(), 1);
+    assert!(!index.terms.contains_key("deploy"));
+    assert!(!index.terms.contains_key("milk"));
+    assert!(index.terms.contains_key("bread"));
+}
+
+#[test]
+fn test_index_score() {
+    let notes = vec![
+        note(1, "groceries", "milk and eggs"),
+        note(2, "release notes", "remember to deploy after the release"),
+        note(3, "deploy the api", "the deploy needs a rollback plan"),
+        note(4, "rollback", "")
+    ];
+    let mut index = Index::new();
+    index.update(&notes);
+    let ids = |query: &str| -> Vec<usize> {
+        index.score(query).iter().map(|s| s.0).collect()
+    };
+    assert_eq!(ids("deploying"), vec![3, 2]);
+    assert_eq!(ids("rollback"), vec![4, 3]);
+    assert_eq!(ids("deploy rollback")[0], 3);
+    assert_eq!(ids("nothing"), Vec::<usize>::new());
+    assert!(Index::new().score("deploy").is_empty());
+}
+
+#[test]
+fn test_snippet() {
+    let terms = tokenize("deploy");
+    let (text, spans) = snippet("we deployed\nthe api", &terms, 72);
+    assert_eq!(text, "we deployed the api".to_string());
+    assert_eq!(spans, vec![(3, 11)]);
+
+    let body = "a long introduction that goes on for a while before it finally gets to the deploy that matters for the release next week";
+    let (text, spans) = snippet(body, &terms, 30);
+    assert!(text.starts_with("..."));
+    assert!(text.ends_with("..."));
+    assert_eq!(&text[spans[0].0..spans[0].1], "deploy");
+
+    let (text, spans) = snippet("", &terms, 30);
+    assert_eq!(text, "".to_string());
+    assert!(spans.is_empty());
+}
@@ -0,0 +1,53 @@
+extern crate theca;
+extern crate time;
+
+mod common;
+
+use time::{strptime};
+
+use theca::journal::{is_day, parse_day, append_entry, days_between};
+use common::{note};
+
+#[test]
+fn test_is_day() {
+    assert!(is_day("2015-03-01"));
+    assert!(!is_day("2015-3-1"));
+    assert!(!is_day("groceries"));
+}
+
+#[test]
+fn test_parse_day() {
+    let now = strptime("2015-03-10 12:00:00", "%Y-%m-%d %H:%M:%S").unwrap();
+    assert_eq!(parse_day("2015-02-01", &now).unwrap(), "2015-02-01".to_string());
+    assert_eq!(parse_day("today", &now).unwrap(), "2015-03-10".to_string());
+    assert_eq!(parse_day("yesterday", &now).unwrap(), "2015-03-09".to_string());
+    assert_eq!(parse_day("1w", &now).unwrap(), "2015-03-03".to_string());
+    assert_eq!(parse_day("1w ago", &now).unwrap(), "2015-03-03".to_string());
+    assert!(parse_day("last tuesday", &now).is_err());
+}
+
+#[test]
+fn test_append_entry() {
+    assert_eq!(append_entry("", "09:15", "coffee\n"), "## 09:15\n\ncoffee".to_string());
+    assert_eq!(
+        append_entry("## 09:15\n\ncoffee\n", "12:30", "lunch"),
+        "## 09:15\n\ncoffee\n\n## 12:30\n\nlunch".to_string()
+    );
+}
+
+#[test]
+fn test_days_between() {
+    let notes = vec![
+        note(1, "2015-03-02", ""),
+        note(2, "not a day", ""),
+        note(3, "2015-03-01", ""),
+        note(4, "2015-03-05", "")
+    ];
+    let ids = |from: &str, to: &str| -> Vec<usize> {
+        days_between(&notes, from, to).iter().map(|n| n.id).collect()
+    };
+    assert_eq!(ids("", ""), vec![3, 1, 4]);
+    assert_eq!(ids("2015-03-02", ""), vec![1, 4]);
+    assert_eq!(ids("", "2015-03-02"), vec![3, 1]);
+    assert_eq!(ids("2015-03-03", "2015-03-04"), vec![]);
+}
@@ -4,10 +4,7 @@ use theca::ThecaProfile;
 
 #[test]
 fn test_add_note() {
-    let mut p = ThecaProfile {
-        encrypted: false,
-        notes: vec![]
-    };
+    let mut p = ThecaProfile::default();
     assert!(p.add_note(&"this is a title".to_string(), &vec![], false, false, false, false, false).is_ok());
     assert_eq!(p.notes.len(), 1);
     assert_eq!(p.notes[0].id, 1);
@@ -18,10 +15,7 @@ fn test_add_note() {
 
 #[test]
 fn test_add_started_note() {
-    let mut p = ThecaProfile {
-        encrypted: false,
-        notes: vec![]
-    };
+    let mut p = ThecaProfile::default();
     assert!(p.add_note(&"this is a title".to_string(), &vec![], true, false, false, false, false).is_ok());
     assert_eq!(p.notes.len(), 1);
     assert_eq!(p.notes[0].id, 1);
@@ -32,10 +26,7 @@ fn test_add_started_note() {
 
 #[test]
 fn test_add_urgent_note() {
-    let mut p = ThecaProfile {
-        encrypted: false,
-        notes: vec![]
-    };
+    let mut p = ThecaProfile::default();
     assert!(p.add_note(&"this is a title".to_string(), &vec![], false, true, false, false, false).is_ok());
     assert_eq!(p.notes.len(), 1);
     assert_eq!(p.notes[0].id, 1);
@@ -46,10 +37,7 @@ fn test_add_urgent_note() {
 
 #[test]
 fn test_add_basic_body_note() {
-    let mut p = ThecaProfile {
-        encrypted: false,
-        notes: vec![]
-    };
+    let mut p = ThecaProfile::default();
     assert!(p.add_note(&"this is a title".to_string(), &vec!["and what?".to_string()], false, false, false, false, false).is_ok());
     assert_eq!(p.notes.len(), 1);
     assert_eq!(p.notes[0].id, 1);
@@ -60,10 +48,7 @@ fn test_add_basic_body_note() {
 
 #[test]
 fn test_add_full_basic_body_note() {
-    let mut p = ThecaProfile {
-        encrypted: false,
-        notes: vec![]
-    };
+    let mut p = ThecaProfile::default();
     assert!(p.add_note(&"this is a title".to_string(), &vec!["and what?".to_string()], false, true, false, false, false).is_ok());
     assert_eq!(p.notes.len(), 1);
     assert_eq!(p.notes[0].id, 1);
@@ -74,10 +59,7 @@ fn test_add_full_basic_body_note() {
 
 #[test]
 fn test_edit_note_title() {
-    let mut p = ThecaProfile {
-        encrypted: false,
-        notes: vec![]
-    };
+    let mut p = ThecaProfile::default();
     assert!(p.add_note(&"this is a title".to_string(), &vec![], false, false, false, false, false).is_ok());
     assert_eq!(p.notes.len(), 1);
     assert!(p.edit_note(1, &"this is a new title".to_string(), &vec![], false, false, false, false, false, false, false).is_ok());
@@ -89,10 +71,7 @@ fn test_edit_note_title() {
 
 #[test]
 fn test_edit_note_status() {
-    let mut p = ThecaProfile {
-        encrypted: false,
-        notes: vec![]
-    };
+    let mut p = ThecaProfile::default();
     assert!(p.add_note(&"this is a title".to_string(), &vec![], false, false, false, false, false).is_ok());
     assert_eq!(p.notes.len(), 1);
     assert!(p.edit_note(1, &"".to_string(), &vec![], true, false, false, false, false, false, false).is_ok());
@@ -114,10 +93,7 @@ fn test_edit_note_status() {
 
 #[test]
 fn test_edit_note_body_basic() {
-    let mut p = ThecaProfile {
-        encrypted: false,
-        notes: vec![]
-    };
+    let mut p = ThecaProfile::default();
     assert!(p.add_note(&"this is a title".to_string(), &vec![], false, false, false, false, false).is_ok());
     assert_eq!(p.notes.len(), 1);
     assert!(p.edit_note(1, &"".to_string(), &vec!["woo body".to_string()], false, false, false, false, false, false, false).is_ok());
@@ -129,10 +105,7 @@ fn test_edit_note_body_basic() {
 
 #[test]
 fn test_edit_full_note() {
-    let mut p = ThecaProfile {
-        encrypted: false,
-        notes: vec![]
-    };
+    let mut p = ThecaProfile::default();
     assert!(p.add_note(&"this is a title".to_string(), &vec![], false, false, false, false, false).is_ok());
     assert_eq!(p.notes.len(), 1);
     assert!(p.edit_note(1, &"this is a new title".to_string(), &vec!["woo body".to_string()], true, false, false, false, false, false, false).is_ok());
@@ -144,10 +117,7 @@ fn test_edit_full_note() {
 
 #[test]
 fn test_delete_single_note() {
-    let mut p = ThecaProfile {
-        encrypted: false,
-        notes: vec![]
-    };
+    let mut p = ThecaProfile::default();
     assert!(p.add_note(&"this is a title".to_string(), &vec![], false, false, false, false, false).is_ok());
     p.delete_note(&vec![1]);
     assert_eq!(p.notes.len(), 0);
@@ -155,10 +125,7 @@ fn test_delete_single_note() {
 
 #[test]
 fn test_delete_some_notes() {
-    let mut p = ThecaProfile {
-        encrypted: false,
-        notes: vec![]
-    };
+    let mut p = ThecaProfile::default();
     assert!(p.add_note(&"this is a title".to_string(), &vec![], false, false, false, false, false).is_ok());
     assert_eq!(p.notes.len(), 1);
     assert!(p.add_note(&"this is a title".to_string(), &vec![], false, false, false, false, false).is_ok());
@@ -175,10 +142,7 @@ fn test_delete_some_notes() {
 
 #[test]
 fn test_clear_notes() {
-    let mut p = ThecaProfile {
-        encrypted: false,
-        notes: vec![]
-    };
+    let mut p = ThecaProfile::default();
     assert!(p.add_note(&"this is a title".to_string(), &vec![], false, false, false, false, false).is_ok());
     assert_eq!(p.notes.len(), 1);
     assert!(p.add_note(&"this is a title".to_string(), &vec![], false, false, false, false, false).is_ok());
@@ -189,3 +153,252 @@ fn test_clear_notes() {
     assert!(p.clear(true).is_ok());
     assert_eq!(p.notes.len(), 0);
 }
+
+#[test]
+fn test_ids_not_reused() {
+    let mut p = ThecaProfile::default();
+    assert!(p.add_note(&"this is a title".to_string(), &vec![], false, false, false, false, false).is_ok());
+    assert!(p.add_note(&"this is a title".to_string(), &vec![], false, false, false, false, false).is_ok());
+    p.delete_note(&vec![2]);
+    assert!(p.add_note(&"this is a title".to_string(), &vec![], false, false, false, false, false).is_ok());
+    assert_eq!(p.notes.len(), 2);
+    assert_eq!(p.notes[1].id, 3);
+    assert!(p.notes[0].uuid.is_some());
+    assert!(p.notes[0].uuid != p.notes[1].uuid);
+}
+
+#[test]
+fn test_subtasks() {
+    let mut p = ThecaProfile::default();
+    assert!(p.add_note(&"this is a title".to_string(), &vec![], false, false, false, false, false).is_ok());
+    assert!(p.add_note(&"this is a title".to_string(), &vec![], false, false, false, false, false).is_ok());
+    assert!(p.add_note(&"this is a title".to_string(), &vec![], false, false, false, false, false).is_ok());
+    assert!(p.set_parent(2, 1).is_ok());
+    assert!(p.set_parent(3, 2).is_ok());
+    assert!(p.set_parent(1, 3).is_err());
+    assert!(p.set_parent(1, 1).is_err());
+    assert!(p.set_parent(1, 9).is_err());
+
+    let ids = p.delete_ids(&vec![1], true);
+    assert!(ids.is_ok());
+    assert_eq!(ids.ok().unwrap(), vec![1, 2, 3]);
+
+    p.delete_note(&vec![2]);
+    assert_eq!(p.notes.len(), 2);
+    assert_eq!(p.notes[1].parent, Some(1));
+}
+
+#[test]
+fn test_blocked_by() {
+    let mut p = ThecaProfile::default();
+    assert!(p.add_note(&"this is a title".to_string(), &vec![], false, false, false, false, false).is_ok());
+    assert!(p.add_note(&"this is a title".to_string(), &vec![], false, false, false, false, false).is_ok());
+    assert!(p.add_blocker(2, 1).is_ok());
+    assert!(p.add_blocker(1, 2).is_err());
+    assert!(p.mark_done(2).is_err());
+    assert!(p.mark_done(1).is_ok());
+    assert_eq!(p.notes[0].status, "Done".to_string());
+    assert!(p.mark_done(2).is_ok());
+
+    assert!(p.remove_blocker(2, 1).is_ok());
+    assert!(p.notes[1].blocked_by.is_none());
+}
+
+#[test]
+fn test_check_item() {
+    let mut p = ThecaProfile::default();
+    assert!(p.add_note(&"this is a title".to_string(), &vec!["- [ ] one\n- [x] two".to_string()], false, false, false, false, false).is_ok());
+    assert!(p.check_item(1, 1, true, false).is_ok());
+    assert_eq!(p.notes[0].body, "- [x] one\n- [x] two".to_string());
+    assert_eq!(p.notes[0].status, "".to_string());
+    assert!(p.check_item(1, 2, false, true).is_ok());
+    assert!(p.check_item(1, 2, true, true).is_ok());
+    assert_eq!(p.notes[0].status, "Done".to_string());
+    assert!(p.check_item(1, 3, true, true).is_err());
+}
+
+#[test]
+fn test_recurring_note() {
+    let mut p = ThecaProfile::default();
+    assert!(p.add_note(&"weekly review".to_string(), &vec!["- [x] inbox".to_string()], false, false, false, false, false).is_ok());
+    assert!(p.set_schedule(1, &"1w".to_string(), &"".to_string()).is_ok());
+    assert!(p.notes[0].due.is_some());
+    assert!(p.set_schedule(1, &"sometimes".to_string(), &"".to_string()).is_err());
+    assert!(p.mark_done(1).is_ok());
+    assert_eq!(p.notes.len(), 2);
+    assert!(p.notes[0].every.is_none());
+    assert_eq!(p.notes[1].title, "weekly review".to_string());
+    assert_eq!(p.notes[1].body, "- [ ] inbox".to_string());
+    assert_eq!(p.notes[1].status, "".to_string());
+    assert_eq!(p.notes[1].every, Some("1w".to_string()));
+    assert_eq!(p.notes[1].series, p.notes[0].series);
+    assert!(p.notes[1].uuid != p.notes[0].uuid);
+}
+
+#[test]
+fn test_archive_notes() {
+    let mut p = ThecaProfile::default();
+    assert!(p.add_note(&"this is a title".to_string(), &vec![], false, false, false, false, false).is_ok());
+    assert!(p.add_note(&"this is another title".to_string(), &vec![], false, false, false, false, false).is_ok());
+    assert!(p.archive_notes(&vec![1, 3], true).is_ok());
+    assert_eq!(p.notes.len(), 2);
+    assert!(p.notes[0].archived.is_some());
+    assert!(p.notes[1].archived.is_none());
+    assert!(p.archive_notes(&vec![1], false).is_ok());
+    assert!(p.notes[0].archived.is_none());
+}
+
+#[test]
+fn test_pin_and_move_notes() {
+    let mut p = ThecaProfile::default();
+    for t in ["one", "two", "three"].iter() {
+        assert!(p.add_note(&t.to_string(), &vec![], false, false, false, false, false).is_ok());
+    }
+    assert!(p.pin_notes(&vec![2], true).is_ok());
+    assert_eq!(p.notes[1].pinned, Some(true));
+    assert!(p.pin_notes(&vec![2], false).is_ok());
+    assert!(p.notes[1].pinned.is_none());
+    assert!(p.move_note(3, 1).is_ok());
+    assert_eq!(p.order, Some(vec![3, 1, 2]));
+    assert!(p.move_note(3, 3).is_err());
+    assert!(p.move_note(3, 9).is_err());
+    p.delete_note(&vec![1]);
+    assert_eq!(p.order, Some(vec![3, 2]));
+}
+
+#[test]
+fn test_set_fields() {
+    let mut p = ThecaProfile::default();
+    assert!(p.add_note(&"this is a title".to_string(), &vec![], false, false, false, false, false).is_ok());
+    assert!(p.set_fields(1, &vec!["estimate=2h".to_string(), "customer=acme".to_string()]).is_ok());
+    assert!(p.set_fields(1, &vec!["estimate=3h".to_string()]).is_ok());
+    {
+        let fields = p.notes[0].fields.as_ref().unwrap();
+        assert_eq!(fields.len(), 2);
+        assert_eq!(fields[0].name, "estimate".to_string());
+        assert_eq!(fields[0].value, "3h".to_string());
+    }
+    assert!(p.set_fields(1, &vec!["estimate=".to_string(), "customer=".to_string()]).is_ok());
+    assert!(p.notes[0].fields.is_none());
+    assert!(p.set_fields(1, &vec!["points:int=lots".to_string()]).is_err());
+    assert!(p.set_fields(2, &vec!["estimate=2h".to_string()]).is_err());
+}
+
+#[test]
+fn test_timers() {
+    let mut p = ThecaProfile::default();
+    assert!(p.add_note(&"this is a title".to_string(), &vec![], false, false, false, false, false).is_ok());
+    assert!(p.add_note(&"this is another title".to_string(), &vec![], false, false, false, false, false).is_ok());
+    assert!(p.start_timer(1, false).is_ok());
+    assert_eq!(p.notes[0].status, "Started".to_string());
+    assert!(p.start_timer(1, false).is_err());
+    // starting another timer stops the first
+    assert!(p.start_timer(2, false).is_ok());
+    assert!(p.notes[0].time_log.as_ref().unwrap()[0].stop.is_some());
+    assert!(p.notes[1].time_log.as_ref().unwrap()[0].stop.is_none());
+    // unless they're allowed to run at the same time
+    assert!(p.start_timer(1, true).is_ok());
+    assert!(p.notes[1].time_log.as_ref().unwrap()[0].stop.is_none());
+    assert!(p.stop_timer(&vec![]).is_ok());
+    assert!(p.notes.iter().all(|n| n.time_log.as_ref().unwrap().iter().all(|e| e.stop.is_some())));
+    assert!(p.stop_timer(&vec![]).is_err());
+}
+
+#[test]
+fn test_set_priority() {
+    let mut p = ThecaProfile::default();
+    assert!(p.add_note(&"this is a title".to_string(), &vec![], false, false, false, false, false).is_ok());
+    assert!(p.set_priority(1, &"b".to_string()).is_ok());
+    assert_eq!(p.notes[0].priority, Some("B".to_string()));
+    assert!(p.set_priority(1, &"none".to_string()).is_ok());
+    assert!(p.notes[0].priority.is_none());
+    assert!(p.set_priority(1, &"urgent".to_string()).is_err());
+    assert!(p.set_priority(2, &"A".to_string()).is_err());
+}
+
+#[test]
+fn test_today() {
+    let mut p = ThecaProfile::default();
+    assert!(p.today(&vec!["first".to_string()], false, false, false, true, false, false, false).is_ok());
+    assert!(p.today(&vec!["second".to_string()], false, false, false, true, false, false, false).is_ok());
+    assert_eq!(p.notes.len(), 1);
+    assert_eq!(p.notes[0].title.len(), 10);
+    assert!(p.notes[0].body.starts_with("## "));
+    assert!(p.notes[0].body.contains("first\n\n## "));
+    assert!(p.notes[0].body.ends_with("second"));
+    assert!(p.today(&vec!["  ".to_string()], false, false, false, true, false, false, false).is_err());
+}
+
+#[test]
+fn test_views() {
+    let mut p = ThecaProfile::default();
+    let args = vec!["-d".to_string(), "-l".to_string(), "10".to_string()];
+    assert!(p.save_view(&"today".to_string(), &args).is_ok());
+    assert!(p.save_view(&"a view".to_string(), &args).is_err());
+    assert_eq!(p.saved_view(&"today".to_string()).unwrap().args, args);
+    assert!(p.saved_view(&"tomorrow".to_string()).is_err());
+    assert!(p.remove_view(&"tomorrow".to_string()).is_err());
+    assert!(p.remove_view(&"today".to_string()).is_ok());
+    assert!(p.views.is_none());
+}
+
+#[test]
+fn test_replace() {
+    let mut p = ThecaProfile::default();
+    assert!(p.add_note(&"deploy api".to_string(), &vec![], false, false, false, false, false).is_ok());
+    assert!(p.add_note(&"write docs".to_string(), &vec![], false, false, false, false, false).is_ok());
+    p.notes[1].last_touched = "2015-01-22 19:43:24 -0800".to_string();
+    let (deploy, ship, none) = ("deploy".to_string(), "ship".to_string(), "".to_string());
+    let both = "both".to_string();
+    // a dry run changes nothing
+    assert_eq!(p.replace(&deploy, &ship, false, &both, false, false, &none, true, true).unwrap(), false);
+    assert_eq!(p.notes[0].title, "deploy api".to_string());
+    assert_eq!(p.replace(&deploy, &ship, false, &both, false, false, &none, false, true).unwrap(), true);
+    assert_eq!(p.notes[0].title, "ship api".to_string());
+    // only the notes that changed are touched
+    assert_eq!(p.notes[1].last_touched, "2015-01-22 19:43:24 -0800".to_string());
+    assert_eq!(p.replace(&deploy, &ship, false, &both, false, false, &none, false, true).unwrap(), false);
+    assert!(p.replace(&"(".to_string(), &ship, true, &both, false, false, &none, false, true).is_err());
+    assert!(p.replace(&deploy, &ship, false, &"tags".to_string(), false, false, &none, false, true).is_err());
+}
+
+#[test]
+fn test_select_notes() {
+    let mut p = ThecaProfile::default();
+    for t in ["deploy api", "write docs", "deploy web"].iter() {
+        assert!(p.add_note(&t.to_string(), &vec![], false, false, false, false, false).is_ok());
+    }
+    let none = "".to_string();
+    let ids = vec!["1-3,!2".to_string()];
+    assert_eq!(p.select_notes(&ids, &none, false, false, "delete", true).unwrap(), vec![1, 3]);
+    let deploys = "title:deploy".to_string();
+    assert_eq!(p.select_notes(&vec![], &deploys, false, false, "delete", true).unwrap(), vec![1, 3]);
+    assert_eq!(p.select_notes(&vec!["2-3".to_string()], &deploys, false, false, "delete", true).unwrap(), vec![3]);
+    assert!(p.select_notes(&vec!["2".to_string()], &deploys, false, false, "delete", true).is_err());
+}
+
+#[test]
+fn test_assign_uuids() {
+    let mut p = ThecaProfile::default();
+    for t in ["old", "new"].iter() {
+        assert!(p.add_note(&t.to_string(), &vec![], false, false, false, false, false).is_ok());
+    }
+    p.notes[0].uuid = None;
+    let kept = p.notes[1].uuid.clone();
+    p.assign_uuids();
+    assert!(p.notes[0].uuid.is_some());
+    assert_eq!(p.notes[1].uuid, kept);
+}
+
+#[test]
+fn test_stats_all_archived() {
+    let mut p = ThecaProfile::default();
+    assert!(p.add_note(&"this is a title".to_string(), &vec![], false, false, false, false, false).is_ok());
+    p.notes[0].archived = Some("2015-01-22 19:43:24 -0800".to_string());
+    let name = "default".to_string();
+    // nothing is shown so there are no ages, but the profile is fine
+    assert!(p.stats(&name, false, false).is_ok());
+    assert!(p.stats(&name, true, false).is_ok());
+    p.notes[0].archived = None;
+    assert!(p.stats(&name, true, false).is_ok());
+}
@@ -1,7 +1,10 @@
 extern crate theca;
 
+use std::iter::{repeat};
+
 use theca::{ThecaItem};
-use theca::lineformat::{LineFormat};
+use theca::lineformat::{LineFormat, FIELD_WIDTH};
+use theca::fields::{Field};
 
 struct LineTest {
     input_notes: Vec<ThecaItem>,
@@ -12,12 +15,14 @@ struct LineTest {
 
 fn test_formatter(tests: &[LineTest]) {
     for t in tests.iter() {
-        let wrapped_format = LineFormat::new(&t.input_notes, t.condensed, t.search);
+        let wrapped_format = LineFormat::new(&t.input_notes, t.condensed, t.search, false, &vec![]);
         assert!(wrapped_format.is_ok());
         let actual_format = wrapped_format.ok().unwrap();
         assert_eq!(t.expected_format.colsep, actual_format.colsep);
         assert_eq!(t.expected_format.id_width, actual_format.id_width);
+        assert_eq!(t.expected_format.priority_width, actual_format.priority_width);
         assert_eq!(t.expected_format.title_width, actual_format.title_width);
+        assert_eq!(t.expected_format.progress_width, actual_format.progress_width);
         assert_eq!(t.expected_format.status_width, actual_format.status_width);
         assert_eq!(t.expected_format.touched_width, actual_format.touched_width);
     }
@@ -31,16 +36,14 @@ fn test_new_line_format_basic() {
                 ThecaItem {
                     id: 1,
                     title: "a title".to_string(),
-                    body: "".to_string(),
-                    status: "".to_string(),
-                    last_touched: "2015-01-22 19:43:24 -0800".to_string()
+                    last_touched: "2015-01-22 19:43:24 -0800".to_string(),
+                    ..Default::default()
                 },
                 ThecaItem {
                     id: 2,
                     title: "a longer title".to_string(),
-                    body: "".to_string(),
-                    status: "".to_string(),
-                    last_touched: "2015-01-22 19:43:24 -0800".to_string()
+                    last_touched: "2015-01-22 19:43:24 -0800".to_string(),
+                    ..Default::default()
                 }
             ],
             condensed: false,
@@ -48,8 +51,11 @@ fn test_new_line_format_basic() {
             expected_format: LineFormat {
                 colsep: 2,
                 id_width: 2,
+                priority_width: 0,
                 title_width: 14,
+                progress_width: 0,
                 status_width: 0,
+                field_widths: vec![],
                 touched_width: 19
             }
         },
@@ -58,16 +64,14 @@ fn test_new_line_format_basic() {
                 ThecaItem {
                     id: 1,
                     title: "a title".to_string(),
-                    body: "".to_string(),
-                    status: "".to_string(),
-                    last_touched: "2015-01-22 19:43:24 -0800".to_string()
+                    last_touched: "2015-01-22 19:43:24 -0800".to_string(),
+                    ..Default::default()
                 },
                 ThecaItem {
                     id: 2,
                     title: "a longer title".to_string(),
-                    body: "".to_string(),
-                    status: "".to_string(),
-                    last_touched: "2015-01-22 19:43:24 -0800".to_string()
+                    last_touched: "2015-01-22 19:43:24 -0800".to_string(),
+                    ..Default::default()
                 }
             ],
             condensed: true,
@@ -75,8 +79,11 @@ fn test_new_line_format_basic() {
             expected_format: LineFormat {
                 colsep: 1,
                 id_width: 1,
+                priority_width: 0,
                 title_width: 14,
+                progress_width: 0,
                 status_width: 0,
+                field_widths: vec![],
                 touched_width: 10
             }
         }
@@ -93,16 +100,15 @@ fn test_new_line_format_statuses() {
                 ThecaItem {
                     id: 1,
                     title: "a title".to_string(),
-                    body: "".to_string(),
                     status: "Started".to_string(),
-                    last_touched: "2015-01-22 19:43:24 -0800".to_string()
+                    last_touched: "2015-01-22 19:43:24 -0800".to_string(),
+                    ..Default::default()
                 },
                 ThecaItem {
                     id: 2,
                     title: "a longer title".to_string(),
-                    body: "".to_string(),
-                    status: "".to_string(),
-                    last_touched: "2015-01-22 19:43:24 -0800".to_string()
+                    last_touched: "2015-01-22 19:43:24 -0800".to_string(),
+                    ..Default::default()
                 }
             ],
             condensed: false,
@@ -110,8 +116,11 @@ fn test_new_line_format_statuses() {
             expected_format: LineFormat {
                 colsep: 2,
                 id_width: 2,
+                priority_width: 0,
                 title_width: 14,
+                progress_width: 0,
                 status_width: 7,
+                field_widths: vec![],
                 touched_width: 19
             }
         },
@@ -120,16 +129,15 @@ fn test_new_line_format_statuses() {
                 ThecaItem {
                     id: 1,
                     title: "a title".to_string(),
-                    body: "".to_string(),
-                    status: "".to_string(),
-                    last_touched: "2015-01-22 19:43:24 -0800".to_string()
+                    last_touched: "2015-01-22 19:43:24 -0800".to_string(),
+                    ..Default::default()
                 },
                 ThecaItem {
                     id: 2,
                     title: "a longer title".to_string(),
-                    body: "".to_string(),
                     status: "Urgent".to_string(),
-                    last_touched: "2015-01-22 19:43:24 -0800".to_string()
+                    last_touched: "2015-01-22 19:43:24 -0800".to_string(),
+                    ..Default::default()
                 }
             ],
             condensed: false,
@@ -137,8 +145,11 @@ fn test_new_line_format_statuses() {
             expected_format: LineFormat {
                 colsep: 2,
                 id_width: 2,
+                priority_width: 0,
                 title_width: 14,
+                progress_width: 0,
                 status_width: 6,
+                field_widths: vec![],
                 touched_width: 19
             }
         },
@@ -147,16 +158,15 @@ fn test_new_line_format_statuses() {
                 ThecaItem {
                     id: 1,
                     title: "a title".to_string(),
-                    body: "".to_string(),
-                    status: "".to_string(),
-                    last_touched: "2015-01-22 19:43:24 -0800".to_string()
+                    last_touched: "2015-01-22 19:43:24 -0800".to_string(),
+                    ..Default::default()
                 },
                 ThecaItem {
                     id: 2,
                     title: "a longer title".to_string(),
-                    body: "".to_string(),
                     status: "Urgent".to_string(),
-                    last_touched: "2015-01-22 19:43:24 -0800".to_string()
+                    last_touched: "2015-01-22 19:43:24 -0800".to_string(),
+                    ..Default::default()
                 }
             ],
             condensed: true,
@@ -164,8 +174,11 @@ fn test_new_line_format_statuses() {
             expected_format: LineFormat {
                 colsep: 1,
                 id_width: 1,
+                priority_width: 0,
                 title_width: 14,
+                progress_width: 0,
                 status_width: 1,
+                field_widths: vec![],
                 touched_width: 10
             }
         }
@@ -182,16 +195,15 @@ fn test_new_line_format_body() {
                 ThecaItem {
                     id: 1,
                     title: "a title".to_string(),
-                    body: "".to_string(),
-                    status: "".to_string(),
-                    last_touched: "2015-01-22 19:43:24 -0800".to_string()
+                    last_touched: "2015-01-22 19:43:24 -0800".to_string(),
+                    ..Default::default()
                 },
                 ThecaItem {
                     id: 2,
                     title: "a longer title".to_string(),
                     body: "this is a body".to_string(),
-                    status: "".to_string(),
-                    last_touched: "2015-01-22 19:43:24 -0800".to_string()
+                    last_touched: "2015-01-22 19:43:24 -0800".to_string(),
+                    ..Default::default()
                 }
             ],
             condensed: false,
@@ -199,8 +211,11 @@ fn test_new_line_format_body() {
             expected_format: LineFormat {
                 colsep: 2,
                 id_width: 2,
+                priority_width: 0,
                 title_width: 18,
+                progress_width: 0,
                 status_width: 0,
+                field_widths: vec![],
                 touched_width: 19
             }
         },
@@ -209,16 +224,15 @@ fn test_new_line_format_body() {
                 ThecaItem {
                     id: 1,
                     title: "a title".to_string(),
-                    body: "".to_string(),
-                    status: "".to_string(),
-                    last_touched: "2015-01-22 19:43:24 -0800".to_string()
+                    last_touched: "2015-01-22 19:43:24 -0800".to_string(),
+                    ..Default::default()
                 },
                 ThecaItem {
                     id: 2,
                     title: "a longer title".to_string(),
                     body: "this is a body".to_string(),
-                    status: "".to_string(),
-                    last_touched: "2015-01-22 19:43:24 -0800".to_string()
+                    last_touched: "2015-01-22 19:43:24 -0800".to_string(),
+                    ..Default::default()
                 }
             ],
             condensed: true,
@@ -226,8 +240,11 @@ fn test_new_line_format_body() {
             expected_format: LineFormat {
                 colsep: 1,
                 id_width: 1,
+                priority_width: 0,
                 title_width: 18,
+                progress_width: 0,
                 status_width: 0,
+                field_widths: vec![],
                 touched_width: 10
             }
         },
@@ -236,16 +253,15 @@ fn test_new_line_format_body() {
                 ThecaItem {
                     id: 1,
                     title: "a title".to_string(),
-                    body: "".to_string(),
-                    status: "".to_string(),
-                    last_touched: "2015-01-22 19:43:24 -0800".to_string()
+                    last_touched: "2015-01-22 19:43:24 -0800".to_string(),
+                    ..Default::default()
                 },
                 ThecaItem {
                     id: 2,
                     title: "a longer title".to_string(),
                     body: "this is a body".to_string(),
-                    status: "".to_string(),
-                    last_touched: "2015-01-22 19:43:24 -0800".to_string()
+                    last_touched: "2015-01-22 19:43:24 -0800".to_string(),
+                    ..Default::default()
                 }
             ],
             condensed: false,
@@ -253,8 +269,11 @@ fn test_new_line_format_body() {
             expected_format: LineFormat {
                 colsep: 2,
                 id_width: 2,
+                priority_width: 0,
                 title_width: 14,
+                progress_width: 0,
                 status_width: 0,
+                field_widths: vec![],
                 touched_width: 19
             }
         },
@@ -263,16 +282,15 @@ fn test_new_line_format_body() {
                 ThecaItem {
                     id: 1,
                     title: "a title".to_string(),
-                    body: "".to_string(),
-                    status: "".to_string(),
-                    last_touched: "2015-01-22 19:43:24 -0800".to_string()
+                    last_touched: "2015-01-22 19:43:24 -0800".to_string(),
+                    ..Default::default()
                 },
                 ThecaItem {
                     id: 2,
                     title: "a longer title".to_string(),
                     body: "this is a body".to_string(),
-                    status: "".to_string(),
-                    last_touched: "2015-01-22 19:43:24 -0800".to_string()
+                    last_touched: "2015-01-22 19:43:24 -0800".to_string(),
+                    ..Default::default()
                 }
             ],
             condensed: true,
@@ -280,8 +298,11 @@ fn test_new_line_format_body() {
             expected_format: LineFormat {
                 colsep: 1,
                 id_width: 1,
+                priority_width: 0,
                 title_width: 14,
+                progress_width: 0,
                 status_width: 0,
+                field_widths: vec![],
                 touched_width: 10
             }
         }
@@ -298,16 +319,16 @@ fn test_new_line_format_full() {
                 ThecaItem {
                     id: 1,
                     title: "a title".to_string(),
-                    body: "".to_string(),
                     status: "Started".to_string(),
-                    last_touched: "2015-01-22 19:43:24 -0800".to_string()
+                    last_touched: "2015-01-22 19:43:24 -0800".to_string(),
+                    ..Default::default()
                 },
                 ThecaItem {
                     id: 2,
                     title: "a longer title".to_string(),
                     body: "this is a body".to_string(),
-                    status: "".to_string(),
-                    last_touched: "2015-01-22 19:43:24 -0800".to_string()
+                    last_touched: "2015-01-22 19:43:24 -0800".to_string(),
+                    ..Default::default()
                 }
             ],
             condensed: false,
@@ -315,8 +336,11 @@ fn test_new_line_format_full() {
             expected_format: LineFormat {
                 colsep: 2,
                 id_width: 2,
+                priority_width: 0,
                 title_width: 18,
+                progress_width: 0,
                 status_width: 7,
+                field_widths: vec![],
                 touched_width: 19
             }
         },
@@ -325,16 +349,16 @@ fn test_new_line_format_full() {
                 ThecaItem {
                     id: 1,
                     title: "a title".to_string(),
-                    body: "".to_string(),
                     status: "Started".to_string(),
-                    last_touched: "2015-01-22 19:43:24 -0800".to_string()
+                    last_touched: "2015-01-22 19:43:24 -0800".to_string(),
+                    ..Default::default()
                 },
                 ThecaItem {
                     id: 2,
                     title: "a longer title".to_string(),
                     body: "this is a body".to_string(),
-                    status: "".to_string(),
-                    last_touched: "2015-01-22 19:43:24 -0800".to_string()
+                    last_touched: "2015-01-22 19:43:24 -0800".to_string(),
+                    ..Default::default()
                 }
             ],
             condensed: true,
@@ -342,8 +366,11 @@ fn test_new_line_format_full() {
             expected_format: LineFormat {
                 colsep: 1,
                 id_width: 1,
+                priority_width: 0,
                 title_width: 18,
+                progress_width: 0,
                 status_width: 1,
+                field_widths: vec![],
                 touched_width: 10
             }
         },
@@ -352,16 +379,16 @@ fn test_new_line_format_full() {
                 ThecaItem {
                     id: 1,
                     title: "a title".to_string(),
-                    body: "".to_string(),
                     status: "Urgent".to_string(),
-                    last_touched: "2015-01-22 19:43:24 -0800".to_string()
+                    last_touched: "2015-01-22 19:43:24 -0800".to_string(),
+                    ..Default::default()
                 },
                 ThecaItem {
                     id: 2,
                     title: "a longer title".to_string(),
                     body: "this is a body".to_string(),
-                    status: "".to_string(),
-                    last_touched: "2015-01-22 19:43:24 -0800".to_string()
+                    last_touched: "2015-01-22 19:43:24 -0800".to_string(),
+                    ..Default::default()
                 }
             ],
             condensed: false,
@@ -369,8 +396,11 @@ fn test_new_line_format_full() {
             expected_format: LineFormat {
                 colsep: 2,
                 id_width: 2,
+                priority_width: 0,
                 title_width: 14,
+                progress_width: 0,
                 status_width: 6,
+                field_widths: vec![],
                 touched_width: 19
             }
         },
@@ -379,16 +409,16 @@ fn test_new_line_format_full() {
                 ThecaItem {
                     id: 1,
                     title: "a title".to_string(),
-                    body: "".to_string(),
-                    status: "".to_string(),
-                    last_touched: "2015-01-22 19:43:24 -0800".to_string()
+                    last_touched: "2015-01-22 19:43:24 -0800".to_string(),
+                    ..Default::default()
                 },
                 ThecaItem {
                     id: 2,
                     title: "a longer title".to_string(),
                     body: "this is a body".to_string(),
                     status: "Urgent".to_string(),
-                    last_touched: "2015-01-22 19:43:24 -0800".to_string()
+                    last_touched: "2015-01-22 19:43:24 -0800".to_string(),
+                    ..Default::default()
                 }
             ],
             condensed: true,
@@ -396,8 +426,11 @@ fn test_new_line_format_full() {
             expected_format: LineFormat {
                 colsep: 1,
                 id_width: 1,
+                priority_width: 0,
                 title_width: 14,
+                progress_width: 0,
                 status_width: 1,
+                field_widths: vec![],
                 touched_width: 10
             }
         }
@@ -405,3 +438,237 @@ fn test_new_line_format_full() {
 
     test_formatter(&body_tests[..]);
 }
+
+#[test]
+fn test_new_line_format_checklist() {
+    // twelve items, two of them checked, so the progress is "2/12"
+    let mut groceries = "- [x] milk\n- [x] eggs".to_string();
+    for _ in 0..10 {groceries.push_str("\n- [ ] bread");}
+    let checklist_tests = vec![
+        LineTest {
+            input_notes: vec![
+                ThecaItem {
+                    id: 1,
+                    title: "groceries".to_string(),
+                    body: groceries.clone(),
+                    last_touched: "2015-01-22 19:43:24 -0800".to_string(),
+                    ..Default::default()
+                },
+                ThecaItem {
+                    id: 2,
+                    title: "a title".to_string(),
+                    last_touched: "2015-01-22 19:43:24 -0800".to_string(),
+                    ..Default::default()
+                }
+            ],
+            condensed: false,
+            search: false,
+            expected_format: LineFormat {
+                colsep: 2,
+                id_width: 2,
+                priority_width: 0,
+                title_width: 13,
+                progress_width: 4,
+                status_width: 0,
+                field_widths: vec![],
+                touched_width: 19
+            }
+        },
+        LineTest {
+            input_notes: vec![
+                ThecaItem {
+                    id: 1,
+                    title: "groceries".to_string(),
+                    body: "- [x] milk\n- [ ] eggs".to_string(),
+                    last_touched: "2015-01-22 19:43:24 -0800".to_string(),
+                    ..Default::default()
+                }
+            ],
+            condensed: true,
+            search: false,
+            expected_format: LineFormat {
+                colsep: 1,
+                id_width: 1,
+                priority_width: 0,
+                title_width: 13,
+                progress_width: 3,
+                status_width: 0,
+                field_widths: vec![],
+                touched_width: 10
+            }
+        }
+    ];
+
+    test_formatter(&checklist_tests[..]);
+}
+
+#[test]
+fn test_new_line_format_priority() {
+    // "pri" isn't truncated in the extended format
+    let priority_tests = vec![
+        LineTest {
+            input_notes: vec![
+                ThecaItem {
+                    id: 1,
+                    title: "a title".to_string(),
+                    last_touched: "2015-01-22 19:43:24 -0800".to_string(),
+                    priority: Some("A".to_string()),
+                    ..Default::default()
+                },
+                ThecaItem {
+                    id: 2,
+                    title: "a longer title".to_string(),
+                    status: "Started".to_string(),
+                    last_touched: "2015-01-22 19:43:24 -0800".to_string(),
+                    ..Default::default()
+                }
+            ],
+            condensed: false,
+            search: false,
+            expected_format: LineFormat {
+                colsep: 2,
+                id_width: 2,
+                priority_width: 3,
+                title_width: 14,
+                progress_width: 0,
+                status_width: 7,
+                field_widths: vec![],
+                touched_width: 19
+            }
+        },
+        LineTest {
+            input_notes: vec![
+                ThecaItem {
+                    id: 1,
+                    title: "a title".to_string(),
+                    last_touched: "2015-01-22 19:43:24 -0800".to_string(),
+                    priority: Some("10".to_string()),
+                    ..Default::default()
+                }
+            ],
+            condensed: true,
+            search: false,
+            expected_format: LineFormat {
+                colsep: 1,
+                id_width: 1,
+                priority_width: 2,
+                title_width: 7,
+                progress_width: 0,
+                status_width: 0,
+                field_widths: vec![],
+                touched_width: 10
+            }
+        },
+        LineTest {
+            input_notes: vec![
+                ThecaItem {
+                    id: 1,
+                    title: "a title".to_string(),
+                    last_touched: "2015-01-22 19:43:24 -0800".to_string(),
+                    priority: Some("A".to_string()),
+                    ..Default::default()
+                }
+            ],
+            condensed: true,
+            search: false,
+            expected_format: LineFormat {
+                colsep: 1,
+                id_width: 1,
+                priority_width: 1,
+                title_width: 7,
+                progress_width: 0,
+                status_width: 0,
+                field_widths: vec![],
+                touched_width: 10
+            }
+        },
+        LineTest {
+            input_notes: vec![
+                ThecaItem {
+                    id: 1,
+                    title: "a title".to_string(),
+                    last_touched: "2015-01-22 19:43:24 -0800".to_string(),
+                    ..Default::default()
+                }
+            ],
+            condensed: true,
+            search: false,
+            expected_format: LineFormat {
+                colsep: 1,
+                id_width: 1,
+                priority_width: 0,
+                title_width: 7,
+                progress_width: 0,
+                status_width: 0,
+                field_widths: vec![],
+                touched_width: 10
+            }
+        }
+    ];
+
+    test_formatter(&priority_tests[..]);
+}
+
+#[test]
+fn test_new_line_format_relative() {
+    let notes = vec![
+        ThecaItem {
+            id: 1,
+            title: "a title".to_string(),
+            last_touched: "2015-01-22 19:43:24 -0800".to_string(),
+            ..Default::default()
+        }
+    ];
+
+    // "Ny ago" is shorter than "last touched" so the header width wins
+    let wrapped_format = LineFormat::new(&notes, false, false, true, &vec![]);
+    assert!(wrapped_format.is_ok());
+    assert_eq!(wrapped_format.ok().unwrap().touched_width, 12);
+
+    let wrapped_format = LineFormat::new(&notes, true, false, true, &vec![]);
+    assert!(wrapped_format.is_ok());
+    assert!(wrapped_format.ok().unwrap().touched_width < 10);
+}
+
+#[test]
+fn test_new_line_format_fields() {
+    let notes = vec![
+        ThecaItem {
+            id: 1,
+            title: "a title".to_string(),
+            last_touched: "2015-01-22 19:43:24 -0800".to_string(),
+            fields: Some(vec![Field {
+                name: "customer".to_string(),
+                kind: "string".to_string(),
+                value: "a very big customer".to_string()
+            }]),
+            ..Default::default()
+        }
+    ];
+    let columns = vec!["customer".to_string(), "estimate".to_string()];
+
+    let wrapped_format = LineFormat::new(&notes, false, false, false, &columns);
+    assert!(wrapped_format.is_ok());
+    let extended = wrapped_format.ok().unwrap();
+    assert_eq!(extended.field_widths, vec![
+        ("customer".to_string(), 19),
+        ("estimate".to_string(), 8)
+    ]);
+
+    let wrapped_format = LineFormat::new(&notes, true, false, false, &columns);
+    assert!(wrapped_format.is_ok());
+    let condensed = wrapped_format.ok().unwrap();
+    assert_eq!(condensed.field_widths, vec![
+        ("customer".to_string(), 19),
+        ("estimate".to_string(), 1)
+    ]);
+    assert_eq!(extended.line_width(), 63);
+    assert_eq!(condensed.line_width(), 42);
+
+    // long values are truncated rather than widening the column without end
+    let mut long = notes.clone();
+    long[0].fields.as_mut().unwrap()[0].value = repeat('x').take(80).collect();
+    let wrapped_format = LineFormat::new(&long, false, false, false, &columns);
+    assert!(wrapped_format.is_ok());
+    assert_eq!(wrapped_format.ok().unwrap().field_widths[0], ("customer".to_string(), FIELD_WIDTH));
+}
@@ -0,0 +1,30 @@
+extern crate theca;
+
+mod common;
+
+use theca::{ThecaItem};
+use theca::links::{parse_links, LinkIndex};
+
+fn note(id: usize, body: &str) -> ThecaItem {
+    ThecaItem {
+        ..common::note(id, &format!("note {}", id), body)
+    }
+}
+
+#[test]
+fn test_parse_links() {
+    assert_eq!(parse_links("see [[3]] and #12, also [[3]] again"), vec![3, 12]);
+    assert_eq!(parse_links("#4 at the start"), vec![4]);
+    assert!(parse_links("issue#5 and &#39; and # 6").is_empty());
+}
+
+#[test]
+fn test_link_index() {
+    let notes = vec![note(1, "needs [[2]] and [[7]]"), note(2, "back to #1, and #2"), note(3, "")];
+    let index = LinkIndex::new(&notes);
+    assert_eq!(index.outgoing(1), vec![2, 7]);
+    assert_eq!(index.outgoing(2), vec![1]);
+    assert_eq!(index.backlinks(1), vec![2]);
+    assert_eq!(index.backlinks(2), vec![1]);
+    assert_eq!(index.broken(&notes), vec![(1, 7)]);
+}
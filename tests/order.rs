@@ -0,0 +1,48 @@
+extern crate theca;
+
+mod common;
+
+use theca::{ThecaItem};
+use theca::order::{manual_order, move_before, sort_manual, pinned_first};
+
+fn note(id: usize, pinned: bool) -> ThecaItem {
+    ThecaItem {
+        pinned: match pinned {
+            true => Some(true),
+            false => None
+        },
+        ..common::note(id, &format!("note {}", id), "")
+    }
+}
+
+fn ids(notes: &Vec<ThecaItem>) -> Vec<usize> {
+    notes.iter().map(|n| n.id).collect()
+}
+
+#[test]
+fn test_manual_order() {
+    let notes = vec![note(3, false), note(1, false), note(2, false), note(4, false)];
+    assert_eq!(manual_order(&notes, &None), vec![1, 2, 3, 4]);
+    // deleted notes drop out, new notes are added by id
+    assert_eq!(manual_order(&notes, &Some(vec![4, 9, 2])), vec![4, 2, 1, 3]);
+}
+
+#[test]
+fn test_move_before() {
+    assert_eq!(move_before(&vec![1, 2, 3, 4], 4, 2), vec![1, 4, 2, 3]);
+    assert_eq!(move_before(&vec![1, 2, 3, 4], 1, 4), vec![2, 3, 1, 4]);
+    assert_eq!(move_before(&vec![1, 2, 3], 2, 9), vec![1, 3, 2]);
+}
+
+#[test]
+fn test_sort_manual() {
+    let mut notes = vec![note(1, false), note(2, false), note(3, false)];
+    sort_manual(&mut notes, &vec![3, 1]);
+    assert_eq!(ids(&notes), vec![3, 1, 2]);
+}
+
+#[test]
+fn test_pinned_first() {
+    let notes = vec![note(1, false), note(2, true), note(3, false), note(4, true)];
+    assert_eq!(ids(&pinned_first(&notes)), vec![2, 4, 1, 3]);
+}
@@ -0,0 +1,51 @@
+extern crate theca;
+
+mod common;
+
+use std::cmp::{Ordering};
+
+use theca::{ThecaItem};
+use theca::priority::{parse_priority, cmp_priority, parse_priority_filter, priority_matches};
+
+fn note(id: usize, priority: Option<&str>) -> ThecaItem {
+    ThecaItem {
+        priority: priority.map(|p| p.to_string()),
+        ..common::note(id, &format!("note {}", id), "")
+    }
+}
+
+#[test]
+fn test_parse_priority() {
+    assert_eq!(parse_priority("a").ok().unwrap(), Some("A".to_string()));
+    assert_eq!(parse_priority("02").ok().unwrap(), Some("2".to_string()));
+    assert_eq!(parse_priority("none").ok().unwrap(), None);
+    assert!(parse_priority("AB").is_err());
+    assert!(parse_priority("-1").is_err());
+}
+
+#[test]
+fn test_cmp_priority() {
+    assert_eq!(cmp_priority(&note(1, Some("A")), &note(2, Some("B"))), Ordering::Less);
+    assert_eq!(cmp_priority(&note(1, Some("2")), &note(2, Some("10"))), Ordering::Less);
+    assert_eq!(cmp_priority(&note(1, Some("Z")), &note(2, Some("1"))), Ordering::Less);
+    assert_eq!(cmp_priority(&note(1, None), &note(2, Some("9"))), Ordering::Greater);
+}
+
+#[test]
+fn test_priority_filter() {
+    let filter = parse_priority_filter("A-C").ok().unwrap();
+    assert!(priority_matches(&note(1, Some("B")), &filter));
+    assert!(!priority_matches(&note(1, Some("D")), &filter));
+    assert!(!priority_matches(&note(1, None), &filter));
+    let filter = parse_priority_filter("3-1").ok().unwrap();
+    assert!(priority_matches(&note(1, Some("2")), &filter));
+    assert!(!priority_matches(&note(1, Some("A")), &filter));
+    let filter = parse_priority_filter("A").ok().unwrap();
+    assert!(priority_matches(&note(1, Some("A")), &filter));
+    assert!(!priority_matches(&note(1, Some("B")), &filter));
+    let filter = parse_priority_filter("none").ok().unwrap();
+    assert!(priority_matches(&note(1, None), &filter));
+    assert!(!priority_matches(&note(1, Some("Z")), &filter));
+    assert!(!priority_matches(&note(1, None), &parse_priority_filter("A-none").ok().unwrap()));
+    assert!(parse_priority_filter("A-").is_err());
+}
@@ -0,0 +1,57 @@
+extern crate theca;
+
+use theca::profiles::{parse_note_ref, expand_note_refs};
+
+static USAGE: &'static str = "
+Usage:
+    theca [options] <id>
+    theca [options] edit <id> [<title>] [-b BODY]
+
+Options:
+    -p PROFILE, --profile PROFILE       Specify non-default profile.
+    -l LIMIT, --limit LIMIT             Limit output to LIMIT notes.
+    --due DATE                          Set when the note is due.
+    -c, --condensed                     Condensed printing.
+";
+
+fn argv(args: &[&str]) -> Vec<String> {
+    args.iter().map(|a| a.to_string()).collect()
+}
+
+#[test]
+fn test_parse_note_ref() {
+    assert_eq!(parse_note_ref("work:3"), Some(("work".to_string(), 3)));
+    assert_eq!(parse_note_ref("side-project:12"), Some(("side-project".to_string(), 12)));
+    assert_eq!(parse_note_ref("3"), None);
+    assert_eq!(parse_note_ref("work:"), None);
+    assert_eq!(parse_note_ref("work:three"), None);
+    assert_eq!(parse_note_ref("a note: 3"), None);
+}
+
+#[test]
+fn test_expand_note_refs() {
+    assert_eq!(
+        expand_note_refs(argv(&["theca", "work:3"]), USAGE).ok(),
+        Some(argv(&["theca", "3", "--profile", "work"]))
+    );
+    assert_eq!(
+        expand_note_refs(argv(&["theca", "-c", "edit", "work:3", "-b", "a:1"]), USAGE).ok(),
+        Some(argv(&["theca", "-c", "edit", "3", "-b", "a:1", "--profile", "work"]))
+    );
+    // option values and other arguments are left alone
+    assert_eq!(
+        expand_note_refs(argv(&["theca", "-l", "2:30", "3"]), USAGE).ok(),
+        Some(argv(&["theca", "-l", "2:30", "3"]))
+    );
+    assert_eq!(
+        expand_note_refs(argv(&["theca", "edit", "3", "meeting:1"]), USAGE).ok(),
+        Some(argv(&["theca", "edit", "3", "meeting:1"]))
+    );
+    assert_eq!(
+        expand_note_refs(argv(&["theca", "add", "meeting:1"]), USAGE).ok(),
+        Some(argv(&["theca", "add", "meeting:1"]))
+    );
+    // the profile can only be given once
+    assert!(expand_note_refs(argv(&["theca", "-p", "home", "work:3"]), USAGE).is_err());
+    assert!(expand_note_refs(argv(&["theca", "work:3", "--profile", "home"]), USAGE).is_err());
+}
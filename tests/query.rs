@@ -0,0 +1,133 @@
+extern crate theca;
+extern crate time;
+
+mod common;
+
+use time::{strptime, at};
+
+use theca::{ThecaItem};
+use theca::fields::{Field};
+use theca::query::{parse_query, matches};
+
+fn note(
+    id: usize,
+    title: &str,
+    status: &str,
+    body: &str,
+    last_touched: &str,
+    fields: Vec<(&str, &str, &str)>
+) -> ThecaItem {
+    ThecaItem {
+        status: status.to_string(),
+        last_touched: last_touched.to_string(),
+        fields: match fields.is_empty() {
+            true => None,
+            false => Some(fields.iter().map(|&(n, k, v)| Field {
+                name: n.to_string(),
+                kind: k.to_string(),
+                value: v.to_string()
+            }).collect())
+        },
+        ..common::note(id, title, body)
+    }
+}
+
+fn notes() -> Vec<ThecaItem> {
+    vec![
+        note(1, "deploy the api", "Started", "", "2015-03-01 10:00:00 +0000",
+             vec![("estimate", "duration", "2h"), ("customer", "string", "acme")]),
+        note(2, "write docs", "Started", "remember the rollback steps", "2015-03-09 10:00:00 +0000",
+             vec![("estimate", "duration", "30m")]),
+        note(3, "Deploy the site", "Urgent", "", "2015-02-01 10:00:00 +0000",
+             vec![("points", "int", "8"), ("deadline", "date", "2015-03-01")]),
+        note(4, "groceries", "", "milk, eggs", "2015-03-10 09:00:00 +0000", vec![])
+    ]
+}
+
+fn found(query: &str) -> Vec<usize> {
+    let now = at(strptime("2015-03-10 12:00:00", "%Y-%m-%d %H:%M:%S").unwrap().to_timespec());
+    let parsed = parse_query(query).ok().unwrap().unwrap();
+    notes().iter()
+           .filter(|n| matches(&parsed, *n, false, &now).ok().unwrap())
+           .map(|n| n.id)
+           .collect()
+}
+
+fn error(query: &str) -> String {
+    match parse_query(query) {
+        Ok(_) => panic!("{} should not parse", query),
+        Err(e) => e.desc
+    }
+}
+
+#[test]
+fn test_parse_empty_query() {
+    assert!(parse_query("").ok().unwrap().is_none());
+    assert!(parse_query("   ").ok().unwrap().is_none());
+}
+
+#[test]
+fn test_query_text() {
+    assert_eq!(found("deploy"), vec![1]);
+    assert_eq!(found("title:/(?i)^deploy/"), vec![1, 3]);
+    assert_eq!(found("\"the api\""), vec![1]);
+    assert_eq!(found("body:rollback"), vec![2]);
+    assert_eq!(found("title=groceries"), vec![4]);
+    assert_eq!(found("title!=groceries"), vec![1, 2, 3]);
+}
+
+#[test]
+fn test_query_builtin_keys() {
+    assert_eq!(found("status:started"), vec![1, 2]);
+    assert_eq!(found("status:none"), vec![4]);
+    assert_eq!(found("status!=started"), vec![3, 4]);
+    assert_eq!(found("id:>2"), vec![3, 4]);
+    assert_eq!(found("touched:>7d"), vec![1, 3]);
+    assert_eq!(found("touched:2d"), vec![2, 4]);
+    assert_eq!(found("touched:>=2015-03-09"), vec![2, 4]);
+    assert_eq!(found("touched:2015-03-01"), vec![1]);
+}
+
+#[test]
+fn test_query_operators() {
+    assert_eq!(
+        found("status:started AND (title:/deploy/ OR body:rollback) AND touched:<7d"),
+        vec![2]
+    );
+    assert_eq!(found("status:started AND (title:/deploy/ OR body:rollback)"), vec![1, 2]);
+    assert_eq!(found("status:started title:/deploy/"), vec![1]);
+    assert_eq!(found("NOT status:started"), vec![3, 4]);
+    assert_eq!(found("status:urgent OR NOT (status:started OR id:4)"), vec![3]);
+    // OR binds looser than AND
+    assert_eq!(found("id:1 OR id:2 AND status:urgent"), vec![1]);
+}
+
+#[test]
+fn test_query_fields() {
+    // the old comma separated --where conditions still work
+    assert_eq!(found("estimate>1h"), vec![1]);
+    assert_eq!(found("estimate<=90m"), vec![2]);
+    assert_eq!(found("has:estimate"), vec![1, 2]);
+    assert_eq!(found("has:estimate,customer=acme"), vec![1]);
+    assert_eq!(found("customer:acme"), vec![1]);
+    assert_eq!(found("points>=10"), Vec::<usize>::new());
+    assert_eq!(found("points!=10"), vec![3]);
+    assert_eq!(found("deadline<2015-04-01"), vec![3]);
+    assert_eq!(found("field.deadline<2015-04-01"), vec![3]);
+    assert_eq!(found("field.title:x"), Vec::<usize>::new());
+}
+
+#[test]
+fn test_query_errors() {
+    assert!(error("status:started AND (title:/deploy/").contains("this ( is never closed"));
+    assert!(error("status:started AND (title:/deploy/").ends_with("\n                       ^"));
+    assert!(error("status:sideways").contains("status is one of"));
+    assert!(error("touched:>soon").contains("takes a date"));
+    assert!(error("id:one").contains("id takes a number"));
+    assert!(error("title:").contains("title needs a value"));
+    assert!(error("title:/deploy").contains("never closed"));
+    assert!(error("deploy)").contains("unexpected )"));
+    assert!(error("deploy AND").contains("ends too early"));
+    assert!(error("OR deploy").contains("expected a term"));
+    assert!(error("estimate>/2h/").contains("regex"));
+}
@@ -0,0 +1,58 @@
+extern crate theca;
+extern crate regex;
+
+mod common;
+
+use regex::{Regex};
+
+use theca::replace::{Target, Change, parse_target, replace_text, note_change, diff_lines};
+use common::{note};
+
+fn lines(l: &[&str]) -> Vec<String> {
+    l.iter().map(|s| s.to_string()).collect()
+}
+
+#[test]
+fn test_parse_target() {
+    assert_eq!(parse_target("title").unwrap(), Target::Title);
+    assert_eq!(parse_target("body").unwrap(), Target::Body);
+    assert_eq!(parse_target("both").unwrap(), Target::Both);
+    assert!(parse_target("tags").is_err());
+    assert!(Target::Both.title() && Target::Both.body());
+    assert!(!Target::Title.body() && !Target::Body.title());
+}
+
+#[test]
+fn test_replace_text() {
+    assert_eq!(replace_text("deploy, then deploy", "deploy", None, "ship"), "ship, then ship");
+    assert_eq!(replace_text("a.b", ".", None, "-"), "a-b");
+    let re = Regex::new(r"v(\d+)\.0").unwrap();
+    assert_eq!(replace_text("v1.0 and v2.0", "", Some(&re), "v$1.1"), "v1.1 and v2.1");
+}
+
+#[test]
+fn test_note_change() {
+    let n = note(3, "deploy api", "deploy on friday");
+    assert_eq!(note_change(&n, "deploy", None, "ship", Target::Title), Some(Change {
+        id: 3,
+        title: "ship api".to_string(),
+        body: "deploy on friday".to_string()
+    }));
+    assert_eq!(note_change(&n, "deploy", None, "ship", Target::Both), Some(Change {
+        id: 3,
+        title: "ship api".to_string(),
+        body: "ship on friday".to_string()
+    }));
+    assert_eq!(note_change(&n, "api", None, "web", Target::Body), None);
+    assert_eq!(note_change(&n, "rollback", None, "revert", Target::Both), None);
+    // replacing something with itself doesn't change the note
+    assert_eq!(note_change(&n, "api", None, "api", Target::Both), None);
+}
+
+#[test]
+fn test_diff_lines() {
+    assert_eq!(diff_lines("a\nb\nc", "a\nB\nc"), lines(&["-b", "+B"]));
+    assert_eq!(diff_lines("a\nb", "a\nb"), lines(&[]));
+    assert_eq!(diff_lines("", "new"), lines(&["+new"]));
+    assert_eq!(diff_lines("a\nb\nc", "a b\nc"), lines(&["-a", "-b", "+a b"]));
+}
@@ -0,0 +1,52 @@
+extern crate theca;
+extern crate time;
+
+use theca::schedule::{Schedule, parse_duration, parse_date};
+use theca::utils::{parse_last_touched};
+use time::{strftime};
+
+fn next(spec: &str, from: &str) -> String {
+    let t = parse_last_touched(from).ok().unwrap();
+    let n = Schedule::parse(spec).ok().unwrap().next_after(&t).ok().unwrap();
+    strftime("%F %T", &n).ok().unwrap()
+}
+
+#[test]
+fn test_parse_duration() {
+    assert_eq!(parse_duration("30m").ok().unwrap(), 30*60);
+    assert_eq!(parse_duration("2h").ok().unwrap(), 2*60*60);
+    assert_eq!(parse_duration("1w").ok().unwrap(), 7*24*60*60);
+    assert!(parse_duration("w").is_err());
+    assert!(parse_duration("2 fortnights").is_err());
+}
+
+#[test]
+fn test_parse_schedule() {
+    assert!(Schedule::parse("1w").is_ok());
+    assert!(Schedule::parse("3mo").is_ok());
+    assert!(Schedule::parse("0 9 * * 1").is_ok());
+    assert!(Schedule::parse("*/15 9-17 * * 1-5").is_ok());
+    assert!(Schedule::parse("0d").is_err());
+    assert!(Schedule::parse("61 9 * * 1").is_err());
+    assert!(Schedule::parse("0 9 * *").is_err());
+}
+
+#[test]
+fn test_next_after() {
+    let from = "2015-01-22 19:43:24 -0800";
+    let local = strftime("%F %T", &parse_last_touched(from).ok().unwrap()).ok().unwrap();
+    // fixed intervals keep the time of day
+    assert!(next("1d", from).ends_with(&local[10..]));
+    // cron lines fire on whole minutes
+    assert!(next("*/15 * * * *", from).ends_with(":00"));
+    assert!(next("0 9 * * *", from).ends_with("09:00:00"));
+    // the 13th or a friday, not only a friday the 13th (february)
+    assert!(next("0 9 13 * 5", from).starts_with("2015-01-"));
+}
+
+#[test]
+fn test_parse_date() {
+    assert!(parse_date("2015-03-01").ok().unwrap().starts_with("2015-03-01 00:00:00"));
+    assert!(parse_date("3d").is_ok());
+    assert!(parse_date("someday").is_err());
+}
@@ -0,0 +1,47 @@
+extern crate theca;
+
+use theca::select::{Term, parse_spec, is_bulk, select_ids};
+
+fn specs(s: &[&str]) -> Vec<String> {
+    s.iter().map(|a| a.to_string()).collect()
+}
+
+#[test]
+fn test_parse_spec() {
+    assert_eq!(parse_spec("12").unwrap(), vec![Term {exclude: false, from: 12, to: 12}]);
+    assert_eq!(parse_spec("3-9,12,!5").unwrap(), vec![
+        Term {exclude: false, from: 3, to: 9},
+        Term {exclude: false, from: 12, to: 12},
+        Term {exclude: true, from: 5, to: 5}
+    ]);
+    assert_eq!(parse_spec("!2-4").unwrap(), vec![Term {exclude: true, from: 2, to: 4}]);
+    assert!(parse_spec("9-3").is_err());
+    assert!(parse_spec("3-").is_err());
+    assert!(parse_spec("3,,4").is_err());
+    assert!(parse_spec("deploy").is_err());
+}
+
+#[test]
+fn test_is_bulk() {
+    assert!(!is_bulk(&specs(&["3", "4,5"])).unwrap());
+    assert!(is_bulk(&specs(&["3", "4-6"])).unwrap());
+    assert!(is_bulk(&specs(&["3,!4"])).unwrap());
+    assert!(is_bulk(&specs(&["x"])).is_err());
+}
+
+#[test]
+fn test_select_ids() {
+    let existing = vec![1, 3, 4, 5, 6, 9, 12];
+    assert_eq!(select_ids(&specs(&["3-9,12,!5"]), &existing, None).unwrap(), vec![3, 4, 6, 9, 12]);
+    // single ids are kept even if the note doesn't exist, ranges aren't
+    assert_eq!(select_ids(&specs(&["7", "1-2", "3"]), &existing, None).unwrap(), vec![7, 1, 3]);
+    assert_eq!(select_ids(&specs(&["4", "4,3-4"]), &existing, None).unwrap(), vec![4, 3]);
+    // a query narrows the ids down, or is the selection without any
+    let matched = vec![4, 9, 12];
+    assert_eq!(select_ids(&specs(&["1-9"]), &existing, Some(&matched)).unwrap(), vec![4, 9]);
+    assert_eq!(select_ids(&specs(&["!9"]), &existing, Some(&matched)).unwrap(), vec![4, 12]);
+    assert_eq!(select_ids(&specs(&[]), &existing, Some(&matched)).unwrap(), vec![4, 9, 12]);
+    assert!(select_ids(&specs(&["!9"]), &existing, None).is_err());
+    assert!(select_ids(&specs(&["3,!3"]), &existing, None).is_err());
+    assert!(select_ids(&specs(&["13-20"]), &existing, None).is_err());
+}
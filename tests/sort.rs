@@ -0,0 +1,93 @@
+extern crate theca;
+
+mod common;
+
+use std::cmp::Ordering::{Less, Equal, Greater};
+
+use theca::{ThecaItem};
+use theca::fields::{Field};
+use theca::sort::{SortKey, parse_sort, collate, sort_by_keys};
+use common::{note};
+
+fn ids(notes: &Vec<ThecaItem>) -> Vec<usize> {
+    notes.iter().map(|n| n.id).collect()
+}
+
+fn sorted(mut notes: Vec<ThecaItem>, sort: &str) -> Vec<usize> {
+    sort_by_keys(&mut notes, &parse_sort(sort).unwrap());
+    ids(&notes)
+}
+
+#[test]
+fn test_parse_sort() {
+    assert_eq!(parse_sort("status, -due,title").unwrap(), vec![
+        SortKey {key: "status".to_string(), descending: false},
+        SortKey {key: "due".to_string(), descending: true},
+        SortKey {key: "title".to_string(), descending: false}
+    ]);
+    assert_eq!(parse_sort("-last_touched").unwrap(), vec![
+        SortKey {key: "touched".to_string(), descending: true}
+    ]);
+    assert_eq!(parse_sort("").unwrap(), vec![]);
+    assert!(parse_sort("status,due date").is_err());
+    assert!(parse_sort("--due").is_err());
+}
+
+#[test]
+fn test_collate() {
+    assert_eq!(collate("apple", "Banana"), Less);
+    assert_eq!(collate("éclair", "eclairs"), Less);
+    assert_eq!(collate("Éclair", "zebra"), Less);
+    assert_eq!(collate("note 2", "note 10"), Less);
+    assert_eq!(collate("note 007", "note 7"), Less);
+    assert_eq!(collate("Straße", "strasse"), Greater);
+    assert_eq!(collate("same", "same"), Equal);
+    // text that only differs by case or accents still has an order
+    assert_eq!(collate("Deploy", "deploy"), Less);
+    assert_eq!(collate("resume", "résumé"), Less);
+}
+
+#[test]
+fn test_sort_by_keys() {
+    let mut a = note(1, "write docs", "");
+    a.status = "Started".to_string();
+    a.due = Some("2015-03-01 00:00:00 +0000".to_string());
+    let mut b = note(2, "Éclairs", "");
+    b.status = "Urgent".to_string();
+    let mut c = note(3, "deploy", "");
+    c.status = "Started".to_string();
+    // earlier than a once the offsets are taken into account
+    c.due = Some("2015-03-01 01:00:00 +0200".to_string());
+    let mut d = note(4, "apples", "");
+    d.status = "Done".to_string();
+    let e = note(5, "bananas", "");
+    let notes = vec![a, b, c, d, e];
+    assert_eq!(sorted(notes.clone(), "title"), vec![4, 5, 3, 2, 1]);
+    assert_eq!(sorted(notes.clone(), "-title"), vec![1, 2, 3, 5, 4]);
+    // notes without a due date go last either way
+    assert_eq!(sorted(notes.clone(), "due"), vec![3, 1, 2, 4, 5]);
+    assert_eq!(sorted(notes.clone(), "-due"), vec![1, 3, 2, 4, 5]);
+    assert_eq!(sorted(notes.clone(), "status,-due,title"), vec![2, 1, 3, 5, 4]);
+    // equal notes keep the order they were in
+    assert_eq!(sorted(notes.clone(), "body"), vec![1, 2, 3, 4, 5]);
+    assert_eq!(sorted(notes.clone(), ""), vec![1, 2, 3, 4, 5]);
+}
+
+#[test]
+fn test_sort_by_custom_field() {
+    let estimate = |v: &str| Some(vec![Field {
+        name: "estimate".to_string(),
+        kind: "duration".to_string(),
+        value: v.to_string()
+    }]);
+    let mut a = note(1, "a", "");
+    a.fields = estimate("1d");
+    let b = note(2, "b", "");
+    let mut c = note(3, "c", "");
+    c.fields = estimate("2h");
+    let mut d = note(4, "d", "");
+    d.fields = estimate("30m");
+    let notes = vec![a, b, c, d];
+    assert_eq!(sorted(notes.clone(), "estimate"), vec![4, 3, 1, 2]);
+    assert_eq!(sorted(notes.clone(), "-estimate,title"), vec![1, 3, 4, 2]);
+}
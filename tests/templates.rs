@@ -0,0 +1,68 @@
+extern crate theca;
+
+use theca::errors::{ThecaError};
+use theca::templates::{expand, parse_template, initial_body, template_file};
+use std::path::{Path};
+
+fn no_prompt(_: &str) -> Result<String, ThecaError> {
+    Ok("".to_string())
+}
+
+#[test]
+fn test_expand_placeholders() {
+    assert_eq!(
+        expand("{{date}} on {{ profile }}", "2015-03-01", "work", no_prompt).unwrap(),
+        "2015-03-01 on work".to_string()
+    );
+    assert_eq!(expand("no placeholders", "", "", no_prompt).unwrap(), "no placeholders".to_string());
+    assert!(expand("{{nope}}", "", "", no_prompt).is_err());
+    assert!(expand("{{date:x}}", "", "", no_prompt).is_err());
+}
+
+#[test]
+fn test_expand_prompts_once_per_question() {
+    let mut asked: Vec<String> = vec![];
+    let expanded = expand(
+        "{{prompt:Severity}} / {{prompt: Severity}} / {{prompt:Area}}",
+        "",
+        "",
+        |q| {asked.push(q.to_string()); Ok(format!("<{}>", q))}
+    ).unwrap();
+    assert_eq!(expanded, "<Severity> / <Severity> / <Area>".to_string());
+    assert_eq!(asked, vec!["Severity".to_string(), "Area".to_string()]);
+}
+
+#[test]
+fn test_parse_template() {
+    let t = parse_template(
+        "---\nstatus: Urgent\npriority: A\nset: severity=high\nset: estimate=2h\ntags: bug, #triage\n---\nsteps:\n"
+    ).unwrap();
+    assert_eq!(t.status, "Urgent".to_string());
+    assert_eq!(t.priority, "A".to_string());
+    assert_eq!(t.fields, vec!["severity=high".to_string(), "estimate=2h".to_string()]);
+    assert_eq!(t.tags, vec!["bug".to_string(), "triage".to_string()]);
+    assert_eq!(t.body, "steps:".to_string());
+    assert_eq!(initial_body(&t), "steps:\n\n#bug #triage".to_string());
+
+    // no header means the whole thing is the body, empty header values are ignored
+    let t = parse_template("just a body").unwrap();
+    assert_eq!(t.body, "just a body".to_string());
+    let t = parse_template("---\nstatus: \ntags: \n---\n").unwrap();
+    assert_eq!(t.status, "".to_string());
+    assert_eq!(initial_body(&t), "".to_string());
+
+    assert!(parse_template("---\nstatus: started\n").is_err());
+    assert!(parse_template("---\nstatus: sideways\n---\n").is_err());
+    assert!(parse_template("---\ncolour: red\n---\n").is_err());
+}
+
+#[test]
+fn test_template_file() {
+    let templates = Path::new("/tmp/default.templates");
+    assert_eq!(
+        template_file(&templates, &"bug".to_string()).unwrap(),
+        templates.join("bug.md")
+    );
+    assert!(template_file(&templates, &"../bug".to_string()).is_err());
+    assert!(template_file(&templates, &"".to_string()).is_err());
+}
@@ -0,0 +1,78 @@
+extern crate theca;
+extern crate time;
+
+mod common;
+
+use theca::{ThecaItem};
+use theca::timetrack::{TimeEntry, running, logged, tags, timesheet, week_start,
+                       format_duration, csv_field};
+use theca::utils::{parse_last_touched};
+
+fn note(id: usize, title: &str, log: Vec<(&str, Option<&str>)>) -> ThecaItem {
+    ThecaItem {
+        status: "Started".to_string(),
+        time_log: Some(log.iter().map(|&(start, stop)| TimeEntry {
+            start: start.to_string(),
+            stop: stop.map(|s| s.to_string())
+        }).collect()),
+        ..common::note(id, title, "")
+    }
+}
+
+#[test]
+fn test_logged() {
+    let n = note(1, "deploy", vec![
+        ("2015-01-20 09:00:00 -0800", Some("2015-01-20 10:30:00 -0800")),
+        ("2015-01-22 09:00:00 -0800", None)
+    ]);
+    let to = parse_last_touched("2015-01-22 10:00:00 -0800").ok().unwrap();
+    let from = parse_last_touched("2015-01-20 10:00:00 -0800").ok().unwrap();
+    assert!(running(&n));
+    assert_eq!(logged(&n, None, &to).ok().unwrap(), (90 + 60) * 60);
+    assert_eq!(logged(&n, Some(&from), &to).ok().unwrap(), (30 + 60) * 60);
+}
+
+#[test]
+fn test_tags() {
+    let mut n = note(1, "#Deploy the thing, see #12", vec![]);
+    n.body = "more #ops work and #deploy again".to_string();
+    assert_eq!(tags(&n), vec!["deploy".to_string(), "ops".to_string()]);
+}
+
+#[test]
+fn test_timesheet() {
+    let notes = vec![
+        note(1, "#ops one", vec![("2015-01-20 09:00:00 -0800", Some("2015-01-20 10:00:00 -0800"))]),
+        note(2, "#ops two", vec![("2015-01-20 11:00:00 -0800", Some("2015-01-20 13:00:00 -0800"))]),
+        note(3, "nothing", vec![])
+    ];
+    let to = parse_last_touched("2015-01-22 10:00:00 -0800").ok().unwrap();
+    let sheet = timesheet(&notes, None, &to).ok().unwrap();
+    assert_eq!(sheet.notes.len(), 2);
+    assert_eq!(sheet.notes[0].id, 2);
+    assert_eq!(sheet.tags.len(), 1);
+    assert_eq!(sheet.tags[0].seconds, 3 * 60 * 60);
+    assert_eq!(sheet.total, 3 * 60 * 60);
+}
+
+#[test]
+fn test_week_start() {
+    let t = parse_last_touched("2015-01-22 19:43:24 -0800").ok().unwrap();
+    let monday = week_start(&t);
+    assert_eq!(monday.tm_wday, 1);
+    assert_eq!((monday.tm_hour, monday.tm_min, monday.tm_sec), (0, 0, 0));
+    assert!(monday.to_timespec() <= t.to_timespec());
+}
+
+#[test]
+fn test_format_duration() {
+    assert_eq!(format_duration(59), "0m".to_string());
+    assert_eq!(format_duration(45 * 60), "45m".to_string());
+    assert_eq!(format_duration(65 * 60), "1h 05m".to_string());
+}
+
+#[test]
+fn test_csv_field() {
+    assert_eq!(csv_field("plain"), "plain".to_string());
+    assert_eq!(csv_field("a, \"b\""), "\"a, \"\"b\"\"\"".to_string());
+}
@@ -0,0 +1,44 @@
+extern crate theca;
+
+mod common;
+
+use theca::{ThecaItem};
+use theca::tree::{descendants, would_cycle, tree_order};
+
+fn note(id: usize, parent: Option<usize>) -> ThecaItem {
+    ThecaItem {
+        parent: parent,
+        ..common::note(id, &format!("note {}", id), "")
+    }
+}
+
+#[test]
+fn test_descendants() {
+    let notes = vec![note(1, None), note(2, Some(1)), note(3, Some(2)), note(4, None)];
+    assert_eq!(descendants(&notes, 1), vec![2, 3]);
+    assert_eq!(descendants(&notes, 2), vec![3]);
+    assert!(descendants(&notes, 4).is_empty());
+}
+
+#[test]
+fn test_would_cycle() {
+    let notes = vec![note(1, None), note(2, Some(1)), note(3, Some(2))];
+    assert!(would_cycle(&notes, 1, 1));
+    assert!(would_cycle(&notes, 1, 3));
+    assert!(!would_cycle(&notes, 3, 1));
+}
+
+#[test]
+fn test_tree_order() {
+    let notes = vec![note(1, None), note(2, None), note(3, Some(1)), note(4, Some(3))];
+    let ordered = tree_order(&notes, false);
+    let ids: Vec<usize> = ordered.iter().map(|n| n.id).collect();
+    assert_eq!(ids, vec![1, 3, 4, 2]);
+    assert_eq!(ordered[1].title, "  note 3".to_string());
+    assert_eq!(ordered[2].title, "    note 4".to_string());
+
+    let collapsed = tree_order(&notes, true);
+    assert_eq!(collapsed.len(), 2);
+    assert_eq!(collapsed[0].title, "note 1 [+2]".to_string());
+    assert_eq!(collapsed[1].title, "note 2".to_string());
+}
@@ -1,6 +1,13 @@
 extern crate theca;
+extern crate time;
 
-use theca::utils::{cmp_last_touched, format_field};
+mod common;
+
+use theca::{ThecaItem};
+use theca::utils::{cmp_last_touched, format_field, relative_last_touched_string,
+                   filter_status_priority};
+use common::{note};
+use time::{now, strftime};
 use std::cmp::Ordering;
 
 #[test]
@@ -34,3 +41,35 @@ fn test_cmp_last_touched() {
     assert_eq!(cmp_last_touched(old, new).ok().unwrap(), Ordering::Less);
     assert_eq!(cmp_last_touched(new, old).ok().unwrap(), Ordering::Greater);
 }
+
+#[test]
+fn test_relative_last_touched_string() {
+    let old = "2015-01-22 19:43:24 -0800";
+    let new = strftime("%F %T %z", &now()).ok().unwrap();
+
+    assert!(relative_last_touched_string(old).is_ok());
+    assert!(relative_last_touched_string(old).ok().unwrap().ends_with("y ago"));
+    assert_eq!(
+        relative_last_touched_string(&*new).ok().unwrap(),
+        "just now".to_string()
+    );
+}
+
+#[test]
+fn test_filter_status_priority() {
+    let notes = vec![
+        ThecaItem {status: "Started".to_string(), ..note(1, "deploy", "")},
+        ThecaItem {priority: Some("A".to_string()), ..note(2, "docs", "")},
+        ThecaItem {status: "Urgent".to_string(), ..note(3, "fix", "")}
+    ];
+    let ids = |no: bool, started: bool, urgent: bool, priority: &str| -> Vec<usize> {
+        let mut kept = notes.clone();
+        filter_status_priority(&mut kept, no, started, urgent, &priority.to_string()).ok().unwrap();
+        kept.iter().map(|n| n.id).collect()
+    };
+    assert_eq!(ids(false, false, false, ""), vec![1, 2, 3]);
+    assert_eq!(ids(false, true, false, ""), vec![1]);
+    assert_eq!(ids(true, false, false, ""), vec![2]);
+    assert_eq!(ids(false, false, false, "A"), vec![2]);
+    assert_eq!(ids(false, true, false, "A"), Vec::<usize>::new());
+}
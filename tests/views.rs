@@ -0,0 +1,56 @@
+extern crate theca;
+
+use theca::views::{View, check_name, set_view, view_argv, quote_view_args};
+
+fn argv(args: &[&str]) -> Vec<String> {
+    args.iter().map(|a| a.to_string()).collect()
+}
+
+#[test]
+fn test_check_name() {
+    assert!(check_name("today").is_ok());
+    assert!(check_name("deploys_2-b").is_ok());
+    assert!(check_name("").is_err());
+    assert!(check_name("a view").is_err());
+    assert!(check_name("work:3").is_err());
+    assert!(check_name("save").is_err());
+    assert!(check_name("remove").is_err());
+}
+
+#[test]
+fn test_set_view() {
+    let mut views = vec![];
+    assert!(!set_view(&mut views, "today", &argv(&["-d", "-l", "10"])));
+    assert!(!set_view(&mut views, "deploys", &argv(&["search", "deploy"])));
+    assert!(set_view(&mut views, "today", &argv(&["-r"])));
+    assert_eq!(views, vec![
+        View {name: "deploys".to_string(), args: argv(&["search", "deploy"])},
+        View {name: "today".to_string(), args: argv(&["-r"])}
+    ]);
+}
+
+#[test]
+fn test_view_argv() {
+    assert_eq!(view_argv(&argv(&["-d", "search", "x"])), argv(&["theca", "-d", "search", "x"]));
+    assert_eq!(view_argv(&vec![]), argv(&["theca"]));
+}
+
+#[test]
+fn test_quote_view_args() {
+    assert_eq!(
+        quote_view_args(argv(&["theca", "view", "save", "today", "-d", "-l", "10"])),
+        argv(&["theca", "view", "save", "today", "--", "-d", "-l", "10"])
+    );
+    assert_eq!(
+        quote_view_args(argv(&["theca", "-p", "work", "view", "save", "today", "--", "-d"])),
+        argv(&["theca", "-p", "work", "view", "save", "today", "--", "-d"])
+    );
+    assert_eq!(
+        quote_view_args(argv(&["theca", "view", "today"])),
+        argv(&["theca", "view", "today"])
+    );
+    assert_eq!(
+        quote_view_args(argv(&["theca", "view", "save", "today"])),
+        argv(&["theca", "view", "save", "today"])
+    );
+}
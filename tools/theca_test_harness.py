@@ -42,7 +42,7 @@ TEXT_OUTPUT_TESTS = [
 
 THECA_CMD = "theca"
 
-STATUSES = ["", "Started", "Urgent"]
+STATUSES = ["", "Started", "Urgent", "Done"]
 DATEFMT = "%Y-%m-%d %H:%M:%S %z"
 SCHEMA_PATH = "docs/schema.json"
 